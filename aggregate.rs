@@ -0,0 +1,155 @@
+// Agregación de precios de varias fuentes: mediana, descarte de valores atípicos y dispersión entre exchanges
+use rust_decimal::prelude::*;
+
+use crate::prices::Quote;
+
+// Precio de una fuente y su desviación respecto a la mediana, en porcentaje
+#[derive(Debug, Clone)]
+pub struct SourcePrice {
+    pub source: &'static str,
+    pub price: Decimal,
+    pub deviation_pct: Decimal,
+}
+
+// Resultado de la agregación
+#[derive(Debug, Clone)]
+pub struct Aggregate {
+    pub median: Decimal,
+    // Fuentes que se han tenido en cuenta para la mediana
+    pub used: Vec<SourcePrice>,
+    // Fuentes descartadas por desviarse demasiado
+    pub outliers: Vec<SourcePrice>,
+    // Diferencia entre el precio más alto y el más bajo de las fuentes usadas
+    pub spread: Decimal,
+    pub spread_pct: Decimal,
+}
+
+// Mediana de una lista de precios (media de los dos centrales si hay un número par)
+pub fn median(prices: &[Decimal]) -> Option<Decimal> {
+    if prices.is_empty() {
+        return None;
+    }
+    let mut sorted = prices.to_vec();
+    sorted.sort();
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        Some((sorted[mid - 1] + sorted[mid]) / Decimal::TWO)
+    } else {
+        Some(sorted[mid])
+    }
+}
+
+// Desviación porcentual de un precio respecto a una referencia
+fn deviation_pct(price: Decimal, reference: Decimal) -> Decimal {
+    if reference.is_zero() {
+        return Decimal::ZERO;
+    }
+    ((price - reference) / reference * Decimal::ONE_HUNDRED).round_dp(2)
+}
+
+// Calcula la mediana descartando las fuentes que se alejan más de max_deviation_pct de la mediana inicial.
+// Devuelve None si no hay ningún precio.
+pub fn aggregate(quotes: &[Quote], max_deviation_pct: Decimal) -> Option<Aggregate> {
+    let prices: Vec<Decimal> = quotes.iter().map(|q| q.price).collect();
+    let first_median = median(&prices)?;
+
+    let (kept, outliers): (Vec<&Quote>, Vec<&Quote>) = quotes.iter()
+        .partition(|q| deviation_pct(q.price, first_median).abs() <= max_deviation_pct);
+
+    // Con la mediana inicial siempre queda al menos un precio, salvo que todos estén a la misma distancia
+    // en direcciones opuestas; en ese caso no hay criterio para descartar y se usan todos
+    let (kept, outliers) = if kept.is_empty() { (quotes.iter().collect(), Vec::new()) } else { (kept, outliers) };
+
+    let kept_prices: Vec<Decimal> = kept.iter().map(|q| q.price).collect();
+    let median = median(&kept_prices)?;
+    let min = kept_prices.iter().copied().min()?;
+    let max = kept_prices.iter().copied().max()?;
+    let spread = max - min;
+
+    let describe = |quotes: Vec<&Quote>| quotes.into_iter()
+        .map(|q| SourcePrice { source: q.source, price: q.price, deviation_pct: deviation_pct(q.price, median) })
+        .collect::<Vec<_>>();
+
+    Some(Aggregate {
+        median,
+        used: describe(kept),
+        outliers: describe(outliers),
+        spread,
+        spread_pct: if median.is_zero() { Decimal::ZERO } else { (spread / median * Decimal::ONE_HUNDRED).round_dp(2) },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::Pair;
+
+    fn d(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn quotes(prices: &[(&'static str, &str)]) -> Vec<Quote> {
+        prices.iter()
+            .map(|(source, price)| Quote { pair: Pair::new("BTC", "USDT"), price: d(price), source })
+            .collect()
+    }
+
+    fn sources(prices: &[SourcePrice]) -> Vec<&'static str> {
+        prices.iter().map(|p| p.source).collect()
+    }
+
+    #[test]
+    fn median_of_odd_and_even_counts() {
+        assert_eq!(median(&[d("102"), d("100"), d("101")]), Some(d("101")));
+        assert_eq!(median(&[d("103"), d("100"), d("102"), d("101")]), Some(d("101.5")));
+        assert_eq!(median(&[d("7")]), Some(d("7")));
+        assert_eq!(median(&[]), None);
+    }
+
+    #[test]
+    fn aggregates_close_prices_without_discarding_any() {
+        let result = aggregate(&quotes(&[("Binance", "100"), ("Kraken", "102"), ("Coinbase", "101")]), d("2")).unwrap();
+        assert_eq!(result.median, d("101"));
+        assert_eq!(sources(&result.used), vec!["Binance", "Kraken", "Coinbase"]);
+        assert!(result.outliers.is_empty());
+        assert_eq!((result.spread, result.spread_pct), (d("2"), d("1.98")));
+        assert_eq!(result.used[0].deviation_pct, d("-0.99"));
+
+        let even = aggregate(&quotes(&[("Binance", "100"), ("Kraken", "101")]), d("2")).unwrap();
+        assert_eq!(even.median, d("100.5"));
+    }
+
+    #[test]
+    fn discards_sources_beyond_the_maximum_deviation() {
+        let result = aggregate(&quotes(&[
+            ("Binance", "100"), ("Kraken", "101"), ("Coinbase", "102"), ("CoinGecko", "120"),
+        ]), d("2")).unwrap();
+        // La mediana inicial es 101.5; CoinGecko se aleja un 18 % y no cuenta para la final
+        assert_eq!(result.median, d("101"));
+        assert_eq!(sources(&result.used), vec!["Binance", "Kraken", "Coinbase"]);
+        assert_eq!(sources(&result.outliers), vec!["CoinGecko"]);
+        assert_eq!(result.outliers[0].deviation_pct, d("18.81"));
+        assert_eq!(result.spread, d("2"));
+    }
+
+    #[test]
+    fn keeps_sources_exactly_at_the_maximum_deviation() {
+        let result = aggregate(&quotes(&[("Binance", "98"), ("Kraken", "100"), ("Coinbase", "102")]), d("2")).unwrap();
+        assert_eq!(result.used.len(), 3);
+        assert!(result.outliers.is_empty());
+    }
+
+    #[test]
+    fn uses_every_source_when_none_is_close_to_the_median() {
+        // Dos fuentes muy separadas: la mediana queda en medio y no hay criterio para descartar ninguna
+        let result = aggregate(&quotes(&[("Binance", "100"), ("Kraken", "200")]), d("2")).unwrap();
+        assert_eq!(result.median, d("150"));
+        assert_eq!(result.used.len(), 2);
+        assert!(result.outliers.is_empty());
+    }
+
+    #[test]
+    fn no_prices_means_no_aggregate() {
+        assert!(aggregate(&[], d("2")).is_none());
+    }
+}
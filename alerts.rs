@@ -0,0 +1,534 @@
+// Alertas de precio por chat: "/alert btcusdt above 70000" avisa cuando el precio cruza el umbral y
+// "/movealert btc 5% 1h" cuando se mueve más de un porcentaje dentro de una ventana de tiempo.
+// Una tarea en segundo plano consulta los precios periódicamente y envía el aviso una sola vez por cruce.
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rust_decimal::Decimal;
+use teloxide::prelude::*;
+
+use crate::history::{self, PriceHistory, WindowRange};
+use crate::i18n::{self, Locale};
+use crate::prices::{format_price, PriceService, Quote};
+use crate::providers::Pair;
+use crate::storage::{NewAlert, Storage, StorageResult};
+
+// Sentido del cruce que se vigila
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Above,
+    Below,
+}
+
+impl Direction {
+    // Indica si el precio cumple la condición de la alerta
+    fn is_met(self, price: Decimal, threshold: Decimal) -> bool {
+        match self {
+            Direction::Above => price >= threshold,
+            Direction::Below => price <= threshold,
+        }
+    }
+
+    // Lado del umbral en el que está el precio, para las respuestas ("above" / "por encima")
+    pub fn side(self, locale: Locale) -> String {
+        match self {
+            Direction::Above => i18n::t(locale, "alert.side.above", &[]),
+            Direction::Below => i18n::t(locale, "alert.side.below", &[]),
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Direction::Above => write!(f, "above"),
+            Direction::Below => write!(f, "below"),
+        }
+    }
+}
+
+impl FromStr for Direction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "above" | ">" => Ok(Direction::Above),
+            "below" | "<" => Ok(Direction::Below),
+            other => Err(format!("'{}' is not a direction, use above or below", other)),
+        }
+    }
+}
+
+// Condición que dispara una alerta
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    // El precio cruza un umbral fijo
+    Threshold { direction: Direction, threshold: Decimal },
+    // El precio se mueve más de `percent` % dentro de la ventana `window`
+    Move { percent: Decimal, window: Duration },
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Condition::Threshold { direction, threshold } => write!(f, "{} {}", direction, format_price(*threshold)),
+            Condition::Move { percent, window } => write!(f, "moves {}% within {}", percent, history::format_window(*window)),
+        }
+    }
+}
+
+impl Condition {
+    // Como Display pero en el idioma del chat; Display queda para los logs
+    pub fn describe(&self, locale: Locale) -> String {
+        match self {
+            Condition::Threshold { direction: Direction::Above, threshold } =>
+                i18n::t(locale, "alert.above", &[("price", &i18n::price(locale, *threshold))]),
+            Condition::Threshold { direction: Direction::Below, threshold } =>
+                i18n::t(locale, "alert.below", &[("price", &i18n::price(locale, *threshold))]),
+            Condition::Move { percent, window } => i18n::t(locale, "alert.moves", &[
+                ("percent", &i18n::amount(locale, *percent)),
+                ("window", &history::format_window(*window)),
+            ]),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PriceAlert {
+    pub id: u64,
+    pub chat_id: ChatId,
+    pub pair: Pair,
+    pub condition: Condition,
+    // La alerta solo se dispara estando armada; se desarma al dispararse y se vuelve a armar
+    // cuando el precio vuelve al otro lado del umbral (o cuando el movimiento se calma),
+    // para avisar una sola vez por cruce
+    pub armed: bool,
+}
+
+impl fmt::Display for PriceAlert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{} {} {}", self.id, self.pair, self.condition)
+    }
+}
+
+impl PriceAlert {
+    pub fn describe(&self, locale: Locale) -> String {
+        i18n::t(locale, "alert.describe", &[("id", &self.id), ("pair", &self.pair), ("condition", &self.condition.describe(locale))])
+    }
+}
+
+// Registro de alertas compartido entre los comandos y la tarea de vigilancia.
+// Se mantiene una copia en memoria para las comprobaciones y cada cambio se guarda en el almacenamiento.
+pub struct Alerts {
+    storage: Arc<dyn Storage>,
+    alerts: Mutex<Vec<PriceAlert>>,
+    // Histéresis de las alertas de movimiento: tras dispararse no se vuelven a armar hasta que
+    // el movimiento baja de esta fracción del porcentaje configurado
+    rearm_ratio: Decimal,
+    // Cada cuánto se muestrean los precios; una ventana más corta solo contendría una muestra
+    sample_interval: Duration,
+}
+
+impl Alerts {
+    // Carga las alertas guardadas en el almacenamiento
+    pub fn load(storage: Arc<dyn Storage>, rearm_ratio: Decimal, sample_interval: Duration) -> StorageResult<Self> {
+        let alerts = storage.alerts()?;
+        log::info!("Loaded {} price alerts", alerts.len());
+        Ok(Alerts { storage, alerts: Mutex::new(alerts), rearm_ratio, sample_interval })
+    }
+
+    // Ventana mínima de las alertas de movimiento
+    pub fn sample_interval(&self) -> Duration {
+        self.sample_interval
+    }
+
+    fn insert(&self, chat_id: ChatId, pair: Pair, condition: Condition, armed: bool) -> StorageResult<PriceAlert> {
+        let alert = self.storage.insert_alert(NewAlert { chat_id, pair, condition, armed })?;
+        self.alerts.lock().unwrap().push(alert.clone());
+        Ok(alert)
+    }
+
+    // Registra una alerta de umbral. Si la condición ya se cumple con el precio actual, se deja desarmada
+    // y solo avisará después de que el precio vuelva a cruzar el umbral.
+    pub fn add(&self, chat_id: ChatId, pair: Pair, direction: Direction, threshold: Decimal, current: Decimal) -> StorageResult<PriceAlert> {
+        let armed = !direction.is_met(current, threshold);
+        self.insert(chat_id, pair, Condition::Threshold { direction, threshold }, armed)
+    }
+
+    // Registra una alerta de movimiento porcentual dentro de una ventana de tiempo
+    pub fn add_move(&self, chat_id: ChatId, pair: Pair, percent: Decimal, window: Duration) -> StorageResult<PriceAlert> {
+        self.insert(chat_id, pair, Condition::Move { percent, window }, true)
+    }
+
+    // Alertas registradas en un chat
+    pub fn list(&self, chat_id: ChatId) -> Vec<PriceAlert> {
+        self.alerts.lock().unwrap().iter()
+            .filter(|a| a.chat_id == chat_id)
+            .cloned()
+            .collect()
+    }
+
+    // Elimina una alerta del chat; devuelve false si no existe en ese chat
+    pub fn remove(&self, chat_id: ChatId, id: u64) -> StorageResult<bool> {
+        if !self.storage.delete_alert(chat_id, id)? {
+            return Ok(false);
+        }
+        self.alerts.lock().unwrap().retain(|a| a.id != id);
+        Ok(true)
+    }
+
+    // Idioma de los avisos de un chat; sin nadie que escriba solo cuenta el elegido con /lang
+    fn locale(&self, chat_id: ChatId) -> Locale {
+        i18n::locale_for(self.storage.as_ref(), chat_id, None)
+    }
+
+    // Guarda el nuevo estado de armado de las alertas que cambiaron. Se llama ya sin el candado para no
+    // bloquear los comandos mientras se escribe; un fallo aquí solo se registra en el log, como mucho
+    // provoca un aviso repetido tras un reinicio
+    fn persist_armed(&self, changed: &[(u64, bool)]) {
+        for &(id, armed) in changed {
+            if let Err(err) = self.storage.set_alert_armed(id, armed) {
+                log::error!("Could not save state of alert #{}: {}", id, err);
+            }
+        }
+    }
+
+    // Pares distintos con alertas que cumplen el filtro
+    fn pairs_where(&self, filter: impl Fn(&Condition) -> bool) -> Vec<Pair> {
+        let mut pairs: Vec<Pair> = self.alerts.lock().unwrap().iter()
+            .filter(|a| filter(&a.condition))
+            .map(|a| a.pair.clone())
+            .collect();
+        pairs.sort();
+        pairs.dedup();
+        pairs
+    }
+
+    // Pares con cualquier alerta
+    pub fn pairs(&self) -> Vec<Pair> {
+        self.pairs_where(|_| true)
+    }
+
+    // Pares con alertas de umbral, que se consultan en cada vuelta del vigilante
+    fn threshold_pairs(&self) -> Vec<Pair> {
+        self.pairs_where(|c| matches!(c, Condition::Threshold { .. }))
+    }
+
+    // Pares con alertas de movimiento, que la tarea de muestreo guarda en el historial
+    pub fn move_pairs(&self) -> Vec<Pair> {
+        self.pairs_where(|c| matches!(c, Condition::Move { .. }))
+    }
+
+    // Evalúa las alertas de umbral de un par con el último precio y devuelve las que se disparan
+    fn check_thresholds(&self, pair: &Pair, price: Decimal) -> Vec<PriceAlert> {
+        let mut fired = Vec::new();
+        let mut changed = Vec::new();
+        for alert in self.alerts.lock().unwrap().iter_mut().filter(|a| &a.pair == pair) {
+            let Condition::Threshold { direction, threshold } = alert.condition else {
+                continue;
+            };
+            let met = direction.is_met(price, threshold);
+            if alert.armed && met {
+                alert.armed = false;
+                changed.push((alert.id, false));
+                fired.push(alert.clone());
+            } else if !alert.armed && !met {
+                alert.armed = true;
+                changed.push((alert.id, true));
+            }
+        }
+        self.persist_armed(&changed);
+        fired
+    }
+
+    // Evalúa las alertas de movimiento con el historial y devuelve las que se disparan junto con su ventana
+    fn check_moves(&self, history: &PriceHistory) -> Vec<(PriceAlert, WindowRange)> {
+        let mut fired = Vec::new();
+        let mut changed = Vec::new();
+        for alert in self.alerts.lock().unwrap().iter_mut() {
+            let Condition::Move { percent, window } = alert.condition else {
+                continue;
+            };
+            let Some(range) = history.range(&alert.pair, window) else {
+                continue;
+            };
+            let moved = range.move_pct().abs();
+            if alert.armed && moved >= percent {
+                alert.armed = false;
+                changed.push((alert.id, false));
+                fired.push((alert.clone(), range));
+            } else if !alert.armed && moved < percent * self.rearm_ratio {
+                alert.armed = true;
+                changed.push((alert.id, true));
+            }
+        }
+        self.persist_armed(&changed);
+        fired
+    }
+}
+
+// Interpreta los argumentos de /alert: "<símbolo> <above|below> <precio>"
+pub fn parse_alert_args(locale: Locale, input: &str) -> Result<(String, Direction, Decimal), String> {
+    let args: Vec<&str> = input.split_whitespace().collect();
+    let [symbol, direction, threshold] = args.as_slice() else {
+        return Err(i18n::t(locale, "alert.usage", &[]));
+    };
+    let direction = direction.parse::<Direction>()
+        .map_err(|_| i18n::t(locale, "alert.invalid_direction", &[("value", direction)]))?;
+    let threshold = Decimal::from_str(threshold)
+        .map_err(|_| i18n::t(locale, "input.invalid_price", &[("value", threshold)]))?;
+    if threshold <= Decimal::ZERO {
+        return Err(i18n::t(locale, "alert.price_positive", &[]));
+    }
+    Ok((symbol.to_string(), direction, threshold))
+}
+
+// Interpreta los argumentos de /movealert: "<símbolo> <porcentaje>% <ventana>"
+pub fn parse_move_alert_args(locale: Locale, input: &str, min_window: Duration) -> Result<(String, Decimal, Duration), String> {
+    let args: Vec<&str> = input.split_whitespace().collect();
+    let [symbol, percent, window] = args.as_slice() else {
+        return Err(i18n::t(locale, "movealert.usage", &[]));
+    };
+    let percent = Decimal::from_str(percent.trim_end_matches('%'))
+        .map_err(|_| i18n::t(locale, "input.invalid_percent", &[("value", percent)]))?;
+    if percent <= Decimal::ZERO {
+        return Err(i18n::t(locale, "movealert.percent_positive", &[]));
+    }
+    let window = history::parse_window(window)
+        .ok_or_else(|| i18n::t(locale, "input.invalid_window", &[("value", window)]))?;
+    if window < min_window {
+        return Err(i18n::t(locale, "movealert.window_too_short", &[("min", &history::format_window(min_window))]));
+    }
+    if window > history::MAX_WINDOW {
+        return Err(i18n::t(locale, "movealert.window_too_long", &[("max", &history::format_window(history::MAX_WINDOW))]));
+    }
+    Ok((symbol.to_string(), percent, window))
+}
+
+// Texto del aviso que se envía al chat cuando se cruza un umbral
+fn alert_text(locale: Locale, alert: &PriceAlert, quote: &Quote) -> String {
+    i18n::t(locale, "alert.fired", &[
+        ("id", &alert.id),
+        ("pair", &alert.pair),
+        ("condition", &alert.condition.describe(locale)),
+        ("price", &i18n::price(locale, quote.price)),
+        ("source", &quote.source),
+    ])
+}
+
+// Texto del aviso de una alerta de movimiento
+fn move_alert_text(locale: Locale, alert: &PriceAlert, range: &WindowRange) -> String {
+    let window = match alert.condition {
+        Condition::Move { window, .. } => history::format_window(window),
+        Condition::Threshold { .. } => String::new(),
+    };
+    i18n::t(locale, "alert.move_fired", &[
+        ("id", &alert.id),
+        ("pair", &alert.pair),
+        ("percent", &i18n::number(locale, range.move_pct(), 2, true)),
+        ("window", &window),
+        ("price", &i18n::price(locale, range.last)),
+        ("low", &i18n::price(locale, range.low)),
+        ("high", &i18n::price(locale, range.high)),
+    ])
+}
+
+// Tarea en segundo plano: cada `interval` consulta el precio de los pares con alertas y avisa de los cruces.
+// Las alertas de movimiento se evalúan con el historial que mantiene la tarea de muestreo.
+pub async fn watch(bot: Bot, prices: Arc<PriceService>, alerts: Arc<Alerts>, history: Arc<PriceHistory>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+
+        for (alert, range) in alerts.check_moves(&history) {
+            if let Err(err) = bot.send_message(alert.chat_id, move_alert_text(alerts.locale(alert.chat_id), &alert, &range)).await {
+                log::error!("Could not deliver alert #{} to chat {}: {:?}", alert.id, alert.chat_id, err);
+            }
+        }
+
+        let pairs = alerts.threshold_pairs();
+        let quotes = futures_util::future::join_all(pairs.iter().map(|pair| prices.price(pair))).await;
+        for (pair, quote) in pairs.iter().zip(quotes) {
+            let quote = match quote {
+                Ok(quote) => quote,
+                Err(err) => {
+                    log::warn!("Alert watcher could not fetch {}: {}", pair, err);
+                    continue;
+                }
+            };
+            for alert in alerts.check_thresholds(pair, quote.price) {
+                if let Err(err) = bot.send_message(alert.chat_id, alert_text(alerts.locale(alert.chat_id), &alert, &quote)).await {
+                    log::error!("Could not deliver alert #{} to chat {}: {:?}", alert.id, alert.chat_id, err);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+
+    const SAMPLE: Duration = Duration::from_secs(60);
+
+    fn d(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn alerts_in_memory() -> (Arc<dyn Storage>, Alerts) {
+        let storage: Arc<dyn Storage> = Arc::new(MemoryStorage::default());
+        storage.migrate().unwrap();
+        let alerts = Alerts::load(storage.clone(), d("0.5"), SAMPLE).unwrap();
+        (storage, alerts)
+    }
+
+    // Estado de armado guardado en el almacenamiento, no el de la copia en memoria
+    fn stored_armed(storage: &dyn Storage, id: u64) -> bool {
+        storage.alerts().unwrap().into_iter().find(|a| a.id == id).unwrap().armed
+    }
+
+    #[test]
+    fn threshold_alert_fires_once_per_crossing_and_rearms_on_the_way_back() {
+        let (storage, alerts) = alerts_in_memory();
+        let pair = Pair::new("BTC", "USDT");
+        let alert = alerts.add(ChatId(1), pair.clone(), Direction::Above, d("70000"), d("69000")).unwrap();
+        assert!(alert.armed);
+
+        assert!(alerts.check_thresholds(&pair, d("69999.99")).is_empty());
+
+        // Al cruzar se dispara y queda desarmada también en el almacenamiento
+        let fired = alerts.check_thresholds(&pair, d("70000"));
+        assert_eq!(fired.iter().map(|a| a.id).collect::<Vec<_>>(), vec![alert.id]);
+        assert!(!stored_armed(storage.as_ref(), alert.id));
+
+        // Mientras siga por encima no se repite el aviso
+        assert!(alerts.check_thresholds(&pair, d("71000")).is_empty());
+        assert!(alerts.check_thresholds(&pair, d("70000")).is_empty());
+
+        // Al volver por debajo se rearma sin avisar y el siguiente cruce vuelve a disparar
+        assert!(alerts.check_thresholds(&pair, d("69500")).is_empty());
+        assert!(stored_armed(storage.as_ref(), alert.id));
+        assert_eq!(alerts.check_thresholds(&pair, d("70500")).len(), 1);
+    }
+
+    #[test]
+    fn threshold_alert_already_met_waits_for_the_next_crossing() {
+        let (storage, alerts) = alerts_in_memory();
+        let pair = Pair::new("ETH", "USDT");
+        let alert = alerts.add(ChatId(1), pair.clone(), Direction::Below, d("3000"), d("2900")).unwrap();
+        assert!(!alert.armed);
+        assert!(!stored_armed(storage.as_ref(), alert.id));
+
+        assert!(alerts.check_thresholds(&pair, d("2800")).is_empty());
+        assert!(alerts.check_thresholds(&pair, d("3100")).is_empty());
+        assert_eq!(alerts.check_thresholds(&pair, d("2999")).len(), 1);
+    }
+
+    #[test]
+    fn threshold_checks_only_touch_alerts_of_the_pair() {
+        let (_, alerts) = alerts_in_memory();
+        let btc = Pair::new("BTC", "USDT");
+        let eth = Pair::new("ETH", "USDT");
+        alerts.add(ChatId(1), btc.clone(), Direction::Above, d("100"), d("50")).unwrap();
+        alerts.add(ChatId(2), eth.clone(), Direction::Above, d("100"), d("50")).unwrap();
+        alerts.add_move(ChatId(1), btc.clone(), d("5"), SAMPLE).unwrap();
+
+        assert_eq!(alerts.check_thresholds(&eth, d("150")).iter().map(|a| a.chat_id).collect::<Vec<_>>(), vec![ChatId(2)]);
+        assert_eq!(alerts.check_thresholds(&btc, d("150")).len(), 1);
+    }
+
+    #[test]
+    fn armed_state_survives_a_reload() {
+        let (storage, alerts) = alerts_in_memory();
+        let pair = Pair::new("BTC", "USDT");
+        alerts.add(ChatId(1), pair.clone(), Direction::Above, d("100"), d("50")).unwrap();
+        assert_eq!(alerts.check_thresholds(&pair, d("150")).len(), 1);
+
+        // Tras un reinicio la alerta sigue desarmada y no repite el aviso
+        let reloaded = Alerts::load(storage, d("0.5"), SAMPLE).unwrap();
+        assert!(reloaded.check_thresholds(&pair, d("150")).is_empty());
+    }
+
+    #[test]
+    fn move_alert_args_accept_windows_between_the_sample_interval_and_the_maximum() {
+        let (symbol, percent, window) = parse_move_alert_args(Locale::En, "btc 5% 1m", SAMPLE).unwrap();
+        assert_eq!((symbol.as_str(), percent, window), ("btc", Decimal::from(5), SAMPLE));
+        assert_eq!(parse_move_alert_args(Locale::En, "btc 5 1d", SAMPLE).unwrap().2, history::MAX_WINDOW);
+    }
+
+    #[test]
+    fn move_alert_args_reject_windows_out_of_range() {
+        let too_short = parse_move_alert_args(Locale::En, "btc 5% 30s", SAMPLE).unwrap_err();
+        assert!(too_short.starts_with("The window cannot be shorter than 1m"), "{}", too_short);
+        let too_long = parse_move_alert_args(Locale::En, "btc 5% 25h", SAMPLE).unwrap_err();
+        assert!(too_long.starts_with("The window cannot be longer than 1d"), "{}", too_long);
+        assert!(parse_move_alert_args(Locale::En, "btc 5% 99999999999999999d", SAMPLE).is_err());
+        assert!(parse_move_alert_args(Locale::En, "btc 0% 1h", SAMPLE).is_err());
+    }
+
+    // Registra un precio tras avanzar el reloj (pausado) del test
+    async fn sample_after(history: &PriceHistory, pair: &Pair, elapsed: Duration, price: &str) {
+        tokio::time::advance(elapsed).await;
+        history.record(pair, d(price));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn move_alert_fires_at_the_threshold_and_not_again_within_the_window() {
+        let (storage, alerts) = alerts_in_memory();
+        let history = PriceHistory::default();
+        let pair = Pair::new("BTC", "USDT");
+        let alert = alerts.add_move(ChatId(1), pair.clone(), d("5"), 5 * SAMPLE).unwrap();
+
+        history.record(&pair, d("100"));
+        sample_after(&history, &pair, SAMPLE, "103").await;
+        assert!(alerts.check_moves(&history).is_empty());
+        sample_after(&history, &pair, SAMPLE, "104.99").await;
+        assert!(alerts.check_moves(&history).is_empty());
+
+        // Justo en el 5 % se dispara, con el rango de la ventana
+        sample_after(&history, &pair, SAMPLE, "105").await;
+        let fired = alerts.check_moves(&history);
+        assert_eq!(fired.len(), 1);
+        let (fired_alert, range) = &fired[0];
+        assert_eq!(fired_alert.id, alert.id);
+        assert_eq!((range.low, range.high, range.last), (d("100"), d("105"), d("105")));
+        assert!(!stored_armed(storage.as_ref(), alert.id));
+
+        // Mientras el movimiento siga por encima de la mitad del porcentaje no se repite
+        sample_after(&history, &pair, SAMPLE, "106").await;
+        assert!(alerts.check_moves(&history).is_empty());
+        sample_after(&history, &pair, SAMPLE, "103").await;
+        assert!(alerts.check_moves(&history).is_empty());
+        assert!(!stored_armed(storage.as_ref(), alert.id));
+
+        // Cuando las muestras antiguas salen de la ventana el movimiento se calma y se rearma
+        sample_after(&history, &pair, 5 * SAMPLE, "103").await;
+        assert!(alerts.check_moves(&history).is_empty());
+        assert!(stored_armed(storage.as_ref(), alert.id));
+
+        // Una caída también cuenta
+        sample_after(&history, &pair, SAMPLE, "97.85").await;
+        let fired = alerts.check_moves(&history);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].1.move_pct(), d("-5"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn move_alert_ignores_samples_older_than_its_window() {
+        let (_, alerts) = alerts_in_memory();
+        let history = PriceHistory::default();
+        let pair = Pair::new("ETH", "USDT");
+        alerts.add_move(ChatId(1), pair.clone(), d("5"), 5 * SAMPLE).unwrap();
+
+        history.record(&pair, d("100"));
+        sample_after(&history, &pair, 6 * SAMPLE, "110").await;
+        assert!(alerts.check_moves(&history).is_empty());
+
+        // Sin muestras del par no hay nada que evaluar
+        assert!(alerts.check_moves(&PriceHistory::default()).is_empty());
+    }
+}
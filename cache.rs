@@ -0,0 +1,272 @@
+// Caché de precios por fuente y par. Cada precio se reutiliza durante un TTL configurable y las
+// peticiones simultáneas del mismo par comparten una única consulta en curso (single-flight),
+// para no agotar el límite de peticiones del exchange en los grupos con mucha actividad.
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures_util::future::{BoxFuture, FutureExt, Shared};
+use rust_decimal::Decimal;
+use tokio::time::Instant;
+
+use crate::config::env_or;
+use crate::prices::PriceService;
+use crate::providers::{Pair, ProviderResult};
+
+type SharedFetch = Shared<BoxFuture<'static, ProviderResult<Decimal>>>;
+
+enum Entry {
+    // Precio ya obtenido y el momento en que llegó
+    Ready { fetched_at: Instant, price: Decimal },
+    // Consulta en curso; quien pida el mismo par espera a este mismo futuro
+    Pending(SharedFetch),
+}
+
+// Cómo se resolvió una consulta a la caché
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lookup {
+    // El precio estaba en caché y no había caducado
+    Hit,
+    // No estaba: esta llamada lanzó la consulta
+    Miss,
+    // Ya había una consulta en curso y se esperó a su resultado
+    Coalesced,
+}
+
+// Contadores de la caché para monitorización
+#[derive(Debug, Clone, Copy)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub coalesced: u64,
+    pub entries: usize,
+}
+
+impl fmt::Display for CacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.hits + self.misses + self.coalesced;
+        let saved = if total == 0 { 0.0 } else { (self.hits + self.coalesced) as f64 * 100.0 / total as f64 };
+        write!(f, "{} hits, {} misses, {} coalesced ({:.1}% served without a request), {} entries",
+            self.hits, self.misses, self.coalesced, saved, self.entries)
+    }
+}
+
+pub struct PriceCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<(&'static str, Pair), Entry>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    coalesced: AtomicU64,
+}
+
+impl PriceCache {
+    pub fn new(ttl: Duration) -> Self {
+        PriceCache {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            coalesced: AtomicU64::new(0),
+        }
+    }
+
+    // PRICE_CACHE_TTL_SECS (por defecto 5 segundos); con 0 solo se agrupan las peticiones simultáneas
+    pub fn from_env() -> Self {
+        Self::new(Duration::from_secs(env_or("PRICE_CACHE_TTL_SECS", 5)))
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            coalesced: self.coalesced.load(Ordering::Relaxed),
+            entries: self.entries.lock().unwrap().len(),
+        }
+    }
+
+    // Devuelve el precio de `pair` en la fuente `source`, usando la caché o lanzando `fetch` si hace falta.
+    // Los errores no se guardan: la siguiente petición vuelve a consultar la fuente.
+    pub async fn get_or_fetch<F>(&self, source: &'static str, pair: &Pair, fetch: F) -> (ProviderResult<Decimal>, Lookup)
+    where
+        F: FnOnce() -> BoxFuture<'static, ProviderResult<Decimal>>,
+    {
+        let key = (source, pair.clone());
+        let (shared, lookup) = {
+            let mut entries = self.entries.lock().unwrap();
+            match entries.get(&key) {
+                Some(Entry::Ready { fetched_at, price }) if fetched_at.elapsed() < self.ttl => {
+                    self.hits.fetch_add(1, Ordering::Relaxed);
+                    return (Ok(*price), Lookup::Hit);
+                }
+                Some(Entry::Pending(shared)) => {
+                    self.coalesced.fetch_add(1, Ordering::Relaxed);
+                    (shared.clone(), Lookup::Coalesced)
+                }
+                _ => {
+                    self.misses.fetch_add(1, Ordering::Relaxed);
+                    // Se aprovecha para olvidar los precios caducados y que la caché no crezca sin límite
+                    let ttl = self.ttl;
+                    entries.retain(|_, entry| match entry {
+                        Entry::Ready { fetched_at, .. } => fetched_at.elapsed() < ttl,
+                        Entry::Pending(_) => true,
+                    });
+                    let shared = fetch().shared();
+                    entries.insert(key.clone(), Entry::Pending(shared.clone()));
+                    (shared, Lookup::Miss)
+                }
+            }
+        };
+
+        let result = shared.clone().await;
+
+        // El primero que termina (aunque quien lanzó la consulta se haya cancelado) guarda el resultado
+        let mut entries = self.entries.lock().unwrap();
+        if matches!(entries.get(&key), Some(Entry::Pending(pending)) if pending.ptr_eq(&shared)) {
+            match &result {
+                Ok(price) => {
+                    entries.insert(key, Entry::Ready { fetched_at: Instant::now(), price: *price });
+                }
+                Err(_) => {
+                    entries.remove(&key);
+                }
+            }
+        }
+        (result, lookup)
+    }
+}
+
+// Tarea que escribe en el log los contadores de la caché cada `interval`
+pub async fn log_stats(prices: Arc<PriceService>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.tick().await;
+    loop {
+        ticker.tick().await;
+        log::info!("Price cache: {}", prices.cache_stats());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use futures_util::future::join_all;
+
+    use super::*;
+    use crate::providers::ProviderError;
+
+    // Fuente falsa que cuenta las consultas y tarda `delay` en responder
+    #[derive(Clone, Default)]
+    struct CountingSource {
+        fetches: Arc<AtomicUsize>,
+        delay: Duration,
+    }
+
+    impl CountingSource {
+        fn fetch(&self, result: ProviderResult<Decimal>) -> BoxFuture<'static, ProviderResult<Decimal>> {
+            let fetches = self.fetches.clone();
+            let delay = self.delay;
+            async move {
+                fetches.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(delay).await;
+                result
+            }.boxed()
+        }
+
+        fn count(&self) -> usize {
+            self.fetches.load(Ordering::SeqCst)
+        }
+    }
+
+    fn btc() -> Pair {
+        Pair::new("BTC", "USDT")
+    }
+
+    // ProviderError no implementa PartialEq, así que se compara solo el precio
+    fn price((result, lookup): (ProviderResult<Decimal>, Lookup)) -> (Option<Decimal>, Lookup) {
+        (result.ok(), lookup)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn concurrent_gets_share_a_single_fetch() {
+        let cache = PriceCache::new(Duration::from_secs(5));
+        let source = CountingSource { delay: Duration::from_millis(200), ..Default::default() };
+
+        let pair = btc();
+        let results = join_all((0..10).map(|_| cache.get_or_fetch("Fake", &pair, || source.fetch(Ok(Decimal::from(100)))))).await;
+
+        assert_eq!(source.count(), 1);
+        assert!(results.iter().all(|(result, _)| matches!(result, Ok(price) if *price == Decimal::from(100))));
+        assert_eq!(results.iter().filter(|(_, lookup)| *lookup == Lookup::Miss).count(), 1);
+        assert_eq!(results.iter().filter(|(_, lookup)| *lookup == Lookup::Coalesced).count(), 9);
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.coalesced, stats.entries), (0, 1, 9, 1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn prices_are_reused_until_the_ttl_expires() {
+        let cache = PriceCache::new(Duration::from_secs(5));
+        let source = &CountingSource::default();
+        let pair = btc();
+        let get = |price: i64| cache.get_or_fetch("Fake", &pair, move || source.fetch(Ok(Decimal::from(price))));
+
+        assert_eq!(price(get(100).await), (Some(Decimal::from(100)), Lookup::Miss));
+        tokio::time::advance(Duration::from_millis(4_999)).await;
+        assert_eq!(price(get(200).await), (Some(Decimal::from(100)), Lookup::Hit));
+        assert_eq!(source.count(), 1);
+
+        // Al cumplirse el TTL se vuelve a consultar la fuente
+        tokio::time::advance(Duration::from_millis(1)).await;
+        assert_eq!(price(get(200).await), (Some(Decimal::from(200)), Lookup::Miss));
+        assert_eq!(source.count(), 2);
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.coalesced), (1, 2, 0));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn sources_and_pairs_are_cached_separately() {
+        let cache = PriceCache::new(Duration::from_secs(5));
+        let source = CountingSource::default();
+
+        for (source_name, pair) in [("Fake", btc()), ("Other", btc()), ("Fake", Pair::new("ETH", "USDT"))] {
+            let lookup = cache.get_or_fetch(source_name, &pair, || source.fetch(Ok(Decimal::ONE))).await;
+            assert_eq!(price(lookup), (Some(Decimal::ONE), Lookup::Miss));
+        }
+
+        assert_eq!(source.count(), 3);
+        assert_eq!(cache.stats().entries, 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn errors_are_not_cached() {
+        let cache = PriceCache::new(Duration::from_secs(5));
+        let source = CountingSource::default();
+
+        let (result, lookup) = cache.get_or_fetch("Fake", &btc(), || source.fetch(Err(ProviderError::Network("connection reset".into())))).await;
+        assert!(result.is_err());
+        assert_eq!(lookup, Lookup::Miss);
+        assert_eq!(cache.stats().entries, 0);
+
+        let lookup = cache.get_or_fetch("Fake", &btc(), || source.fetch(Ok(Decimal::ONE))).await;
+        assert_eq!(price(lookup), (Some(Decimal::ONE), Lookup::Miss));
+        assert_eq!(source.count(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn zero_ttl_only_coalesces_simultaneous_gets() {
+        let cache = PriceCache::new(Duration::ZERO);
+        let source = CountingSource { delay: Duration::from_millis(50), ..Default::default() };
+
+        let pair = btc();
+        join_all((0..3).map(|_| cache.get_or_fetch("Fake", &pair, || source.fetch(Ok(Decimal::ONE))))).await;
+        assert_eq!(source.count(), 1);
+
+        let (_, lookup) = cache.get_or_fetch("Fake", &btc(), || source.fetch(Ok(Decimal::ONE))).await;
+        assert_eq!(lookup, Lookup::Miss);
+        assert_eq!(source.count(), 2);
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.coalesced), (0, 2, 2));
+    }
+}
@@ -0,0 +1,341 @@
+// Gráficos de velas: "/chart btc 1h 7d" descarga las velas de Binance y dibuja un PNG con
+// velas y barras de volumen, todo en Rust y sin servicios externos.
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use image::{ImageFormat, Rgb, RgbImage};
+use rust_decimal::prelude::*;
+
+use crate::history;
+use crate::i18n::{self, Locale};
+use crate::prices::format_price;
+use crate::providers::Candle;
+
+// Intervalos que acepta el endpoint de velas de Binance. "1M" (un mes) se aproxima a 30 días
+// solo para calcular cuántas velas caben en el rango.
+const INTERVALS: [(&str, u64); 16] = [
+    ("1s", 1), ("1m", 60), ("3m", 3 * 60), ("5m", 5 * 60), ("15m", 15 * 60), ("30m", 30 * 60),
+    ("1h", 60 * 60), ("2h", 2 * 60 * 60), ("4h", 4 * 60 * 60), ("6h", 6 * 60 * 60), ("8h", 8 * 60 * 60),
+    ("12h", 12 * 60 * 60), ("1d", 24 * 60 * 60), ("3d", 3 * 24 * 60 * 60), ("1w", 7 * 24 * 60 * 60),
+    ("1M", 30 * 24 * 60 * 60),
+];
+// Binance devuelve como mucho 1000 velas por petición
+const MAX_CANDLES: u64 = 1000;
+// Por debajo de este número de velas el gráfico no aporta nada
+const MIN_CANDLES: u64 = 2;
+
+// Tamaño de la imagen
+pub const WIDTH: u32 = 1000;
+pub const HEIGHT: u32 = 600;
+
+// Parámetros validados de /chart
+#[derive(Debug, Clone)]
+pub struct ChartRequest {
+    pub symbol: String,
+    pub interval: &'static str,
+    pub range: Duration,
+    pub candles: u32,
+}
+
+// Interpreta "/chart <símbolo> [intervalo] [rango]", por defecto velas de 1h durante 1 día
+pub fn parse_chart_args(locale: Locale, input: &str) -> Result<ChartRequest, String> {
+    let args: Vec<&str> = input.split_whitespace().collect();
+    let (symbol, interval, range) = match args.as_slice() {
+        [symbol] => (*symbol, "1h", "1d"),
+        [symbol, interval] => (*symbol, *interval, "1d"),
+        [symbol, interval, range] => (*symbol, *interval, *range),
+        _ => return Err(i18n::t(locale, "chart.usage", &[])),
+    };
+
+    let (interval, interval_secs) = INTERVALS.iter()
+        .find(|(name, _)| *name == interval)
+        .copied()
+        .ok_or_else(|| i18n::t(locale, "chart.invalid_interval", &[
+            ("interval", &interval),
+            ("intervals", &INTERVALS.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(", ")),
+        ]))?;
+    let range = parse_range(range)
+        .ok_or_else(|| i18n::t(locale, "chart.invalid_range", &[("range", &range)]))?;
+
+    let candles = range.as_secs() / interval_secs;
+    if candles < MIN_CANDLES {
+        return Err(i18n::t(locale, "chart.too_few", &[("min", &MIN_CANDLES), ("interval", &interval)]));
+    }
+    if candles > MAX_CANDLES {
+        return Err(i18n::t(locale, "chart.too_many", &[
+            ("range", &history::format_window(range)),
+            ("interval", &interval),
+            ("candles", &candles),
+            ("max", &MAX_CANDLES),
+        ]));
+    }
+    Ok(ChartRequest { symbol: symbol.to_string(), interval, range, candles: candles as u32 })
+}
+
+// Rango del gráfico: lo mismo que una ventana de /movealert y además semanas ("4w")
+fn parse_range(input: &str) -> Option<Duration> {
+    match input.strip_suffix('w').and_then(|weeks| weeks.parse::<u64>().ok()) {
+        // Un número de semanas desbordado se trata como un rango inválido
+        Some(weeks) if weeks > 0 => weeks.checked_mul(7 * 24 * 60 * 60).map(Duration::from_secs),
+        Some(_) => None,
+        None => history::parse_window(input),
+    }
+}
+
+// Pie de foto con el resumen del periodo
+pub fn caption(locale: Locale, pair: &crate::providers::Pair, request: &ChartRequest, candles: &[Candle]) -> String {
+    let (Some(first), Some(last)) = (candles.first(), candles.last()) else {
+        return pair.to_string();
+    };
+    let high = candles.iter().map(|c| c.high).max().unwrap_or(last.high);
+    let low = candles.iter().map(|c| c.low).min().unwrap_or(last.low);
+    let change = if first.open.is_zero() { Decimal::ZERO } else { (last.close - first.open) / first.open * Decimal::ONE_HUNDRED };
+    i18n::t(locale, "chart.caption", &[
+        ("pair", pair),
+        ("interval", &request.interval),
+        ("range", &history::format_window(request.range)),
+        ("open", &i18n::price(locale, first.open)),
+        ("high", &i18n::price(locale, high)),
+        ("low", &i18n::price(locale, low)),
+        ("close", &i18n::price(locale, last.close)),
+        ("change", &i18n::change(locale, change.round_dp(2))),
+    ])
+}
+
+// Colores del gráfico (tema oscuro)
+const BACKGROUND: Rgb<u8> = Rgb([19, 23, 34]);
+const GRID: Rgb<u8> = Rgb([42, 46, 57]);
+const LABEL: Rgb<u8> = Rgb([178, 181, 190]);
+const UP: Rgb<u8> = Rgb([38, 166, 154]);
+const DOWN: Rgb<u8> = Rgb([239, 83, 80]);
+const UP_VOLUME: Rgb<u8> = Rgb([24, 86, 83]);
+const DOWN_VOLUME: Rgb<u8> = Rgb([115, 46, 49]);
+
+// Márgenes: a la derecha van las etiquetas de precio y abajo las de tiempo
+const MARGIN_LEFT: u32 = 10;
+const MARGIN_RIGHT: u32 = 140;
+const MARGIN_TOP: u32 = 20;
+const MARGIN_BOTTOM: u32 = 30;
+// Separación entre el panel de precios y el de volumen
+const PANEL_GAP: u32 = 10;
+// Líneas horizontales de la rejilla en el panel de precios
+const GRID_LINES: u32 = 5;
+// Escala del texto (cada píxel de la fuente de 5x7 ocupa 2x2)
+const TEXT_SCALE: u32 = 2;
+
+// Dibuja las velas y el volumen y devuelve la imagen codificada en PNG.
+// Es una función pura para poder probarla con velas fijas sin acceso a red.
+pub fn render(candles: &[Candle], width: u32, height: u32) -> Result<Vec<u8>, image::ImageError> {
+    let mut img = RgbImage::from_pixel(width, height, BACKGROUND);
+
+    let plot_left = MARGIN_LEFT;
+    let plot_right = width.saturating_sub(MARGIN_RIGHT).max(plot_left + 1);
+    let plot_width = plot_right - plot_left;
+    let available = height.saturating_sub(MARGIN_TOP + MARGIN_BOTTOM + PANEL_GAP);
+    let price_top = MARGIN_TOP;
+    let price_height = (available * 4 / 5).max(1);
+    let volume_top = price_top + price_height + PANEL_GAP;
+    let volume_height = (available - price_height).max(1);
+
+    if !candles.is_empty() {
+        let to_f64 = |d: Decimal| d.to_f64().unwrap_or(0.0);
+        let mut low = candles.iter().map(|c| to_f64(c.low)).fold(f64::INFINITY, f64::min);
+        let mut high = candles.iter().map(|c| to_f64(c.high)).fold(f64::NEG_INFINITY, f64::max);
+        // Un poco de aire arriba y abajo; si el precio no se ha movido se abre un rango artificial
+        let padding = if high > low { (high - low) * 0.05 } else { high.abs().max(1.0) * 0.01 };
+        low -= padding;
+        high += padding;
+        let max_volume = candles.iter().map(|c| to_f64(c.volume)).fold(0.0, f64::max);
+
+        let price_y = |price: f64| -> i64 {
+            price_top as i64 + ((high - price) / (high - low) * price_height as f64).round() as i64
+        };
+
+        // Rejilla y etiquetas de precio
+        for i in 0..=GRID_LINES {
+            let price = high - (high - low) * i as f64 / GRID_LINES as f64;
+            let y = price_y(price);
+            hline(&mut img, plot_left as i64, plot_right as i64, y, GRID);
+            let label = format_price(Decimal::from_f64(price).unwrap_or_default());
+            draw_text(&mut img, plot_right as i64 + 8, y - (7 * TEXT_SCALE as i64) / 2, &label, LABEL);
+        }
+
+        // Velas y volumen
+        let step = plot_width as f64 / candles.len() as f64;
+        let body_width = (step * 0.7).max(1.0);
+        for (i, candle) in candles.iter().enumerate() {
+            let center = plot_left as f64 + (i as f64 + 0.5) * step;
+            let x0 = (center - body_width / 2.0).round() as i64;
+            let x1 = (center + body_width / 2.0).round() as i64;
+            let up = candle.close >= candle.open;
+            let (color, volume_color) = if up { (UP, UP_VOLUME) } else { (DOWN, DOWN_VOLUME) };
+
+            vline(&mut img, center.round() as i64, price_y(to_f64(candle.high)), price_y(to_f64(candle.low)), color);
+            let body_top = price_y(to_f64(candle.open.max(candle.close)));
+            let body_bottom = price_y(to_f64(candle.open.min(candle.close)));
+            fill_rect(&mut img, x0, body_top, x1.max(x0 + 1), body_bottom.max(body_top + 1), color);
+
+            if max_volume > 0.0 {
+                let bar = (to_f64(candle.volume) / max_volume * volume_height as f64).round() as i64;
+                let bottom = (volume_top + volume_height) as i64;
+                fill_rect(&mut img, x0, bottom - bar, x1.max(x0 + 1), bottom, volume_color);
+            }
+        }
+
+        // Etiquetas de tiempo: unas cuantas repartidas por el eje horizontal
+        let span = candles.last().unwrap().open_time - candles.first().unwrap().open_time;
+        let format = if span.num_hours() >= 48 { "%d/%m" } else { "%H:%M" };
+        let labels = (plot_width / 160).max(1) as usize;
+        for n in 0..labels {
+            let index = n * candles.len() / labels;
+            let center = plot_left as f64 + (index as f64 + 0.5) * step;
+            let text = time_label(candles[index].open_time, format);
+            let y = (volume_top + volume_height + 10) as i64;
+            vline(&mut img, center.round() as i64, (volume_top + volume_height) as i64, y - 4, GRID);
+            draw_text(&mut img, center.round() as i64, y, &text, LABEL);
+        }
+    }
+
+    let mut png = Vec::new();
+    img.write_to(&mut std::io::Cursor::new(&mut png), ImageFormat::Png)?;
+    Ok(png)
+}
+
+fn time_label(time: DateTime<Utc>, format: &str) -> String {
+    time.format(format).to_string()
+}
+
+// Rectángulo relleno [x0, x1) x [y0, y1), recortado a los límites de la imagen
+fn fill_rect(img: &mut RgbImage, x0: i64, y0: i64, x1: i64, y1: i64, color: Rgb<u8>) {
+    let (width, height) = (img.width() as i64, img.height() as i64);
+    for y in y0.max(0)..y1.min(height) {
+        for x in x0.max(0)..x1.min(width) {
+            img.put_pixel(x as u32, y as u32, color);
+        }
+    }
+}
+
+fn hline(img: &mut RgbImage, x0: i64, x1: i64, y: i64, color: Rgb<u8>) {
+    fill_rect(img, x0, y, x1, y + 1, color);
+}
+
+fn vline(img: &mut RgbImage, x: i64, y0: i64, y1: i64, color: Rgb<u8>) {
+    fill_rect(img, x, y0.min(y1), x + 1, y0.max(y1) + 1, color);
+}
+
+// Fuente de mapa de bits de 5x7 con los caracteres que aparecen en las etiquetas;
+// cada fila es un byte cuyos 5 bits bajos son los píxeles de izquierda a derecha
+fn glyph(c: char) -> [u8; 7] {
+    match c {
+        '0' => [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110],
+        '1' => [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        '2' => [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111],
+        '3' => [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110],
+        '4' => [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010],
+        '5' => [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110],
+        '6' => [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110],
+        '7' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000],
+        '8' => [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110],
+        '9' => [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100],
+        '.' => [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100],
+        '-' => [0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000],
+        ':' => [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000],
+        '/' => [0b00001, 0b00010, 0b00010, 0b00100, 0b01000, 0b01000, 0b10000],
+        _ => [0; 7],
+    }
+}
+
+// Escribe un texto con la fuente de 5x7 a partir de la esquina superior izquierda (x, y)
+fn draw_text(img: &mut RgbImage, x: i64, y: i64, text: &str, color: Rgb<u8>) {
+    let scale = TEXT_SCALE as i64;
+    for (i, c) in text.chars().enumerate() {
+        let origin = x + i as i64 * 6 * scale;
+        for (row, bits) in glyph(c).iter().enumerate() {
+            for col in 0..5 {
+                if bits & (1 << (4 - col)) != 0 {
+                    let px = origin + col * scale;
+                    let py = y + row as i64 * scale;
+                    fill_rect(img, px, py, px + scale, py + scale, color);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    // Vela de una hora: sube si close > open
+    fn candle(hour: i64, open: &str, close: &str, volume: &str) -> Candle {
+        let (open, close) = (d(open), d(close));
+        Candle {
+            open_time: DateTime::from_timestamp(1_700_000_000 + hour * 3600, 0).unwrap(),
+            open,
+            high: open.max(close) + Decimal::ONE,
+            low: open.min(close) - Decimal::ONE,
+            close,
+            volume: d(volume),
+        }
+    }
+
+    fn fixture() -> Vec<Candle> {
+        vec![
+            candle(0, "100", "110", "5"),
+            candle(1, "110", "104", "8"),
+            candle(2, "104", "120", "3"),
+            candle(3, "120", "115", "10"),
+        ]
+    }
+
+    #[test]
+    fn render_draws_a_png_with_candles_and_volume() {
+        let png = render(&fixture(), 400, 300).unwrap();
+        let img = image::load_from_memory_with_format(&png, ImageFormat::Png).unwrap().to_rgb8();
+        assert_eq!(img.dimensions(), (400, 300));
+        for color in [UP, DOWN, UP_VOLUME, DOWN_VOLUME, GRID, LABEL] {
+            assert!(img.pixels().any(|pixel| *pixel == color), "missing color {:?}", color);
+        }
+    }
+
+    #[test]
+    fn render_without_candles_is_an_empty_background() {
+        let png = render(&[], WIDTH, HEIGHT).unwrap();
+        let img = image::load_from_memory_with_format(&png, ImageFormat::Png).unwrap().to_rgb8();
+        assert_eq!(img.dimensions(), (WIDTH, HEIGHT));
+        assert!(img.pixels().all(|pixel| *pixel == BACKGROUND));
+    }
+
+    #[test]
+    fn chart_args_default_to_one_day_of_hourly_candles() {
+        let request = parse_chart_args(Locale::En, "btc").unwrap();
+        assert_eq!((request.symbol.as_str(), request.interval, request.candles), ("btc", "1h", 24));
+        assert_eq!(request.range, Duration::from_secs(24 * 60 * 60));
+        assert_eq!(parse_chart_args(Locale::En, "eth 4h 4w").unwrap().candles, 168);
+    }
+
+    #[test]
+    fn chart_args_enforce_the_candle_limits() {
+        assert_eq!(parse_chart_args(Locale::En, "btc 1m 1000m").unwrap().candles, MAX_CANDLES as u32);
+        assert_eq!(parse_chart_args(Locale::En, "btc 1d 2d").unwrap().candles, MIN_CANDLES as u32);
+        let too_many = parse_chart_args(Locale::En, "btc 1m 1001m").unwrap_err();
+        assert!(too_many.contains("needs 1001 candles, the maximum is 1000"), "{}", too_many);
+        let too_few = parse_chart_args(Locale::En, "btc 1d 1d").unwrap_err();
+        assert!(too_few.contains("at least 2 1d candles"), "{}", too_few);
+    }
+
+    #[test]
+    fn chart_args_reject_invalid_input() {
+        assert!(parse_chart_args(Locale::En, "").unwrap_err().starts_with("Usage: /chart"));
+        assert!(parse_chart_args(Locale::En, "btc 1h 1d extra").unwrap_err().starts_with("Usage: /chart"));
+        assert!(parse_chart_args(Locale::En, "btc 7m").unwrap_err().contains("'7m' is not a valid interval"));
+        for range in ["0w", "abc", "5y", "99999999999999999w"] {
+            let err = parse_chart_args(Locale::En, &format!("btc 1h {}", range)).unwrap_err();
+            assert!(err.contains("is not a valid range"), "{}: {}", range, err);
+        }
+    }
+}
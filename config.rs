@@ -0,0 +1,24 @@
+// Utilidades para leer la configuración desde las variables de entorno
+use std::str::FromStr;
+
+// Lee una variable de entorno y la convierte al tipo pedido; si no existe o no es válida se usa el valor por defecto
+pub fn env_or<T: FromStr>(name: &str, default: T) -> T {
+    match std::env::var(name) {
+        Ok(value) => match value.trim().parse() {
+            Ok(parsed) => parsed,
+            Err(_) => {
+                log::warn!("Invalid value '{}' for {}, using the default", value, name);
+                default
+            }
+        },
+        Err(_) => default,
+    }
+}
+
+// Lee una lista separada por comas, ignorando los elementos vacíos
+pub fn env_list(name: &str) -> Option<Vec<String>> {
+    std::env::var(name).ok().map(|value| value.split(',')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect())
+}
@@ -0,0 +1,181 @@
+// Conversión de importes entre activos (/convert 0.35 btc eur). Se usa el par directo o inverso si
+// existe; si no, se pasa por USDT, y las divisas fiat sin par en los exchanges se cambian con el
+// proveedor de tipos de cambio tomando USDT como equivalente al dólar.
+use rust_decimal::prelude::*;
+
+use crate::i18n::{self, Locale};
+use crate::portfolio::parse_positive;
+use crate::prices::PriceService;
+use crate::providers::{FxProvider, Pair, ProviderError, ProviderResult};
+
+// Activo puente para las conversiones sin par directo
+const HUB: &str = "USDT";
+
+// Decimales con los que se muestra cada activo. Las divisas sin decimales (JPY, KRW...) se redondean a
+// la unidad, las stablecoins y el resto de fiat a céntimos y las criptomonedas a 8 decimales (satoshis)
+const ASSET_DECIMALS: [(&str, u32); 12] = [
+    ("JPY", 0), ("KRW", 0), ("ISK", 0), ("IDR", 0),
+    ("USDT", 2), ("USDC", 2), ("FDUSD", 2), ("DAI", 2), ("TUSD", 2),
+    ("BTC", 8), ("ETH", 8), ("BNB", 8),
+];
+const FIAT_DECIMALS: u32 = 2;
+const CRYPTO_DECIMALS: u32 = 8;
+
+// Precio usado en un paso de la conversión, p. ej. BTC/USDT = 65000 (Binance)
+pub struct Step {
+    pub pair: String,
+    pub price: Decimal,
+    pub source: &'static str,
+}
+
+// Resultado de una conversión junto con los pasos seguidos
+pub struct Conversion {
+    pub amount: Decimal,
+    pub from: String,
+    pub to: String,
+    pub result: Decimal,
+    pub steps: Vec<Step>,
+}
+
+// Interpreta "<importe> <desde> <hacia>", admitiendo también "0.35 btc to eur" y "500 usd in eth"
+pub fn parse_convert_args(locale: Locale, input: &str) -> Result<(Decimal, String, String), String> {
+    let args: Vec<&str> = input.split_whitespace()
+        .filter(|arg| !arg.eq_ignore_ascii_case("to") && !arg.eq_ignore_ascii_case("in"))
+        .collect();
+    let [amount, from, to] = args.as_slice() else {
+        return Err(i18n::t(locale, "convert.usage", &[]));
+    };
+    let amount = parse_positive(locale, amount, "amount")?;
+    Ok((amount, from.to_uppercase(), to.to_uppercase()))
+}
+
+// Decimales con los que se redondea un importe del activo indicado
+pub fn asset_decimals(asset: &str, fx: &FxProvider) -> u32 {
+    match ASSET_DECIMALS.iter().find(|(name, _)| name.eq_ignore_ascii_case(asset)) {
+        Some((_, decimals)) => *decimals,
+        None if fx.is_fiat(asset) => FIAT_DECIMALS,
+        None => CRYPTO_DECIMALS,
+    }
+}
+
+// Redondeo comercial (la mitad se redondea hacia arriba) a los decimales del activo
+pub fn round_amount(amount: Decimal, asset: &str, fx: &FxProvider) -> Decimal {
+    amount.round_dp_with_strategy(asset_decimals(asset, fx), RoundingStrategy::MidpointAwayFromZero)
+}
+
+// Precio de `from` en `to` usando solo el par directo o el inverso, si alguno existe
+async fn pair_rate(prices: &PriceService, from: &str, to: &str, steps: &mut Vec<Step>) -> ProviderResult<Option<Decimal>> {
+    let direct = Pair::new(from, to);
+    if prices.has_pair(&direct).await? {
+        let quote = prices.price(&direct).await?;
+        steps.push(Step { pair: direct.to_string(), price: quote.price, source: quote.source });
+        return Ok(Some(quote.price));
+    }
+    let inverse = Pair::new(to, from);
+    if prices.has_pair(&inverse).await? {
+        let quote = prices.price(&inverse).await?;
+        if quote.price.is_zero() {
+            return Err(ProviderError::MalformedNumber(quote.price.to_string()));
+        }
+        steps.push(Step { pair: inverse.to_string(), price: quote.price, source: quote.source });
+        return Ok(Some(Decimal::ONE / quote.price));
+    }
+    Ok(None)
+}
+
+// Valor de una unidad de `asset` en el activo puente
+async fn hub_rate(prices: &PriceService, fx: &FxProvider, asset: &str, steps: &mut Vec<Step>) -> ProviderResult<Decimal> {
+    if asset == HUB {
+        return Ok(Decimal::ONE);
+    }
+    if let Some(rate) = pair_rate(prices, asset, HUB, steps).await? {
+        return Ok(rate);
+    }
+    if fx.is_fiat(asset) {
+        let per_usd = fx.per_usd(asset).await?;
+        // USD se toma como equivalente a USDT y no aporta ningún paso
+        if asset != "USD" {
+            steps.push(Step { pair: format!("USD/{}", asset), price: per_usd.round_dp(4), source: "FX" });
+        }
+        return Ok(Decimal::ONE / per_usd);
+    }
+    Err(ProviderError::InvalidSymbol(asset.to_string()))
+}
+
+pub async fn convert(prices: &PriceService, fx: &FxProvider, amount: Decimal, from: &str, to: &str) -> ProviderResult<Conversion> {
+    let mut steps = Vec::new();
+    let rate = if from == to {
+        Decimal::ONE
+    } else if let Some(rate) = pair_rate(prices, from, to, &mut steps).await? {
+        rate
+    } else {
+        let from_hub = hub_rate(prices, fx, from, &mut steps).await?;
+        let to_hub = hub_rate(prices, fx, to, &mut steps).await?;
+        if to_hub.is_zero() {
+            return Err(ProviderError::MalformedNumber(to_hub.to_string()));
+        }
+        from_hub / to_hub
+    };
+    Ok(Conversion {
+        amount,
+        from: from.to_string(),
+        to: to.to_string(),
+        result: round_amount(amount * rate, to, fx),
+        steps,
+    })
+}
+
+// Texto de la respuesta: importe convertido y los precios usados en cada paso
+pub fn conversion_text(locale: Locale, conversion: &Conversion, fx: &FxProvider) -> String {
+    let decimals = asset_decimals(&conversion.to, fx);
+    let mut lines = vec![format!("{} {} = {} {}", i18n::amount(locale, conversion.amount), conversion.from,
+        i18n::number(locale, conversion.result, decimals, false), conversion.to)];
+    if !conversion.steps.is_empty() {
+        lines.push(i18n::t(locale, "convert.rates", &[]));
+        lines.extend(conversion.steps.iter()
+            .map(|step| format!("• {} = {} ({})", step.pair, i18n::price(locale, step.price), step.source)));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn parses_amount_and_assets_with_connectors() {
+        assert_eq!(parse_convert_args(Locale::En, "0.35 btc eur"), Ok((d("0.35"), "BTC".into(), "EUR".into())));
+        assert_eq!(parse_convert_args(Locale::En, "0.35 btc to eur"), Ok((d("0.35"), "BTC".into(), "EUR".into())));
+        assert_eq!(parse_convert_args(Locale::En, "500 usd IN eth"), Ok((d("500"), "USD".into(), "ETH".into())));
+    }
+
+    #[test]
+    fn rejects_commas_instead_of_guessing_the_separator() {
+        // "1,000" no puede convertirse en 1 ni en 1000 sin saber la intención del usuario
+        let error = parse_convert_args(Locale::En, "1,000 btc eur").unwrap_err();
+        assert!(error.contains("1,000"));
+        assert_eq!(error, i18n::field_error(Locale::En, "number.decimal_comma", "1,000", "amount"));
+        assert!(parse_convert_args(Locale::Es, "0,5 btc eur").is_err());
+    }
+
+    #[test]
+    fn rejects_zero_negative_and_malformed_amounts() {
+        for input in ["0 btc eur", "0.00 btc eur", "-1 btc eur", "abc btc eur"] {
+            assert!(parse_convert_args(Locale::En, input).is_err(), "{}", input);
+        }
+        assert_eq!(parse_convert_args(Locale::En, "0.00 btc eur").unwrap_err(),
+            i18n::field_error(Locale::En, "number.invalid", "0.00", "amount"));
+    }
+
+    #[test]
+    fn wrong_argument_count_shows_usage() {
+        let usage = i18n::t(Locale::En, "convert.usage", &[]);
+        assert_eq!(parse_convert_args(Locale::En, "").unwrap_err(), usage);
+        assert_eq!(parse_convert_args(Locale::En, "1 btc").unwrap_err(), usage);
+        assert_eq!(parse_convert_args(Locale::En, "1 btc eur usd").unwrap_err(), usage);
+    }
+}
@@ -0,0 +1,431 @@
+// Resúmenes de mercado programados por chat: "/digest daily 08:00 Europe/Madrid btc eth",
+// "/digest weekly mon 09:30 UTC btc", "/digest list" y "/digest cancel <id>". La hora se guarda en la
+// zona horaria del chat, así que el envío sigue a las 08:00 locales aunque cambie el horario de verano.
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use teloxide::prelude::*;
+use teloxide::types::ParseMode;
+use teloxide::{ApiError, RequestError};
+
+use crate::i18n::{self, Locale};
+use crate::prices::PriceService;
+use crate::providers::Pair;
+use crate::storage::{NewDigest, Storage};
+
+// Límites por chat y por resumen, para que el mensaje quepa y no se abuse del API de precios
+const MAX_DIGESTS_PER_CHAT: usize = 5;
+const MAX_DIGEST_PAIRS: usize = 10;
+
+// Frecuencia de un resumen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Schedule {
+    Daily,
+    Weekly(Weekday),
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Schedule::Daily => write!(f, "daily"),
+            Schedule::Weekly(day) => write!(f, "weekly:{}", day.to_string().to_lowercase()),
+        }
+    }
+}
+
+// Lee el formato que escribe Display ("daily", "weekly:mon")
+impl FromStr for Schedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "daily" => Ok(Schedule::Daily),
+            Some(("weekly", day)) => day.parse::<Weekday>()
+                .map(Schedule::Weekly)
+                .map_err(|_| format!("'{}' is not a day of the week", day)),
+            _ => Err(format!("unknown digest schedule '{}'", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Digest {
+    pub id: u64,
+    pub chat_id: ChatId,
+    pub schedule: Schedule,
+    // Hora local del envío en `timezone`
+    pub time: NaiveTime,
+    pub timezone: Tz,
+    pub pairs: Vec<Pair>,
+    // Próximo envío, en UTC
+    pub next_run: DateTime<Utc>,
+}
+
+impl Digest {
+    fn describe(&self, locale: Locale) -> String {
+        let pairs = self.pairs.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(", ");
+        let time = self.time.format("%H:%M").to_string();
+        match self.schedule {
+            Schedule::Daily => i18n::t(locale, "digest.daily", &[
+                ("id", &self.id), ("time", &time), ("timezone", &self.timezone), ("pairs", &pairs),
+            ]),
+            Schedule::Weekly(day) => i18n::t(locale, "digest.weekly", &[
+                ("id", &self.id), ("day", &weekday_name(locale, day)), ("time", &time), ("timezone", &self.timezone), ("pairs", &pairs),
+            ]),
+        }
+    }
+}
+
+// Nombre del día en el idioma del chat ("weekday.mon" -> "Monday" / "lunes")
+fn weekday_name(locale: Locale, day: Weekday) -> String {
+    i18n::t(locale, &format!("weekday.{}", day.to_string().to_lowercase()), &[])
+}
+
+// Instante UTC de la hora local `time` del día `date`. Si esa hora no existe por el cambio al horario
+// de verano se usa una hora después, y si existe dos veces (cambio al de invierno) la primera.
+fn local_instant(timezone: Tz, date: NaiveDate, time: NaiveTime) -> DateTime<Utc> {
+    let local = date.and_time(time);
+    timezone.from_local_datetime(&local).earliest()
+        .or_else(|| timezone.from_local_datetime(&(local + chrono::Duration::hours(1))).earliest())
+        .map(|instant| instant.with_timezone(&Utc))
+        // Ninguna zona tiene huecos de más de una hora; por si acaso se interpreta en UTC
+        .unwrap_or_else(|| local.and_utc())
+}
+
+// Primer envío posterior a `after` según la frecuencia, la hora y la zona horaria
+pub fn next_run(schedule: Schedule, time: NaiveTime, timezone: Tz, after: DateTime<Utc>) -> DateTime<Utc> {
+    let today = after.with_timezone(&timezone).date_naive();
+    // Entre hoy y dentro de ocho días siempre hay un envío, también en los semanales
+    today.iter_days()
+        .take(9)
+        .filter(|date| match schedule {
+            Schedule::Daily => true,
+            Schedule::Weekly(day) => date.weekday() == day,
+        })
+        .map(|date| local_instant(timezone, date, time))
+        .find(|instant| *instant > after)
+        .expect("there is always a run within the next eight days")
+}
+
+// Interpreta "daily <hh:mm> <zona> <símbolos...>" o "weekly <día> <hh:mm> <zona> <símbolos...>"
+fn parse_schedule_args(locale: Locale, args: &[&str]) -> Result<(Schedule, NaiveTime, Tz, Vec<String>), String> {
+    let usage = || i18n::t(locale, "digest.usage", &[]);
+    let (schedule, rest) = match args {
+        [kind, rest @ ..] if kind.eq_ignore_ascii_case("daily") => (Schedule::Daily, rest),
+        [kind, day, rest @ ..] if kind.eq_ignore_ascii_case("weekly") => match day.parse::<Weekday>() {
+            Ok(day) => (Schedule::Weekly(day), rest),
+            Err(_) => return Err(i18n::t(locale, "digest.invalid_day", &[("value", day)])),
+        },
+        _ => return Err(usage()),
+    };
+    let [time, timezone, symbols @ ..] = rest else {
+        return Err(usage());
+    };
+    let time = NaiveTime::parse_from_str(time, "%H:%M")
+        .map_err(|_| i18n::t(locale, "digest.invalid_time", &[("value", time)]))?;
+    // Los nombres de zona distinguen mayúsculas ("Europe/Madrid"), pero se acepta "europe/madrid"
+    let timezone = timezone.parse::<Tz>()
+        .or_else(|_| Tz::from_str_insensitive(timezone))
+        .map_err(|_| i18n::t(locale, "digest.invalid_timezone", &[("value", timezone)]))?;
+    if symbols.is_empty() {
+        return Err(usage());
+    }
+    if symbols.len() > MAX_DIGEST_PAIRS {
+        return Err(i18n::t(locale, "digest.too_many_symbols", &[("max", &MAX_DIGEST_PAIRS)]));
+    }
+    Ok((schedule, time, timezone, symbols.iter().map(|s| s.to_string()).collect()))
+}
+
+// Procesa "/digest ..." y devuelve el texto de respuesta
+pub async fn digest_command(prices: &PriceService, storage: &dyn Storage, chat_id: ChatId, locale: Locale, input: &str) -> String {
+    let args: Vec<&str> = input.split_whitespace().collect();
+    let digests = match storage.digests() {
+        Ok(digests) => digests.into_iter().filter(|d| d.chat_id == chat_id).collect::<Vec<_>>(),
+        Err(err) => {
+            log::error!("Could not load digests: {}", err);
+            return i18n::t(locale, "digest.load_failed", &[]);
+        }
+    };
+
+    match args.as_slice() {
+        [action] if action.eq_ignore_ascii_case("list") => {
+            if digests.is_empty() {
+                return i18n::t(locale, "digest.empty", &[]);
+            }
+            let mut lines = vec![i18n::t(locale, "digest.list_title", &[])];
+            for digest in &digests {
+                let next = digest.next_run.with_timezone(&digest.timezone).format("%Y-%m-%d %H:%M %Z").to_string();
+                lines.push(digest.describe(locale));
+                lines.push(i18n::t(locale, "digest.next", &[("time", &next)]));
+            }
+            lines.join("\n")
+        }
+        [action, id] if action.eq_ignore_ascii_case("cancel") => match id.trim_start_matches('#').parse::<u64>() {
+            Ok(id) => match storage.delete_digest(chat_id, id) {
+                Ok(true) => i18n::t(locale, "digest.cancelled", &[("id", &id)]),
+                Ok(false) => i18n::t(locale, "digest.not_found", &[("id", &id)]),
+                Err(err) => {
+                    log::error!("Could not delete digest #{}: {}", id, err);
+                    i18n::t(locale, "digest.cancel_failed", &[])
+                }
+            },
+            Err(_) => i18n::t(locale, "digest.cancel_usage", &[]),
+        },
+        _ => {
+            let (schedule, time, timezone, symbols) = match parse_schedule_args(locale, &args) {
+                Ok(args) => args,
+                Err(err) => return err,
+            };
+            if digests.len() >= MAX_DIGESTS_PER_CHAT {
+                return i18n::t(locale, "digest.limit", &[("max", &MAX_DIGESTS_PER_CHAT)]);
+            }
+            let mut pairs = Vec::new();
+            for symbol in symbols {
+                match prices.resolve(&symbol).await {
+                    Ok(pair) if !pairs.contains(&pair) => pairs.push(pair),
+                    Ok(_) => {}
+                    Err(err) => return i18n::t(locale, "error.resolve", &[("error", &err.user_message(locale))]),
+                }
+            }
+            let digest = NewDigest {
+                chat_id,
+                schedule,
+                time,
+                timezone,
+                pairs,
+                next_run: next_run(schedule, time, timezone, Utc::now()),
+            };
+            match storage.insert_digest(digest) {
+                Ok(digest) => i18n::t(locale, "digest.scheduled", &[
+                    ("digest", &digest.describe(locale)),
+                    ("time", &digest.next_run.with_timezone(&timezone).format("%Y-%m-%d %H:%M %Z").to_string()),
+                ]),
+                Err(err) => {
+                    log::error!("Could not save digest of chat {}: {}", chat_id, err);
+                    i18n::t(locale, "digest.save_failed", &[])
+                }
+            }
+        }
+    }
+}
+
+// Texto del resumen: precio, variación y máximo y mínimo de 24 horas de cada par, en una tabla HTML
+pub async fn digest_text(prices: &PriceService, digest: &Digest, locale: Locale) -> String {
+    let tickers = futures_util::future::join_all(digest.pairs.iter().map(|pair| prices.ticker_24h(pair))).await;
+    let header = |key: &str| i18n::t(locale, key, &[]);
+    let symbol_width = digest.pairs.iter().map(|p| p.to_string().len()).max().unwrap_or(0)
+        .max(header("table.symbol").chars().count());
+    let mut rows = vec![format!("{:<width$}  {:>12}  {:>8}  {:>12}  {:>12}", header("table.symbol"), header("table.price"),
+        header("table.change"), header("table.high"), header("table.low"), width = symbol_width)];
+    for (pair, ticker) in digest.pairs.iter().zip(tickers) {
+        match ticker {
+            Ok(quote) => rows.push(format!("{:<width$}  {:>12}  {:>7}%  {:>12}  {:>12}", pair.to_string(),
+                i18n::price(locale, quote.ticker.last), i18n::number(locale, quote.ticker.change_percent, 2, true),
+                i18n::price(locale, quote.ticker.high), i18n::price(locale, quote.ticker.low), width = symbol_width)),
+            Err(err) => {
+                log::warn!("Digest #{} could not fetch {}: {}", digest.id, pair, err);
+                rows.push(format!("{:<width$}  {:>12}", pair.to_string(), header("table.unavailable"), width = symbol_width));
+            }
+        }
+    }
+    let title = match digest.schedule {
+        Schedule::Daily => header("digest.title.daily"),
+        Schedule::Weekly(_) => header("digest.title.weekly"),
+    };
+    let now = Utc::now().with_timezone(&digest.timezone);
+    format!("<b>{}</b> — {} {}\n<pre>{}</pre>", title, weekday_name(locale, now.weekday()),
+        now.format("%Y-%m-%d %H:%M %Z"), rows.join("\n"))
+}
+
+// Qué hacer en esta vuelta con un resumen cuya hora ya pasó
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Due {
+    // Se envía, aunque sea con algo de retraso
+    Send,
+    // Se perdió hace más del retraso admitido (el indicado) y se salta
+    Missed(Duration),
+}
+
+// Resúmenes cuya hora de envío llegó en `now`
+fn due_digests(digests: &[Digest], now: DateTime<Utc>, max_delay: Duration) -> Vec<(&Digest, Due)> {
+    digests.iter()
+        .filter(|d| d.next_run <= now)
+        .map(|digest| {
+            let late = (now - digest.next_run).to_std().unwrap_or_default();
+            (digest, if late > max_delay { Due::Missed(late) } else { Due::Send })
+        })
+        .collect()
+}
+
+// Programa el siguiente envío a partir de `now`, de modo que los perdidos no se recuperan uno a uno
+fn reschedule(storage: &dyn Storage, digest: &Digest, now: DateTime<Utc>) {
+    let next = next_run(digest.schedule, digest.time, digest.timezone, now);
+    if let Err(err) = storage.set_digest_next_run(digest.id, next) {
+        log::error!("Could not reschedule digest #{}: {}", digest.id, err);
+    }
+}
+
+// Tarea que envía los resúmenes pendientes. Tras una caída o un reinicio, un resumen que se perdió
+// hace menos de `max_delay` se envía una sola vez con retraso; si se perdió hace más se salta
+// y se programa el siguiente. Nunca se envían varios seguidos para recuperar los perdidos.
+pub async fn run(bot: Bot, prices: Arc<PriceService>, storage: Arc<dyn Storage>, interval: Duration, max_delay: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+        let digests = match storage.digests() {
+            Ok(digests) => digests,
+            Err(err) => {
+                log::error!("Could not load digests: {}", err);
+                continue;
+            }
+        };
+
+        let now = Utc::now();
+        for (digest, due) in due_digests(&digests, now, max_delay) {
+            if let Due::Missed(late) = due {
+                log::warn!("Skipping digest #{} of chat {}, missed by {} minutes",
+                    digest.id, digest.chat_id, late.as_secs() / 60);
+            } else {
+                // Sin nadie que escriba, el idioma es el elegido para el chat con /lang o el de por defecto
+                let locale = i18n::locale_for(storage.as_ref(), digest.chat_id, None);
+                let text = digest_text(&prices, digest, locale).await;
+                match bot.send_message(digest.chat_id, text).parse_mode(ParseMode::Html).await {
+                    Ok(_) => {}
+                    // El bot ya no puede escribir en el chat: el resumen no tiene sentido
+                    Err(RequestError::Api(ApiError::BotBlocked | ApiError::ChatNotFound | ApiError::BotKicked
+                        | ApiError::BotKickedFromSupergroup)) => {
+                        log::warn!("Removing digest #{}, chat {} is no longer reachable", digest.id, digest.chat_id);
+                        if let Err(err) = storage.delete_digest(digest.chat_id, digest.id) {
+                            log::error!("Could not delete digest #{}: {}", digest.id, err);
+                        }
+                        continue;
+                    }
+                    Err(err) => log::error!("Could not deliver digest #{} to chat {}: {}", digest.id, digest.chat_id, err),
+                }
+            }
+            reschedule(storage.as_ref(), digest, now);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono_tz::Europe::Madrid;
+
+    use super::*;
+    use crate::storage::MemoryStorage;
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value).unwrap().with_timezone(&Utc)
+    }
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    fn time(value: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(value, "%H:%M").unwrap()
+    }
+
+    #[test]
+    fn local_times_skipped_by_the_spring_change_move_one_hour_later() {
+        // El 29 de marzo de 2026 en Madrid se pasa de las 02:00 CET a las 03:00 CEST
+        assert_eq!(local_instant(Madrid, date("2026-03-29"), time("02:30")), utc("2026-03-29T01:30:00Z"));
+        assert_eq!(local_instant(Madrid, date("2026-03-29"), time("03:00")), utc("2026-03-29T01:00:00Z"));
+        assert_eq!(local_instant(Madrid, date("2026-03-29"), time("01:59")), utc("2026-03-29T00:59:00Z"));
+    }
+
+    #[test]
+    fn local_times_repeated_by_the_autumn_change_use_the_first_one() {
+        // El 25 de octubre de 2026 las 02:00-03:00 ocurren dos veces; vale la primera, aún en CEST
+        assert_eq!(local_instant(Madrid, date("2026-10-25"), time("02:30")), utc("2026-10-25T00:30:00Z"));
+        assert_eq!(local_instant(Madrid, date("2026-10-25"), time("03:00")), utc("2026-10-25T02:00:00Z"));
+    }
+
+    #[test]
+    fn daily_runs_keep_the_local_time_across_dst_changes() {
+        let eight = time("08:00");
+        let spring = next_run(Schedule::Daily, eight, Madrid, utc("2026-03-28T07:00:00Z"));
+        assert_eq!(spring, utc("2026-03-29T06:00:00Z"));
+        let autumn = next_run(Schedule::Daily, eight, Madrid, utc("2026-10-24T06:00:00Z"));
+        assert_eq!(autumn, utc("2026-10-25T07:00:00Z"));
+
+        // Un envío a una hora que se repite no se programa dos veces el mismo día
+        let half_past_two = time("02:30");
+        let first = next_run(Schedule::Daily, half_past_two, Madrid, utc("2026-10-24T12:00:00Z"));
+        assert_eq!(first, utc("2026-10-25T00:30:00Z"));
+        assert_eq!(next_run(Schedule::Daily, half_past_two, Madrid, first), utc("2026-10-26T01:30:00Z"));
+    }
+
+    #[test]
+    fn weekly_runs_roll_over_to_the_next_week() {
+        let monday = Schedule::Weekly(Weekday::Mon);
+        let half_past_nine = time("09:30");
+        // 2026-10-19 es lunes
+        assert_eq!(next_run(monday, half_past_nine, Tz::UTC, utc("2026-10-19T09:29:00Z")), utc("2026-10-19T09:30:00Z"));
+        assert_eq!(next_run(monday, half_past_nine, Tz::UTC, utc("2026-10-19T09:30:00Z")), utc("2026-10-26T09:30:00Z"));
+        assert_eq!(next_run(monday, half_past_nine, Tz::UTC, utc("2026-10-20T00:00:00Z")), utc("2026-10-26T09:30:00Z"));
+        assert_eq!(next_run(monday, half_past_nine, Tz::UTC, utc("2026-10-18T23:59:00Z")), utc("2026-10-19T09:30:00Z"));
+
+        // El día de la semana se mira en la zona del resumen: el domingo 23:30 UTC ya es lunes en Madrid
+        let after = utc("2026-10-18T23:30:00Z");
+        assert_eq!(next_run(monday, time("00:30"), Madrid, after), utc("2026-10-25T23:30:00Z"));
+    }
+
+    fn stored_digest(storage: &dyn Storage, next_run: DateTime<Utc>) -> Digest {
+        storage.insert_digest(NewDigest {
+            chat_id: ChatId(1),
+            schedule: Schedule::Daily,
+            time: time("08:00"),
+            timezone: Tz::UTC,
+            pairs: vec![Pair::new("BTC", "USDT")],
+            next_run,
+        }).unwrap()
+    }
+
+    #[test]
+    fn a_missed_run_is_sent_once_and_then_rescheduled() {
+        let storage = MemoryStorage::default();
+        storage.migrate().unwrap();
+        let max_delay = Duration::from_secs(60 * 60);
+        let digest = stored_digest(&storage, utc("2026-10-18T08:00:00Z"));
+
+        // El bot vuelve 20 minutos tarde: se envía una vez con retraso
+        let now = utc("2026-10-18T08:20:00Z");
+        let digests = storage.digests().unwrap();
+        let due = due_digests(&digests, now, max_delay);
+        assert_eq!(due.iter().map(|(d, due)| (d.id, *due)).collect::<Vec<_>>(), vec![(digest.id, Due::Send)]);
+        reschedule(&storage, due[0].0, now);
+
+        // En las vueltas siguientes ya no hay nada pendiente hasta el día siguiente
+        let digests = storage.digests().unwrap();
+        assert_eq!(digests[0].next_run, utc("2026-10-19T08:00:00Z"));
+        assert!(due_digests(&digests, utc("2026-10-18T08:21:00Z"), max_delay).is_empty());
+        assert_eq!(due_digests(&digests, utc("2026-10-19T08:00:00Z"), max_delay).len(), 1);
+    }
+
+    #[test]
+    fn runs_missed_for_too_long_are_skipped_without_catching_up() {
+        let storage = MemoryStorage::default();
+        storage.migrate().unwrap();
+        let max_delay = Duration::from_secs(60 * 60);
+        // Tres días caído: no se envían los tres resúmenes perdidos, solo se programa el siguiente
+        stored_digest(&storage, utc("2026-10-15T08:00:00Z"));
+        let now = utc("2026-10-18T09:00:00Z");
+
+        let digests = storage.digests().unwrap();
+        let due = due_digests(&digests, now, max_delay);
+        assert_eq!(due.len(), 1);
+        assert!(matches!(due[0].1, Due::Missed(late) if late > max_delay));
+        reschedule(&storage, due[0].0, now);
+
+        let digests = storage.digests().unwrap();
+        assert_eq!(digests[0].next_run, utc("2026-10-19T08:00:00Z"));
+        assert!(due_digests(&digests, now, max_delay).is_empty());
+    }
+}
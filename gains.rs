@@ -0,0 +1,387 @@
+// Cálculo de ganancias realizadas y posiciones abiertas a partir del registro de operaciones.
+// Es puro a propósito: no consulta precios ni almacenamiento, solo recorre las operaciones en orden
+// y va consumiendo lotes según el método elegido (FIFO, LIFO o coste medio).
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+use crate::providers::Pair;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeKind {
+    Buy,
+    Sell,
+    // Comisión pagada en el propio activo (p. ej. al retirarlo): sale de la posición sin ingresos
+    Fee,
+}
+
+impl fmt::Display for TradeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TradeKind::Buy => write!(f, "buy"),
+            TradeKind::Sell => write!(f, "sell"),
+            TradeKind::Fee => write!(f, "fee"),
+        }
+    }
+}
+
+impl FromStr for TradeKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "buy" => Ok(TradeKind::Buy),
+            "sell" => Ok(TradeKind::Sell),
+            "fee" => Ok(TradeKind::Fee),
+            other => Err(format!("unknown trade kind '{}'", other)),
+        }
+    }
+}
+
+// Operación del registro. La cantidad está en el activo base y el precio y la comisión en el de
+// cotización; en las compras la comisión se suma al coste y en las ventas se resta de lo cobrado.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trade {
+    pub id: u64,
+    pub time: DateTime<Utc>,
+    pub kind: TradeKind,
+    pub pair: Pair,
+    pub quantity: Decimal,
+    pub price: Decimal,
+    pub fee: Decimal,
+}
+
+// Método para decidir qué lotes se consumen en cada venta
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CostMethod {
+    // Primero los lotes más antiguos
+    #[default]
+    Fifo,
+    // Primero los más recientes
+    Lifo,
+    // Un único lote con el coste medio de todas las compras
+    Average,
+}
+
+impl fmt::Display for CostMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CostMethod::Fifo => write!(f, "fifo"),
+            CostMethod::Lifo => write!(f, "lifo"),
+            CostMethod::Average => write!(f, "average"),
+        }
+    }
+}
+
+impl FromStr for CostMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "fifo" => Ok(CostMethod::Fifo),
+            "lifo" => Ok(CostMethod::Lifo),
+            "average" | "avg" => Ok(CostMethod::Average),
+            other => Err(format!("unknown cost method '{}', use fifo, lifo or average", other)),
+        }
+    }
+}
+
+// Cantidad comprada a un mismo coste unitario que todavía no se ha vendido
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lot {
+    pub quantity: Decimal,
+    pub unit_cost: Decimal,
+}
+
+// Lotes abiertos de un par
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Position {
+    pub lots: VecDeque<Lot>,
+}
+
+impl Position {
+    pub fn quantity(&self) -> Decimal {
+        self.lots.iter().map(|lot| lot.quantity).sum()
+    }
+
+    // Coste pendiente de los lotes abiertos
+    pub fn cost(&self) -> Decimal {
+        self.lots.iter().map(|lot| lot.quantity * lot.unit_cost).sum()
+    }
+
+    fn add(&mut self, quantity: Decimal, cost: Decimal, method: CostMethod) {
+        match (method, self.lots.front_mut()) {
+            // Con coste medio se mantiene un único lote y se recalcula su coste unitario
+            (CostMethod::Average, Some(lot)) => {
+                let total = lot.quantity + quantity;
+                lot.unit_cost = (lot.quantity * lot.unit_cost + cost) / total;
+                lot.quantity = total;
+            }
+            _ => self.lots.push_back(Lot { quantity, unit_cost: cost / quantity }),
+        }
+    }
+
+    // Saca `quantity` de los lotes según el método y devuelve su coste; los lotes se consumen
+    // enteros o en parte y los que quedan vacíos se eliminan
+    fn remove(&mut self, mut quantity: Decimal, method: CostMethod) -> Decimal {
+        let mut cost = Decimal::ZERO;
+        while !quantity.is_zero() {
+            let lot = match method {
+                CostMethod::Lifo => self.lots.back_mut(),
+                CostMethod::Fifo | CostMethod::Average => self.lots.front_mut(),
+            };
+            let Some(lot) = lot else { break };
+            let taken = quantity.min(lot.quantity);
+            cost += taken * lot.unit_cost;
+            lot.quantity -= taken;
+            quantity -= taken;
+            if lot.quantity.is_zero() {
+                match method {
+                    CostMethod::Lifo => self.lots.pop_back(),
+                    CostMethod::Fifo | CostMethod::Average => self.lots.pop_front(),
+                };
+            }
+        }
+        cost
+    }
+}
+
+// Venta (o comisión en el activo) con la ganancia que realiza
+#[derive(Debug, Clone, PartialEq)]
+pub struct Disposal {
+    pub trade_id: u64,
+    pub time: DateTime<Utc>,
+    pub pair: Pair,
+    pub quantity: Decimal,
+    pub proceeds: Decimal,
+    pub cost: Decimal,
+}
+
+impl Disposal {
+    pub fn gain(&self) -> Decimal {
+        self.proceeds - self.cost
+    }
+}
+
+// Resultado de recorrer el registro completo
+#[derive(Debug, Clone, Default)]
+pub struct Gains {
+    pub positions: BTreeMap<Pair, Position>,
+    pub disposals: Vec<Disposal>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GainsError {
+    // Se vende más de lo que hay en la posición en ese momento
+    Oversold { trade_id: u64, pair: Pair, available: Decimal, requested: Decimal },
+    // Cantidad cero o negativa, o precio o comisión negativos
+    InvalidTrade { trade_id: u64 },
+}
+
+impl fmt::Display for GainsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GainsError::Oversold { trade_id, pair, available, requested } =>
+                write!(f, "trade #{} disposes of {} {} but only {} is held at that point",
+                    trade_id, requested.normalize(), pair.base, available.normalize()),
+            GainsError::InvalidTrade { trade_id } =>
+                write!(f, "trade #{} has an invalid quantity, price or fee", trade_id),
+        }
+    }
+}
+
+impl std::error::Error for GainsError {}
+
+// Recorre las operaciones por fecha (y por id si coinciden) y calcula los lotes abiertos y las
+// ganancias realizadas de cada venta
+pub fn compute(trades: &[Trade], method: CostMethod) -> Result<Gains, GainsError> {
+    let mut ordered: Vec<&Trade> = trades.iter().collect();
+    ordered.sort_by_key(|trade| (trade.time, trade.id));
+
+    let mut gains = Gains::default();
+    for trade in ordered {
+        if trade.quantity <= Decimal::ZERO || trade.price.is_sign_negative() || trade.fee.is_sign_negative() {
+            return Err(GainsError::InvalidTrade { trade_id: trade.id });
+        }
+        let position = gains.positions.entry(trade.pair.clone()).or_default();
+        match trade.kind {
+            TradeKind::Buy => position.add(trade.quantity, trade.quantity * trade.price + trade.fee, method),
+            TradeKind::Sell | TradeKind::Fee => {
+                let available = position.quantity();
+                if trade.quantity > available {
+                    return Err(GainsError::Oversold {
+                        trade_id: trade.id,
+                        pair: trade.pair.clone(),
+                        available,
+                        requested: trade.quantity,
+                    });
+                }
+                let cost = position.remove(trade.quantity, method);
+                let proceeds = match trade.kind {
+                    TradeKind::Sell => trade.quantity * trade.price - trade.fee,
+                    _ => Decimal::ZERO,
+                };
+                gains.disposals.push(Disposal {
+                    trade_id: trade.id,
+                    time: trade.time,
+                    pair: trade.pair.clone(),
+                    quantity: trade.quantity,
+                    proceeds,
+                    cost,
+                });
+            }
+        }
+    }
+    gains.positions.retain(|_, position| !position.lots.is_empty());
+    Ok(gains)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    fn btc() -> Pair {
+        Pair::new("BTC", "USDT")
+    }
+
+    fn trade(id: u64, minute: i64, kind: TradeKind, quantity: &str, price: &str, fee: &str) -> Trade {
+        Trade {
+            id,
+            time: DateTime::from_timestamp(1_700_000_000 + minute * 60, 0).unwrap(),
+            kind,
+            pair: btc(),
+            quantity: d(quantity),
+            price: d(price),
+            fee: d(fee),
+        }
+    }
+
+    // Dos compras a 100 y 200 y una venta de 1.5 a 300
+    fn ledger() -> Vec<Trade> {
+        vec![
+            trade(1, 0, TradeKind::Buy, "1", "100", "0"),
+            trade(2, 1, TradeKind::Buy, "1", "200", "0"),
+            trade(3, 2, TradeKind::Sell, "1.5", "300", "0"),
+        ]
+    }
+
+    #[test]
+    fn fifo_consumes_oldest_lots_first() {
+        let gains = compute(&ledger(), CostMethod::Fifo).unwrap();
+        let sale = &gains.disposals[0];
+        assert_eq!((sale.proceeds, sale.cost, sale.gain()), (d("450"), d("200"), d("250")));
+        let position = &gains.positions[&btc()];
+        assert_eq!((position.quantity(), position.cost()), (d("0.5"), d("100")));
+    }
+
+    #[test]
+    fn lifo_consumes_newest_lots_first() {
+        let gains = compute(&ledger(), CostMethod::Lifo).unwrap();
+        assert_eq!(gains.disposals[0].gain(), d("200"));
+        let position = &gains.positions[&btc()];
+        assert_eq!((position.quantity(), position.cost()), (d("0.5"), d("50")));
+    }
+
+    #[test]
+    fn average_keeps_a_single_lot() {
+        let gains = compute(&ledger(), CostMethod::Average).unwrap();
+        assert_eq!(gains.disposals[0].gain(), d("225"));
+        let position = &gains.positions[&btc()];
+        assert_eq!(position.lots.len(), 1);
+        assert_eq!((position.quantity(), position.cost()), (d("0.5"), d("75")));
+    }
+
+    #[test]
+    fn lot_is_consumed_partly_across_two_sales() {
+        let trades = vec![
+            trade(1, 0, TradeKind::Buy, "2", "100", "0"),
+            trade(2, 1, TradeKind::Buy, "1", "400", "0"),
+            trade(3, 2, TradeKind::Sell, "0.5", "200", "0"),
+            trade(4, 3, TradeKind::Sell, "2", "300", "0"),
+        ];
+        let gains = compute(&trades, CostMethod::Fifo).unwrap();
+        // La primera venta deja 1.5 del primer lote y la segunda lo termina y entra en el segundo
+        assert_eq!(gains.disposals[0].cost, d("50"));
+        assert_eq!(gains.disposals[1].cost, d("350"));
+        assert_eq!(gains.positions[&btc()].lots, VecDeque::from([Lot { quantity: d("0.5"), unit_cost: d("400") }]));
+    }
+
+    #[test]
+    fn selling_everything_closes_the_position() {
+        let mut trades = ledger();
+        trades.push(trade(4, 3, TradeKind::Sell, "0.5", "300", "1"));
+        let gains = compute(&trades, CostMethod::Fifo).unwrap();
+        assert!(gains.positions.is_empty());
+        // La comisión de la venta se resta de lo cobrado
+        assert_eq!(gains.disposals[1].proceeds, d("149"));
+    }
+
+    #[test]
+    fn fee_disposal_has_no_proceeds() {
+        let trades = vec![
+            trade(1, 0, TradeKind::Buy, "1", "100", "1"),
+            trade(2, 1, TradeKind::Fee, "0.1", "0", "0"),
+        ];
+        let gains = compute(&trades, CostMethod::Fifo).unwrap();
+        let fee = &gains.disposals[0];
+        // La comisión de la compra forma parte del coste: 101 por unidad
+        assert_eq!((fee.proceeds, fee.cost, fee.gain()), (Decimal::ZERO, d("10.1"), d("-10.1")));
+        assert_eq!(gains.positions[&btc()].quantity(), d("0.9"));
+    }
+
+    #[test]
+    fn overselling_is_an_error() {
+        let trades = vec![
+            trade(1, 0, TradeKind::Buy, "1", "100", "0"),
+            trade(2, 1, TradeKind::Sell, "2", "100", "0"),
+        ];
+        assert_eq!(compute(&trades, CostMethod::Fifo).unwrap_err(),
+            GainsError::Oversold { trade_id: 2, pair: btc(), available: d("1"), requested: d("2") });
+    }
+
+    #[test]
+    fn selling_before_buying_is_an_error() {
+        let trades = vec![
+            trade(1, 1, TradeKind::Buy, "1", "100", "0"),
+            trade(2, 0, TradeKind::Sell, "1", "100", "0"),
+        ];
+        assert!(matches!(compute(&trades, CostMethod::Lifo),
+            Err(GainsError::Oversold { trade_id: 2, available, .. }) if available.is_zero()));
+    }
+
+    #[test]
+    fn invalid_trades_are_rejected() {
+        let zero = vec![trade(7, 0, TradeKind::Buy, "0", "100", "0")];
+        assert_eq!(compute(&zero, CostMethod::Fifo).unwrap_err(), GainsError::InvalidTrade { trade_id: 7 });
+        let negative_price = vec![trade(8, 0, TradeKind::Buy, "1", "-100", "0")];
+        assert_eq!(compute(&negative_price, CostMethod::Fifo).unwrap_err(), GainsError::InvalidTrade { trade_id: 8 });
+        let negative_fee = vec![trade(9, 0, TradeKind::Buy, "1", "100", "-1")];
+        assert_eq!(compute(&negative_fee, CostMethod::Fifo).unwrap_err(), GainsError::InvalidTrade { trade_id: 9 });
+    }
+
+    #[test]
+    fn equal_times_are_ordered_by_id() {
+        // La venta tiene menor id que la compra y la misma hora, así que va antes y no hay nada que vender
+        let sale_first = vec![
+            trade(2, 0, TradeKind::Buy, "1", "100", "0"),
+            trade(1, 0, TradeKind::Sell, "1", "100", "0"),
+        ];
+        assert!(matches!(compute(&sale_first, CostMethod::Fifo), Err(GainsError::Oversold { trade_id: 1, .. })));
+
+        // Dos compras a la misma hora: FIFO consume primero la de menor id aunque llegue después
+        let trades = vec![
+            trade(5, 0, TradeKind::Buy, "1", "100", "0"),
+            trade(3, 0, TradeKind::Buy, "1", "200", "0"),
+            trade(6, 0, TradeKind::Sell, "1", "300", "0"),
+        ];
+        let gains = compute(&trades, CostMethod::Fifo).unwrap();
+        assert_eq!(gains.disposals[0].cost, d("200"));
+    }
+}
@@ -0,0 +1,149 @@
+// Historial de precios en memoria por par, alimentado por una tarea de muestreo.
+// Lo usan las alertas de movimiento porcentual para calcular la variación dentro de una ventana móvil.
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rust_decimal::Decimal;
+use tokio::time::Instant;
+
+use crate::alerts::Alerts;
+use crate::prices::PriceService;
+use crate::providers::Pair;
+
+// Ventana máxima que se conserva; es también la ventana más larga que admite /movealert
+pub const MAX_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    at: Instant,
+    price: Decimal,
+}
+
+// Resumen de los precios de una ventana
+#[derive(Debug, Clone, Copy)]
+pub struct WindowRange {
+    pub low: Decimal,
+    pub high: Decimal,
+    pub last: Decimal,
+}
+
+impl WindowRange {
+    // Mayor movimiento dentro de la ventana, en porcentaje: subida desde el mínimo (positivo)
+    // o caída desde el máximo (negativo), la que sea mayor en valor absoluto
+    pub fn move_pct(&self) -> Decimal {
+        let up = if self.low.is_zero() { Decimal::ZERO } else { (self.last - self.low) / self.low * Decimal::ONE_HUNDRED };
+        let down = if self.high.is_zero() { Decimal::ZERO } else { (self.last - self.high) / self.high * Decimal::ONE_HUNDRED };
+        if up >= down.abs() { up } else { down }
+    }
+}
+
+#[derive(Default)]
+pub struct PriceHistory {
+    samples: Mutex<HashMap<Pair, VecDeque<Sample>>>,
+}
+
+impl PriceHistory {
+    // Añade una muestra y descarta las que ya no caben en la ventana máxima
+    pub fn record(&self, pair: &Pair, price: Decimal) {
+        let now = Instant::now();
+        let mut samples = self.samples.lock().unwrap();
+        let series = samples.entry(pair.clone()).or_default();
+        series.push_back(Sample { at: now, price });
+        while series.front().is_some_and(|s| now.duration_since(s.at) > MAX_WINDOW) {
+            series.pop_front();
+        }
+    }
+
+    // Olvida los pares que ya no se vigilan
+    pub fn retain(&self, pairs: &[Pair]) {
+        self.samples.lock().unwrap().retain(|pair, _| pairs.contains(pair));
+    }
+
+    // Mínimo, máximo y último precio de un par dentro de la ventana indicada
+    pub fn range(&self, pair: &Pair, window: Duration) -> Option<WindowRange> {
+        let samples = self.samples.lock().unwrap();
+        let series = samples.get(pair)?;
+        let now = Instant::now();
+        let mut in_window = series.iter().filter(|s| now.duration_since(s.at) <= window);
+        let first = in_window.next()?;
+        let mut range = WindowRange { low: first.price, high: first.price, last: first.price };
+        for sample in in_window {
+            range.low = range.low.min(sample.price);
+            range.high = range.high.max(sample.price);
+            range.last = sample.price;
+        }
+        Some(range)
+    }
+}
+
+// Interpreta una ventana de tiempo como "90s", "15m", "1h" o "1d"
+pub fn parse_window(input: &str) -> Option<Duration> {
+    let input = input.trim().to_lowercase();
+    let split = input.find(|c: char| !c.is_ascii_digit())?;
+    let (amount, unit) = input.split_at(split);
+    let amount: u64 = amount.parse().ok()?;
+    // Una cantidad que desborda al pasarla a segundos es una ventana inválida
+    let seconds = match unit {
+        "s" => Some(amount),
+        "m" => amount.checked_mul(60),
+        "h" => amount.checked_mul(60 * 60),
+        "d" => amount.checked_mul(24 * 60 * 60),
+        _ => return None,
+    }?;
+    (seconds > 0).then(|| Duration::from_secs(seconds))
+}
+
+// Representación corta de una ventana, inversa de parse_window
+pub fn format_window(window: Duration) -> String {
+    let seconds = window.as_secs();
+    if seconds.is_multiple_of(24 * 60 * 60) {
+        format!("{}d", seconds / (24 * 60 * 60))
+    } else if seconds.is_multiple_of(60 * 60) {
+        format!("{}h", seconds / (60 * 60))
+    } else if seconds.is_multiple_of(60) {
+        format!("{}m", seconds / 60)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+// Tarea de muestreo: cada `interval` guarda el precio de los pares con alertas de movimiento
+pub async fn sample(prices: Arc<PriceService>, alerts: Arc<Alerts>, history: Arc<PriceHistory>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+
+        let pairs = alerts.move_pairs();
+        history.retain(&pairs);
+        let quotes = futures_util::future::join_all(pairs.iter().map(|pair| prices.price(pair))).await;
+        for (pair, quote) in pairs.iter().zip(quotes) {
+            match quote {
+                Ok(quote) => history.record(pair, quote.price),
+                Err(err) => log::warn!("Price sampler could not fetch {}: {}", pair, err),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windows_round_trip_through_format_window() {
+        for (input, seconds) in [("90s", 90), ("15m", 15 * 60), ("1h", 60 * 60), ("2D", 2 * 24 * 60 * 60)] {
+            let window = parse_window(input).unwrap();
+            assert_eq!(window, Duration::from_secs(seconds));
+            assert_eq!(parse_window(&format_window(window)), Some(window));
+        }
+    }
+
+    #[test]
+    fn invalid_and_overflowing_windows_are_rejected() {
+        for input in ["", "h", "0m", "-1h", "1w", "1.5h", "999999999999999999m", "99999999999999999d"] {
+            assert_eq!(parse_window(input), None, "{} was accepted", input);
+        }
+    }
+}
@@ -0,0 +1,785 @@
+// Traducciones de los mensajes del bot. Cada idioma tiene un catálogo de claves con textos que llevan
+// marcadores con nombre ("{pair}"), para que cada idioma pueda ordenar las partes a su manera. Si una
+// clave falta en un catálogo se usa la versión en inglés. El idioma de cada respuesta sale del ajuste
+// del chat (/lang) o, si no hay, del idioma de Telegram de quien escribe.
+use std::fmt;
+use std::str::FromStr;
+
+use rust_decimal::{Decimal, RoundingStrategy};
+use teloxide::types::ChatId;
+
+use crate::prices::{format_change, format_price};
+use crate::providers::ProviderError;
+use crate::storage::Storage;
+
+// Clave del ajuste de chat con el idioma elegido con /lang
+const LOCALE_SETTING: &str = "locale";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    #[default]
+    En,
+    Es,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::Es];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Es => "es",
+        }
+    }
+
+    // Nombre del idioma en ese mismo idioma
+    pub fn name(&self) -> &'static str {
+        match self {
+            Locale::En => "English",
+            Locale::Es => "Español",
+        }
+    }
+
+    // Acepta el código de idioma que manda Telegram ("es", "es-ES", "en-GB"...)
+    pub fn from_code(code: &str) -> Option<Locale> {
+        let language = code.split(['-', '_']).next().unwrap_or_default().to_lowercase();
+        Locale::ALL.into_iter().find(|locale| locale.code() == language)
+    }
+
+    // DEFAULT_LOCALE (por defecto en) es el idioma cuando no se sabe el de quien escribe
+    pub fn from_env() -> Locale {
+        std::env::var("DEFAULT_LOCALE").ok()
+            .and_then(|code| Locale::from_code(&code))
+            .unwrap_or_default()
+    }
+
+    fn catalog(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Locale::En => EN,
+            Locale::Es => ES,
+        }
+    }
+
+    // Separadores de miles y de decimales
+    fn separators(&self) -> (char, char) {
+        match self {
+            Locale::En => (',', '.'),
+            Locale::Es => ('.', ','),
+        }
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+// Idioma de una respuesta: el elegido para el chat con /lang o, si no hay, el del usuario
+pub fn locale_for(storage: &dyn Storage, chat_id: ChatId, language_code: Option<&str>) -> Locale {
+    match storage.chat_setting(chat_id, LOCALE_SETTING) {
+        Ok(Some(code)) => if let Some(locale) = Locale::from_code(&code) {
+            return locale;
+        },
+        Ok(None) => {}
+        Err(err) => log::error!("Could not load locale of chat {}: {}", chat_id, err),
+    }
+    user_locale(language_code)
+}
+
+// Idioma de Telegram de un usuario, para las respuestas sin chat (modo inline)
+pub fn user_locale(language_code: Option<&str>) -> Locale {
+    language_code.and_then(Locale::from_code).unwrap_or_else(Locale::from_env)
+}
+
+// Procesa "/lang [en|es|auto]": sin argumento muestra el idioma del chat
+pub fn lang_command(storage: &dyn Storage, chat_id: ChatId, language_code: Option<&str>, input: &str) -> String {
+    let input = input.trim();
+    let current = locale_for(storage, chat_id, language_code);
+    if input.is_empty() {
+        let fixed = matches!(storage.chat_setting(chat_id, LOCALE_SETTING), Ok(Some(_)));
+        let key = if fixed { "lang.current" } else { "lang.current_auto" };
+        return t(current, key, &[("language", &current.name())]);
+    }
+    let saved = if input.eq_ignore_ascii_case("auto") {
+        storage.remove_chat_setting(chat_id, LOCALE_SETTING).map(|_| user_locale(language_code))
+    } else if let Some(locale) = Locale::from_code(input) {
+        storage.set_chat_setting(chat_id, LOCALE_SETTING, locale.code()).map(|_| locale)
+    } else {
+        return t(current, "lang.usage", &[]);
+    };
+    match saved {
+        Ok(locale) if input.eq_ignore_ascii_case("auto") => t(locale, "lang.auto", &[]),
+        Ok(locale) => t(locale, "lang.set", &[("language", &locale.name())]),
+        Err(err) => {
+            log::error!("Could not save locale of chat {}: {}", chat_id, err);
+            t(current, "lang.save_failed", &[])
+        }
+    }
+}
+
+// Texto de una clave con sus marcadores sustituidos
+pub fn t(locale: Locale, key: &str, args: &[(&str, &(dyn fmt::Display + Sync))]) -> String {
+    let template = lookup(locale.catalog(), key)
+        .or_else(|| lookup(EN, key))
+        .unwrap_or_else(|| {
+            log::warn!("Missing translation key '{}'", key);
+            key
+        });
+    let mut text = template.to_string();
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    text
+}
+
+// Como t, pero con la variante en singular ("clave.one") cuando `count` es 1; el número va en {count}
+pub fn tn(locale: Locale, key: &str, count: usize, args: &[(&str, &(dyn fmt::Display + Sync))]) -> String {
+    let key = if count == 1 { format!("{}.one", key) } else { key.to_string() };
+    let mut args = args.to_vec();
+    args.push(("count", &count));
+    t(locale, &key, &args)
+}
+
+// Texto de una clave solo si el idioma la tiene, para los textos que ya tienen su versión en inglés en otro sitio
+pub fn translation(locale: Locale, key: &str) -> Option<&'static str> {
+    lookup(locale.catalog(), key)
+}
+
+fn lookup(catalog: &'static [(&'static str, &'static str)], key: &str) -> Option<&'static str> {
+    catalog.iter().find(|(k, _)| *k == key).map(|(_, text)| *text)
+}
+
+// Aplica los separadores del idioma a un número ya formateado con punto decimal ("-65000.5")
+fn localize_number(locale: Locale, formatted: &str) -> String {
+    let (thousands, decimal) = locale.separators();
+    let (sign, digits) = match formatted.strip_prefix(['-', '+']) {
+        Some(rest) => (&formatted[..1], rest),
+        None => ("", formatted),
+    };
+    let (integer, fraction) = digits.split_once('.').map_or((digits, None), |(i, f)| (i, Some(f)));
+    let mut grouped = String::new();
+    for (index, digit) in integer.chars().enumerate() {
+        if index > 0 && (integer.len() - index) % 3 == 0 {
+            grouped.push(thousands);
+        }
+        grouped.push(digit);
+    }
+    match fraction {
+        Some(fraction) => format!("{}{}{}{}", sign, grouped, decimal, fraction),
+        None => format!("{}{}", sign, grouped),
+    }
+}
+
+// Precio con la misma precisión que format_price y los separadores del idioma
+pub fn price(locale: Locale, value: Decimal) -> String {
+    localize_number(locale, &format_price(value))
+}
+
+// Variación con signo explícito, como format_change
+pub fn change(locale: Locale, value: Decimal) -> String {
+    localize_number(locale, &format_change(value))
+}
+
+// Cantidad sin ceros sobrantes ("0.5", "1200"), con los separadores del idioma
+pub fn amount(locale: Locale, value: Decimal) -> String {
+    localize_number(locale, &value.normalize().to_string())
+}
+
+// Número con los decimales indicados y signo explícito si se pide (porcentajes). Se redondea la
+// mitad hacia arriba ("2.345" -> "2.35") y lo que queda en cero se muestra sin signo
+pub fn number(locale: Locale, value: Decimal, decimals: u32, signed: bool) -> String {
+    let mut rounded = value.round_dp_with_strategy(decimals, RoundingStrategy::MidpointAwayFromZero);
+    if rounded.is_zero() {
+        rounded.set_sign_positive(true);
+    }
+    let formatted = if signed && rounded > Decimal::ZERO {
+        format!("+{:.*}", decimals as usize, rounded)
+    } else {
+        format!("{:.*}", decimals as usize, rounded)
+    };
+    localize_number(locale, &formatted)
+}
+
+// Número sin signo escrito por el usuario o leído de un fichero, siempre con punto decimal. Una coma
+// sería ambigua ("0,5" o "1,000"), así que se rechaza explicando el motivo en lugar de adivinar.
+// `field` es la clave del nombre del campo en el catálogo ("quantity", "price"...).
+pub fn parse_number(locale: Locale, value: &str, field: &str) -> Result<Decimal, String> {
+    let value = value.trim();
+    if value.contains(',') {
+        return Err(field_error(locale, "number.decimal_comma", value, field));
+    }
+    Decimal::from_str(value)
+        .ok()
+        .filter(|number| !number.is_sign_negative())
+        .ok_or_else(|| field_error(locale, "number.invalid", value, field))
+}
+
+// Error de un valor del campo `field`, con el nombre del campo traducido
+pub fn field_error(locale: Locale, key: &str, value: &str, field: &str) -> String {
+    t(locale, key, &[("value", &value), ("field", &t(locale, &format!("field.{}", field), &[]))])
+}
+
+// Mensaje de error de precios para el usuario, en su idioma; va detrás de frases como
+// "Error fetching BTC/USDT price: ..."
+pub fn provider_error(locale: Locale, err: &ProviderError) -> String {
+    match err {
+        ProviderError::Network(_) | ProviderError::Timeout(_) | ProviderError::Disabled
+        | ProviderError::Status(_) | ProviderError::InvalidResponse(_) => t(locale, "error.unreachable", &[]),
+        ProviderError::Api { message, .. } => t(locale, "error.rejected", &[("message", message)]),
+        ProviderError::RateLimited { retry_after: Some(wait) } =>
+            t(locale, "error.rate_limited_secs", &[("seconds", &wait.as_secs().max(1))]),
+        ProviderError::RateLimited { retry_after: None } => t(locale, "error.rate_limited", &[]),
+        ProviderError::InvalidSymbol(symbol) => t(locale, "error.unknown_symbol", &[("symbol", symbol)]),
+        ProviderError::MalformedNumber(_) => t(locale, "error.invalid_price", &[]),
+        ProviderError::Config(_) => t(locale, "error.misconfigured", &[]),
+        ProviderError::Unavailable(errors) => {
+            // Si todas las fuentes coinciden en que el símbolo no existe, eso es lo que se cuenta al usuario
+            if let Some((_, err)) = errors.first().filter(|_| errors.iter().all(|(_, e)| e.is_invalid_symbol())) {
+                provider_error(locale, err)
+            } else if let Some((_, err)) = errors.iter().find(|(_, e)| matches!(e, ProviderError::RateLimited { .. })) {
+                provider_error(locale, err)
+            } else {
+                t(locale, "error.no_source", &[])
+            }
+        }
+    }
+}
+
+const EN: &[(&str, &str)] = &[
+    ("info", "Meow! I'm {name}, version {version}. I can get the price of any pair with /price (source: {sources})."),
+    ("price", "The price of {pair} is: {price} (source: {source})"),
+    ("price.update", "Update Price"),
+    ("price.usage", "Usage: /price <symbol> [median], e.g. /price ethusdt or /price eth median"),
+    ("median.title", "Median price of {pair}: {price} ({used} of {total} sources)"),
+    ("median.outlier", "Discarded outlier {source}: {price} ({deviation}%)"),
+    ("median.unavailable", "Unavailable {source}: {error}"),
+    ("median.spread", "Spread: {spread} ({percent}%)"),
+    ("stats.title", "{pair} 24h statistics (source: {source})"),
+    ("stats.last", "Last: {value}"),
+    ("stats.open", "Open: {value}"),
+    ("stats.high", "High: {value}"),
+    ("stats.low", "Low: {value}"),
+    ("stats.change", "Change: {change} ({percent}%)"),
+    ("stats.weighted_avg", "Weighted average: {value}"),
+    ("stats.volume", "Volume: {value} {asset}"),
+    ("stats.quote_volume", "Quote volume: {value} {asset}"),
+    ("stats.usage", "Usage: /stats <symbol>, e.g. /stats btcusdt"),
+    ("chart.no_data", "There is no chart data for {pair}"),
+    ("chart.render_failed", "Could not draw the chart, please try again later."),
+    ("live.stopped", "Live updates stopped."),
+    ("live.already_ended", "These live updates have already ended."),
+    ("watchlist.load_failed", "Could not load the watchlist, please try again later."),
+    ("error.resolve", "Error resolving symbol: {error}"),
+    ("error.fetch_price", "Error fetching {pair} price: {error}"),
+    ("error.fetch_stats", "Error fetching {pair} statistics: {error}"),
+    ("error.fetch_candles", "Error fetching {pair} candles: {error}"),
+    ("error.convert", "Error converting {from} to {to}: {error}"),
+    ("error.unreachable", "the price service is not reachable right now, please try again later"),
+    ("error.rejected", "the exchange rejected the request ({message})"),
+    ("error.rate_limited_secs", "too many requests to the price service, please try again in {seconds} seconds"),
+    ("error.rate_limited", "too many requests to the price service, please try again in a minute"),
+    ("error.unknown_symbol", "unknown symbol '{symbol}', try something like btc, ethusdt or eth/btc"),
+    ("error.invalid_price", "the price service sent an invalid price, please try again later"),
+    ("error.misconfigured", "the bot is misconfigured, please contact its administrator"),
+    ("error.no_source", "no price source is available right now, please try again later"),
+    ("inline.description", "Send the current price of {pair} (source: {source})"),
+    ("help.title", "These commands are supported:"),
+    ("lang.current", "The language of this chat is {language}."),
+    ("lang.current_auto", "This chat follows the Telegram language of each user (now {language})."),
+    ("lang.set", "The language of this chat is now {language}."),
+    ("lang.auto", "This chat now follows the Telegram language of each user."),
+    ("lang.usage", "Usage: /lang [en|es|auto], e.g. /lang es"),
+    ("lang.save_failed", "Could not save the language, please try again later."),
+    ("table.symbol", "Symbol"),
+    ("table.price", "Price"),
+    ("table.change", "24h"),
+    ("table.high", "High"),
+    ("table.low", "Low"),
+    ("table.unavailable", "unavailable"),
+    ("input.invalid_price", "'{value}' is not a valid price"),
+    ("input.invalid_percent", "'{value}' is not a valid percentage"),
+    ("input.invalid_window", "'{value}' is not a valid window, use for example 15m, 1h or 1d"),
+    ("personal.portfolio", "Portfolios are personal, use {command} from a user account."),
+    ("personal.ledger", "Trade ledgers are personal, use {command} from a user account."),
+    ("personal.trade", "Trade ledgers are personal, record trades from a user account."),
+    ("chart.usage", "Usage: /chart <symbol> [interval] [range], e.g. /chart btc 1h 7d"),
+    ("chart.invalid_interval", "'{interval}' is not a valid interval. Valid intervals: {intervals}"),
+    ("chart.invalid_range", "'{range}' is not a valid range, use for example 12h, 7d or 4w"),
+    ("chart.too_few", "The range must cover at least {min} {interval} candles"),
+    ("chart.too_many", "A {range} range with {interval} candles needs {candles} candles, the maximum is {max}. Use a longer interval or a shorter range."),
+    ("chart.caption", "{pair} · {interval} candles · {range} (source: Binance)\nO {open} H {high} L {low} C {close} ({change}%)"),
+    ("alert.above", "above {price}"),
+    ("alert.below", "below {price}"),
+    ("alert.side.above", "above"),
+    ("alert.side.below", "below"),
+    ("alert.moves", "moves {percent}% within {window}"),
+    ("alert.describe", "#{id} {pair} {condition}"),
+    ("alert.usage", "Usage: /alert <symbol> <above|below> <price>, e.g. /alert btcusdt above 70000"),
+    ("alert.invalid_direction", "'{value}' is not a direction, use above or below"),
+    ("alert.price_positive", "The price must be greater than zero"),
+    ("alert.created", "Alert {alert} created (now {price})."),
+    ("alert.created_met", "Alert {alert} created. The price is already {side} it ({price}), you will be notified on the next crossing."),
+    ("alert.created_move", "Alert {alert} created."),
+    ("alert.save_failed", "Could not save the alert, please try again later."),
+    ("alert.fired", "Alert #{id}: {pair} crossed {condition} (now {price}, source: {source})"),
+    ("alert.move_fired", "Alert #{id}: {pair} moved {percent}% within the last {window} (now {price}, low {low}, high {high})"),
+    ("movealert.usage", "Usage: /movealert <symbol> <percent>% <window>, e.g. /movealert btc 5% 1h"),
+    ("movealert.percent_positive", "The percentage must be greater than zero"),
+    ("movealert.window_too_short", "The window cannot be shorter than {min}, prices are sampled every {min}"),
+    ("movealert.window_too_long", "The window cannot be longer than {max}"),
+    ("alerts.empty", "There are no alerts in this chat. Create one with /alert btcusdt above 70000"),
+    ("alerts.title", "Alerts in this chat:"),
+    ("unalert.removed", "Alert #{id} removed."),
+    ("unalert.not_found", "There is no alert #{id} in this chat."),
+    ("unalert.remove_failed", "Could not remove the alert, please try again later."),
+    ("unalert.usage", "Usage: /unalert <id>, e.g. /unalert 3"),
+    ("watch.usage", "Usage: /watch add <symbols...> or /watch remove <symbols...>, e.g. /watch add btc eth sol"),
+    ("watch.full", "{pair}: the watchlist is full ({max} symbols)"),
+    ("watch.added", "{pair}: added"),
+    ("watch.already", "{pair}: already in the watchlist"),
+    ("watch.save_failed", "{pair}: could not be saved"),
+    ("watch.not_found", "{symbol}: not in the watchlist"),
+    ("watch.removed", "{pair}: removed"),
+    ("watch.remove_failed", "{pair}: could not be removed"),
+    ("watchlist.empty", "The watchlist of this chat is empty. Add symbols with /watch add btc eth sol"),
+    ("watchlist.source", "Source: {sources}"),
+    ("watchlist.refresh", "Refresh"),
+    ("hold.usage", "Usage: /hold add <symbol> <quantity> [@ <price>] or /hold remove <symbol> [quantity], e.g. /hold add btc 0.5 @ 42000"),
+    ("hold.full", "Your portfolio is full ({max} assets), remove one before adding another."),
+    ("hold.save_failed", "Could not save the holding, please try again later."),
+    ("hold.added", "Added {quantity} {base} at {price} {quote}. You now hold {total} {base} at an average price of {average} {quote}."),
+    ("hold.not_found", "{symbol} is not in your portfolio."),
+    ("hold.reduced", "Removed {quantity} {base}. You now hold {total} {base}."),
+    ("hold.removed", "Removed {base} from your portfolio."),
+    ("hold.update_failed", "Could not update the holding, please try again later."),
+    ("portfolio.load_failed", "Could not load your portfolio, please try again later."),
+    ("portfolio.empty", "Your portfolio is empty. Add holdings with /hold add btc 0.5 @ 42000"),
+    ("portfolio.asset", "Asset"),
+    ("portfolio.quantity", "Quantity"),
+    ("portfolio.cost", "Cost"),
+    ("portfolio.value", "Value"),
+    ("portfolio.pnl", "P&L"),
+    ("portfolio.pnl_percent", "P&L %"),
+    ("portfolio.allocation", "Alloc"),
+    ("portfolio.total", "Total: {value} {quote} (cost {cost} {quote}, P&L {pnl} {quote}, {percent})"),
+    ("portfolio.partial", "Assets without a current price are left out of the totals."),
+    ("method.fifo", "FIFO"),
+    ("method.lifo", "LIFO"),
+    ("method.average", "average cost"),
+    ("trade.usage.buy", "Usage: /buy <symbol> <quantity> [@ <price>] [fee <amount>], e.g. /buy btc 0.5 @ 42000 fee 5"),
+    ("trade.usage.sell", "Usage: /sell <symbol> <quantity> [@ <price>] [fee <amount>], e.g. /sell btc 0.2 @ 50000"),
+    ("trade.usage.fee", "Usage: /fee <symbol> <quantity>, e.g. /fee btc 0.0005 for a fee paid in BTC"),
+    ("trade.load_failed", "Could not load your trades, please try again later."),
+    ("trade.save_failed", "Could not save the trade, please try again later."),
+    ("trade.oversold", "You only hold {available} {base} in your trade ledger."),
+    ("trade.invalid", "Invalid trade: {error}"),
+    ("trade.recorded.buy", "Recorded buy #{id}: {quantity} {base} at {price} {quote}{fee}."),
+    ("trade.recorded.sell", "Recorded sell #{id}: {quantity} {base} at {price} {quote}{fee}."),
+    ("trade.recorded.fee", "Recorded fee #{id}: {quantity} {base}."),
+    ("trade.fee", " (fee {fee} {quote})"),
+    ("trade.held", "You now hold {quantity} {base}."),
+    ("trade.realized", "Realized P&L ({method}): {gain} {quote}"),
+    ("gains.oversold", "trade #{id} disposes of {requested} {base} but only {available} is held at that point"),
+    ("gains.invalid", "trade #{id} has a zero or negative quantity or a negative price or fee"),
+    ("pnl.usage", "Usage: /pnl [year] or /pnl method [fifo|lifo|average], e.g. /pnl 2024"),
+    ("pnl.method", "Your cost method is {method}. Change it with /pnl method fifo, lifo or average."),
+    ("pnl.method_set", "Your cost method is now {method}."),
+    ("pnl.method_save_failed", "Could not save the cost method, please try again later."),
+    ("pnl.invalid_method", "unknown cost method '{value}', use fifo, lifo or average"),
+    ("pnl.empty", "Your trade ledger is empty. Record trades with /buy, /sell and /fee."),
+    ("pnl.inconsistent", "Your trade ledger is inconsistent: {error}"),
+    ("pnl.realized", "Realized P&L ({method}):"),
+    ("pnl.realized_year", "Realized P&L in {year} ({method}):"),
+    ("pnl.realized_line", "{pair}: {gain} {quote} ({percent}) on {quantity} {base} sold"),
+    ("pnl.no_sales", "No sales."),
+    ("pnl.total", "Total: {gain} {quote}"),
+    ("pnl.unrealized", "Unrealized P&L:"),
+    ("pnl.no_positions", "No open positions."),
+    ("pnl.unrealized_line", "{pair}: {quantity} {base} held, cost {cost}, value {value}, {gain} {quote} ({percent})"),
+    ("pnl.unrealized_unavailable", "{pair}: {quantity} {base} held, cost {cost}, price unavailable"),
+    ("export.empty", "Your trade ledger is empty, there is nothing to export."),
+    ("export.failed", "Could not export your trades, please try again later."),
+    ("import.help", "Send a CSV file with /import as the caption. Supported formats:\n• Binance trade history export (Spot > Trade History > Export)\n• The generic format of /export: date,type,base,quote,quantity,price,fee\nType is buy, sell or fee; price and fee are in the quote asset and the id column is optional.\nEvery line is checked first and nothing is imported if any line has an error."),
+    ("import.too_large", "The file is too large, the limit is {limit} KB."),
+    ("import.download_failed", "Could not download the file, please try again."),
+    ("import.format.generic", "generic"),
+    ("import.format.binance", "Binance trade history"),
+    ("import.format.binance_legacy", "Binance trade history (legacy)"),
+    ("field.quantity", "quantity"),
+    ("field.price", "price"),
+    ("field.fee", "fee"),
+    ("field.amount", "amount"),
+    ("field.executed", "executed quantity"),
+    ("import.invalid_date", "'{value}' is not a valid date"),
+    ("number.invalid", "'{value}' is not a valid {field}"),
+    ("number.decimal_comma", "'{value}' is not a valid {field}, use a dot as the decimal separator"),
+    ("import.thousands", "'{value}' is not a valid {field}, commas can only separate thousands"),
+    ("import.no_asset", "'{value}' does not include the asset of the {field}"),
+    ("import.invalid_type", "'{value}' is not buy, sell or fee"),
+    ("import.missing", "missing {column}"),
+    ("import.binance_fee", "Binance exports only contain buys and sells"),
+    ("import.zero_quantity", "the quantity must be greater than zero"),
+    ("import.header_failed", "Could not read the CSV header: {error}"),
+    ("import.unknown_format", "Unknown CSV format with columns: {columns}"),
+    ("import.line", "Line {line}: {error}"),
+    ("import.existing", "Existing trade #{id}: {error}"),
+    ("import.too_many_rows", "The file has more than {max} rows, split it into smaller files."),
+    ("import.no_trades", "The file does not contain any trades."),
+    ("import.errors.one", "Nothing was imported, found {count} error:"),
+    ("import.errors", "Nothing was imported, found {count} errors:"),
+    ("import.more_errors", "... and {count} more"),
+    ("import.check_pairs_failed", "Could not check the trading pairs of the file: {error}"),
+    ("import.unknown_pair", "unknown trading pair '{symbol}'"),
+    ("import.nothing_new", "Nothing to import, all {count} trades of the file are already in your ledger."),
+    ("import.oversold", "disposes of {requested} {base} but only {available} is held at that point"),
+    ("import.save_failed", "Could not save the imported trades, please try again later."),
+    ("import.imported.one", "Imported {count} trade ({format} format)."),
+    ("import.imported", "Imported {count} trades ({format} format)."),
+    ("import.skipped.one", "Skipped {count} trade already in your ledger."),
+    ("import.skipped", "Skipped {count} trades already in your ledger."),
+    ("import.ignored_fees.one", "Fees paid in {asset} were not recorded ({count} row)."),
+    ("import.ignored_fees", "Fees paid in {asset} were not recorded ({count} rows)."),
+    ("digest.usage", "Usage: /digest daily <hh:mm> <timezone> <symbols...> or /digest weekly <day> <hh:mm> <timezone> <symbols...>, e.g. /digest daily 08:00 Europe/Madrid btc eth"),
+    ("digest.invalid_day", "'{value}' is not a day of the week, e.g. mon or monday"),
+    ("digest.invalid_time", "'{value}' is not a valid time, use hh:mm, e.g. 08:00"),
+    ("digest.invalid_timezone", "'{value}' is not a known timezone, use a name like Europe/Madrid or UTC"),
+    ("digest.too_many_symbols", "A digest can include up to {max} symbols."),
+    ("digest.load_failed", "Could not load the digests, please try again later."),
+    ("digest.empty", "There are no digests in this chat. Schedule one with /digest daily 08:00 Europe/Madrid btc eth"),
+    ("digest.list_title", "Digests of this chat:"),
+    ("digest.next", "  next: {time}"),
+    ("digest.cancelled", "Digest #{id} cancelled."),
+    ("digest.not_found", "There is no digest #{id} in this chat."),
+    ("digest.cancel_failed", "Could not cancel the digest, please try again later."),
+    ("digest.cancel_usage", "Usage: /digest cancel <id>, e.g. /digest cancel 2"),
+    ("digest.limit", "This chat already has {max} digests, cancel one before adding another."),
+    ("digest.save_failed", "Could not save the digest, please try again later."),
+    ("digest.scheduled", "Digest scheduled: {digest}\nFirst one: {time}"),
+    ("digest.daily", "#{id} daily at {time} {timezone}: {pairs}"),
+    ("digest.weekly", "#{id} every {day} at {time} {timezone}: {pairs}"),
+    ("digest.title.daily", "Daily market digest"),
+    ("digest.title.weekly", "Weekly market digest"),
+    ("weekday.mon", "Monday"),
+    ("weekday.tue", "Tuesday"),
+    ("weekday.wed", "Wednesday"),
+    ("weekday.thu", "Thursday"),
+    ("weekday.fri", "Friday"),
+    ("weekday.sat", "Saturday"),
+    ("weekday.sun", "Sunday"),
+    ("live.usage", "Usage: /live <symbol> [minutes], e.g. /live btc 10 (up to {max} minutes)"),
+    ("live.limit", "There are already {max} live prices in this chat, stop one before starting another."),
+    ("live.last_change", "Last change: {time} UTC"),
+    ("live.status.one", "Live for {count} minute"),
+    ("live.status", "Live for {count} minutes"),
+    ("live.stop", "Stop"),
+    ("live.final_stopped", "Stopped."),
+    ("live.final_ended", "Live updates ended."),
+    ("convert.usage", "Usage: /convert <amount> <from> <to>, e.g. /convert 0.35 btc eur or /convert 500 usd eth"),
+    ("convert.rates", "Rates used:"),
+];
+
+const ES: &[(&str, &str)] = &[
+    ("info", "¡Miau! Soy {name}, en mi versión {version}. Puedo obtener el precio de cualquier par con /price (fuente: {sources})."),
+    ("price", "El precio de {pair} es: {price} (fuente: {source})"),
+    ("price.update", "Actualizar precio"),
+    ("price.usage", "Uso: /price <símbolo> [median], p. ej. /price ethusdt o /price eth median"),
+    ("median.title", "Precio mediano de {pair}: {price} ({used} de {total} fuentes)"),
+    ("median.outlier", "Descartado por desviación {source}: {price} ({deviation}%)"),
+    ("median.unavailable", "No disponible {source}: {error}"),
+    ("median.spread", "Dispersión: {spread} ({percent}%)"),
+    ("stats.title", "Estadísticas de 24 h de {pair} (fuente: {source})"),
+    ("stats.last", "Último: {value}"),
+    ("stats.open", "Apertura: {value}"),
+    ("stats.high", "Máximo: {value}"),
+    ("stats.low", "Mínimo: {value}"),
+    ("stats.change", "Variación: {change} ({percent}%)"),
+    ("stats.weighted_avg", "Media ponderada: {value}"),
+    ("stats.volume", "Volumen: {value} {asset}"),
+    ("stats.quote_volume", "Volumen en cotización: {value} {asset}"),
+    ("stats.usage", "Uso: /stats <símbolo>, p. ej. /stats btcusdt"),
+    ("chart.no_data", "No hay datos para el gráfico de {pair}"),
+    ("chart.render_failed", "No se ha podido dibujar el gráfico, inténtalo de nuevo más tarde."),
+    ("live.stopped", "Actualizaciones detenidas."),
+    ("live.already_ended", "Estas actualizaciones ya habían terminado."),
+    ("watchlist.load_failed", "No se ha podido cargar la lista de seguimiento, inténtalo de nuevo más tarde."),
+    ("error.resolve", "Error al interpretar el símbolo: {error}"),
+    ("error.fetch_price", "Error al obtener el precio de {pair}: {error}"),
+    ("error.fetch_stats", "Error al obtener las estadísticas de {pair}: {error}"),
+    ("error.fetch_candles", "Error al obtener las velas de {pair}: {error}"),
+    ("error.convert", "Error al convertir {from} a {to}: {error}"),
+    ("error.unreachable", "el servicio de precios no responde ahora mismo, inténtalo de nuevo más tarde"),
+    ("error.rejected", "el exchange ha rechazado la petición ({message})"),
+    ("error.rate_limited_secs", "demasiadas peticiones al servicio de precios, inténtalo de nuevo en {seconds} segundos"),
+    ("error.rate_limited", "demasiadas peticiones al servicio de precios, inténtalo de nuevo en un minuto"),
+    ("error.unknown_symbol", "símbolo desconocido '{symbol}', prueba con algo como btc, ethusdt o eth/btc"),
+    ("error.invalid_price", "el servicio de precios ha enviado un precio no válido, inténtalo de nuevo más tarde"),
+    ("error.misconfigured", "el bot no está bien configurado, contacta con su administrador"),
+    ("error.no_source", "ahora mismo no hay ninguna fuente de precios disponible, inténtalo de nuevo más tarde"),
+    ("inline.description", "Enviar el precio actual de {pair} (fuente: {source})"),
+    ("help.title", "Estos son los comandos disponibles:"),
+    ("command.info", "Información sobre el bot."),
+    ("command.help", "Muestra este texto."),
+    ("command.getbtcprice", "Precio de BTC en USDT."),
+    ("command.price", "Precio de un par, p. ej. /price ethusdt o /price eth. Añade 'median' para combinar todas las fuentes."),
+    ("command.live", "Publica un precio que se actualiza durante unos minutos, p. ej. /live btc 10."),
+    ("command.convert", "Convierte un importe entre activos o divisas, p. ej. /convert 0.35 btc eur."),
+    ("command.stats", "Estadísticas de 24 h de un par, p. ej. /stats btc."),
+    ("command.chart", "Gráfico de velas, p. ej. /chart btc 1h 7d."),
+    ("command.alert", "Crea una alerta de precio, p. ej. /alert btcusdt above 70000."),
+    ("command.movealert", "Alerta por un movimiento porcentual en un periodo, p. ej. /movealert btc 5% 1h."),
+    ("command.alerts", "Lista las alertas de precio de este chat."),
+    ("command.unalert", "Elimina una alerta de precio por su id, p. ej. /unalert 3."),
+    ("command.watch", "Añade o quita símbolos de la lista de seguimiento, p. ej. /watch add btc eth sol o /watch remove sol."),
+    ("command.watchlist", "Precios y variación de 24 h de la lista de seguimiento."),
+    ("command.hold", "Añade o quita posiciones de tu cartera, p. ej. /hold add btc 0.5 @ 42000 o /hold remove btc."),
+    ("command.portfolio", "Tu cartera con su valor actual y la ganancia no realizada."),
+    ("command.buy", "Registra una compra, p. ej. /buy btc 0.5 @ 42000 fee 5."),
+    ("command.sell", "Registra una venta, p. ej. /sell btc 0.2 @ 50000."),
+    ("command.fee", "Registra una comisión pagada en un activo, p. ej. /fee btc 0.0005."),
+    ("command.pnl", "Ganancias realizadas y no realizadas de tus operaciones, p. ej. /pnl 2024. Usa /pnl method lifo para cambiar el método."),
+    ("command.digest", "Programa un resumen de mercado, p. ej. /digest daily 08:00 Europe/Madrid btc eth, /digest list o /digest cancel 1."),
+    ("command.export", "Descarga tus operaciones en un fichero CSV."),
+    ("command.import", "Importa operaciones desde un CSV (historial de Binance o el formato de /export)."),
+    ("command.lang", "Elige el idioma de este chat, p. ej. /lang en, o /lang auto para usar el de cada usuario."),
+    ("lang.current", "El idioma de este chat es {language}."),
+    ("lang.current_auto", "Este chat usa el idioma de Telegram de cada usuario (ahora {language})."),
+    ("lang.set", "El idioma de este chat es ahora {language}."),
+    ("lang.auto", "Este chat usa ahora el idioma de Telegram de cada usuario."),
+    ("lang.usage", "Uso: /lang [en|es|auto], p. ej. /lang en"),
+    ("lang.save_failed", "No se ha podido guardar el idioma, inténtalo de nuevo más tarde."),
+    ("table.symbol", "Símbolo"),
+    ("table.price", "Precio"),
+    ("table.change", "24 h"),
+    ("table.high", "Máximo"),
+    ("table.low", "Mínimo"),
+    ("table.unavailable", "sin datos"),
+    ("input.invalid_price", "'{value}' no es un precio válido"),
+    ("input.invalid_percent", "'{value}' no es un porcentaje válido"),
+    ("input.invalid_window", "'{value}' no es un periodo válido, usa por ejemplo 15m, 1h o 1d"),
+    ("personal.portfolio", "Las carteras son personales, usa {command} desde una cuenta de usuario."),
+    ("personal.ledger", "Los registros de operaciones son personales, usa {command} desde una cuenta de usuario."),
+    ("personal.trade", "Los registros de operaciones son personales, registra las operaciones desde una cuenta de usuario."),
+    ("chart.usage", "Uso: /chart <símbolo> [intervalo] [rango], p. ej. /chart btc 1h 7d"),
+    ("chart.invalid_interval", "'{interval}' no es un intervalo válido. Intervalos válidos: {intervals}"),
+    ("chart.invalid_range", "'{range}' no es un rango válido, usa por ejemplo 12h, 7d o 4w"),
+    ("chart.too_few", "El rango debe abarcar al menos {min} velas de {interval}"),
+    ("chart.too_many", "Un rango de {range} con velas de {interval} necesita {candles} velas y el máximo es {max}. Usa un intervalo más largo o un rango más corto."),
+    ("chart.caption", "{pair} · velas de {interval} · {range} (fuente: Binance)\nA {open} M {high} m {low} C {close} ({change}%)"),
+    ("alert.above", "por encima de {price}"),
+    ("alert.below", "por debajo de {price}"),
+    ("alert.side.above", "por encima"),
+    ("alert.side.below", "por debajo"),
+    ("alert.moves", "se mueve un {percent}% en {window}"),
+    ("alert.describe", "#{id} {pair} {condition}"),
+    ("alert.usage", "Uso: /alert <símbolo> <above|below> <precio>, p. ej. /alert btcusdt above 70000"),
+    ("alert.invalid_direction", "'{value}' no es un sentido, usa above o below"),
+    ("alert.price_positive", "El precio debe ser mayor que cero"),
+    ("alert.created", "Alerta {alert} creada (ahora {price})."),
+    ("alert.created_met", "Alerta {alert} creada. El precio ya está {side} ({price}), se avisará en el próximo cruce."),
+    ("alert.created_move", "Alerta {alert} creada."),
+    ("alert.save_failed", "No se ha podido guardar la alerta, inténtalo de nuevo más tarde."),
+    ("alert.fired", "Alerta #{id}: {pair} ha cruzado {condition} (ahora {price}, fuente: {source})"),
+    ("alert.move_fired", "Alerta #{id}: {pair} se ha movido un {percent}% en {window} (ahora {price}, mínimo {low}, máximo {high})"),
+    ("movealert.usage", "Uso: /movealert <símbolo> <porcentaje>% <periodo>, p. ej. /movealert btc 5% 1h"),
+    ("movealert.percent_positive", "El porcentaje debe ser mayor que cero"),
+    ("movealert.window_too_short", "El periodo no puede ser menor de {min}, los precios se muestrean cada {min}"),
+    ("movealert.window_too_long", "El periodo no puede ser mayor de {max}"),
+    ("alerts.empty", "No hay alertas en este chat. Crea una con /alert btcusdt above 70000"),
+    ("alerts.title", "Alertas de este chat:"),
+    ("unalert.removed", "Alerta #{id} eliminada."),
+    ("unalert.not_found", "No hay ninguna alerta #{id} en este chat."),
+    ("unalert.remove_failed", "No se ha podido eliminar la alerta, inténtalo de nuevo más tarde."),
+    ("unalert.usage", "Uso: /unalert <id>, p. ej. /unalert 3"),
+    ("watch.usage", "Uso: /watch add <símbolos...> o /watch remove <símbolos...>, p. ej. /watch add btc eth sol"),
+    ("watch.full", "{pair}: la lista de seguimiento está llena ({max} símbolos)"),
+    ("watch.added", "{pair}: añadido"),
+    ("watch.already", "{pair}: ya está en la lista de seguimiento"),
+    ("watch.save_failed", "{pair}: no se ha podido guardar"),
+    ("watch.not_found", "{symbol}: no está en la lista de seguimiento"),
+    ("watch.removed", "{pair}: quitado"),
+    ("watch.remove_failed", "{pair}: no se ha podido quitar"),
+    ("watchlist.empty", "La lista de seguimiento de este chat está vacía. Añade símbolos con /watch add btc eth sol"),
+    ("watchlist.source", "Fuente: {sources}"),
+    ("watchlist.refresh", "Actualizar"),
+    ("hold.usage", "Uso: /hold add <símbolo> <cantidad> [@ <precio>] o /hold remove <símbolo> [cantidad], p. ej. /hold add btc 0.5 @ 42000"),
+    ("hold.full", "Tu cartera está llena ({max} activos), quita uno antes de añadir otro."),
+    ("hold.save_failed", "No se ha podido guardar la posición, inténtalo de nuevo más tarde."),
+    ("hold.added", "Añadido {quantity} {base} a {price} {quote}. Ahora tienes {total} {base} a un precio medio de {average} {quote}."),
+    ("hold.not_found", "{symbol} no está en tu cartera."),
+    ("hold.reduced", "Quitado {quantity} {base}. Ahora tienes {total} {base}."),
+    ("hold.removed", "{base} quitado de tu cartera."),
+    ("hold.update_failed", "No se ha podido actualizar la posición, inténtalo de nuevo más tarde."),
+    ("portfolio.load_failed", "No se ha podido cargar tu cartera, inténtalo de nuevo más tarde."),
+    ("portfolio.empty", "Tu cartera está vacía. Añade posiciones con /hold add btc 0.5 @ 42000"),
+    ("portfolio.asset", "Activo"),
+    ("portfolio.quantity", "Cantidad"),
+    ("portfolio.cost", "Coste"),
+    ("portfolio.value", "Valor"),
+    ("portfolio.pnl", "G/P"),
+    ("portfolio.pnl_percent", "G/P %"),
+    ("portfolio.allocation", "Peso"),
+    ("portfolio.total", "Total: {value} {quote} (coste {cost} {quote}, G/P {pnl} {quote}, {percent})"),
+    ("portfolio.partial", "Los activos sin precio actual no cuentan en los totales."),
+    ("method.fifo", "FIFO"),
+    ("method.lifo", "LIFO"),
+    ("method.average", "coste medio"),
+    ("trade.usage.buy", "Uso: /buy <símbolo> <cantidad> [@ <precio>] [fee <importe>], p. ej. /buy btc 0.5 @ 42000 fee 5"),
+    ("trade.usage.sell", "Uso: /sell <símbolo> <cantidad> [@ <precio>] [fee <importe>], p. ej. /sell btc 0.2 @ 50000"),
+    ("trade.usage.fee", "Uso: /fee <símbolo> <cantidad>, p. ej. /fee btc 0.0005 para una comisión pagada en BTC"),
+    ("trade.load_failed", "No se han podido cargar tus operaciones, inténtalo de nuevo más tarde."),
+    ("trade.save_failed", "No se ha podido guardar la operación, inténtalo de nuevo más tarde."),
+    ("trade.oversold", "Solo tienes {available} {base} en tu registro de operaciones."),
+    ("trade.invalid", "Operación no válida: {error}"),
+    ("trade.recorded.buy", "Compra #{id} registrada: {quantity} {base} a {price} {quote}{fee}."),
+    ("trade.recorded.sell", "Venta #{id} registrada: {quantity} {base} a {price} {quote}{fee}."),
+    ("trade.recorded.fee", "Comisión #{id} registrada: {quantity} {base}."),
+    ("trade.fee", " (comisión {fee} {quote})"),
+    ("trade.held", "Ahora tienes {quantity} {base}."),
+    ("trade.realized", "Ganancia realizada ({method}): {gain} {quote}"),
+    ("gains.oversold", "la operación #{id} saca {requested} {base} pero en ese momento solo hay {available}"),
+    ("gains.invalid", "la operación #{id} tiene una cantidad nula o negativa o un precio o comisión negativos"),
+    ("pnl.usage", "Uso: /pnl [año] o /pnl method [fifo|lifo|average], p. ej. /pnl 2024"),
+    ("pnl.method", "Tu método de cálculo es {method}. Cámbialo con /pnl method fifo, lifo o average."),
+    ("pnl.method_set", "Tu método de cálculo es ahora {method}."),
+    ("pnl.method_save_failed", "No se ha podido guardar el método de cálculo, inténtalo de nuevo más tarde."),
+    ("pnl.invalid_method", "método de cálculo desconocido '{value}', usa fifo, lifo o average"),
+    ("pnl.empty", "Tu registro de operaciones está vacío. Registra operaciones con /buy, /sell y /fee."),
+    ("pnl.inconsistent", "Tu registro de operaciones no cuadra: {error}"),
+    ("pnl.realized", "Ganancias realizadas ({method}):"),
+    ("pnl.realized_year", "Ganancias realizadas en {year} ({method}):"),
+    ("pnl.realized_line", "{pair}: {gain} {quote} ({percent}) por {quantity} {base} vendidos"),
+    ("pnl.no_sales", "Sin ventas."),
+    ("pnl.total", "Total: {gain} {quote}"),
+    ("pnl.unrealized", "Ganancias no realizadas:"),
+    ("pnl.no_positions", "Sin posiciones abiertas."),
+    ("pnl.unrealized_line", "{pair}: {quantity} {base}, coste {cost}, valor {value}, {gain} {quote} ({percent})"),
+    ("pnl.unrealized_unavailable", "{pair}: {quantity} {base}, coste {cost}, precio no disponible"),
+    ("export.empty", "Tu registro de operaciones está vacío, no hay nada que exportar."),
+    ("export.failed", "No se han podido exportar tus operaciones, inténtalo de nuevo más tarde."),
+    ("import.help", "Envía un fichero CSV con /import como pie. Formatos admitidos:\n• Exportación del historial de operaciones de Binance (Spot > Historial de operaciones > Exportar)\n• El formato genérico de /export: date,type,base,quote,quantity,price,fee\nEl tipo es buy, sell o fee; el precio y la comisión van en el activo de cotización y la columna id es opcional.\nSe comprueban todas las líneas antes y no se importa nada si alguna tiene un error."),
+    ("import.too_large", "El fichero es demasiado grande, el límite es de {limit} KB."),
+    ("import.download_failed", "No se ha podido descargar el fichero, inténtalo de nuevo."),
+    ("import.format.generic", "genérico"),
+    ("import.format.binance", "historial de operaciones de Binance"),
+    ("import.format.binance_legacy", "historial de operaciones de Binance (antiguo)"),
+    ("field.quantity", "cantidad"),
+    ("field.price", "precio"),
+    ("field.fee", "comisión"),
+    ("field.amount", "cantidad"),
+    ("field.executed", "cantidad ejecutada"),
+    ("import.invalid_date", "'{value}' no es una fecha válida"),
+    ("number.invalid", "'{value}' no es un valor válido de {field}"),
+    ("number.decimal_comma", "'{value}' no es un valor válido de {field}, usa el punto como separador decimal"),
+    ("import.thousands", "'{value}' no es un valor válido de {field}, la coma solo puede separar los miles"),
+    ("import.no_asset", "'{value}' no indica el activo de la {field}"),
+    ("import.invalid_type", "'{value}' no es buy, sell ni fee"),
+    ("import.missing", "falta {column}"),
+    ("import.binance_fee", "las exportaciones de Binance solo tienen compras y ventas"),
+    ("import.zero_quantity", "la cantidad debe ser mayor que cero"),
+    ("import.header_failed", "No se ha podido leer la cabecera del CSV: {error}"),
+    ("import.unknown_format", "Formato de CSV desconocido con las columnas: {columns}"),
+    ("import.line", "Línea {line}: {error}"),
+    ("import.existing", "Operación existente #{id}: {error}"),
+    ("import.too_many_rows", "El fichero tiene más de {max} filas, divídelo en ficheros más pequeños."),
+    ("import.no_trades", "El fichero no contiene ninguna operación."),
+    ("import.errors.one", "No se ha importado nada, hay {count} error:"),
+    ("import.errors", "No se ha importado nada, hay {count} errores:"),
+    ("import.more_errors", "... y {count} más"),
+    ("import.check_pairs_failed", "No se han podido comprobar los pares del fichero: {error}"),
+    ("import.unknown_pair", "par desconocido '{symbol}'"),
+    ("import.nothing_new", "No hay nada que importar, las {count} operaciones del fichero ya están en tu registro."),
+    ("import.oversold", "saca {requested} {base} pero en ese momento solo hay {available}"),
+    ("import.save_failed", "No se han podido guardar las operaciones importadas, inténtalo de nuevo más tarde."),
+    ("import.imported.one", "Importada {count} operación (formato {format})."),
+    ("import.imported", "Importadas {count} operaciones (formato {format})."),
+    ("import.skipped.one", "Se ha saltado {count} operación que ya estaba en tu registro."),
+    ("import.skipped", "Se han saltado {count} operaciones que ya estaban en tu registro."),
+    ("import.ignored_fees.one", "No se han registrado las comisiones pagadas en {asset} ({count} fila)."),
+    ("import.ignored_fees", "No se han registrado las comisiones pagadas en {asset} ({count} filas)."),
+    ("digest.usage", "Uso: /digest daily <hh:mm> <zona horaria> <símbolos...> o /digest weekly <día> <hh:mm> <zona horaria> <símbolos...>, p. ej. /digest daily 08:00 Europe/Madrid btc eth"),
+    ("digest.invalid_day", "'{value}' no es un día de la semana, p. ej. mon o monday"),
+    ("digest.invalid_time", "'{value}' no es una hora válida, usa hh:mm, p. ej. 08:00"),
+    ("digest.invalid_timezone", "'{value}' no es una zona horaria conocida, usa un nombre como Europe/Madrid o UTC"),
+    ("digest.too_many_symbols", "Un resumen puede incluir hasta {max} símbolos."),
+    ("digest.load_failed", "No se han podido cargar los resúmenes, inténtalo de nuevo más tarde."),
+    ("digest.empty", "No hay resúmenes en este chat. Programa uno con /digest daily 08:00 Europe/Madrid btc eth"),
+    ("digest.list_title", "Resúmenes de este chat:"),
+    ("digest.next", "  próximo: {time}"),
+    ("digest.cancelled", "Resumen #{id} cancelado."),
+    ("digest.not_found", "No hay ningún resumen #{id} en este chat."),
+    ("digest.cancel_failed", "No se ha podido cancelar el resumen, inténtalo de nuevo más tarde."),
+    ("digest.cancel_usage", "Uso: /digest cancel <id>, p. ej. /digest cancel 2"),
+    ("digest.limit", "Este chat ya tiene {max} resúmenes, cancela uno antes de añadir otro."),
+    ("digest.save_failed", "No se ha podido guardar el resumen, inténtalo de nuevo más tarde."),
+    ("digest.scheduled", "Resumen programado: {digest}\nPrimer envío: {time}"),
+    ("digest.daily", "#{id} cada día a las {time} {timezone}: {pairs}"),
+    ("digest.weekly", "#{id} cada {day} a las {time} {timezone}: {pairs}"),
+    ("digest.title.daily", "Resumen diario del mercado"),
+    ("digest.title.weekly", "Resumen semanal del mercado"),
+    ("weekday.mon", "lunes"),
+    ("weekday.tue", "martes"),
+    ("weekday.wed", "miércoles"),
+    ("weekday.thu", "jueves"),
+    ("weekday.fri", "viernes"),
+    ("weekday.sat", "sábado"),
+    ("weekday.sun", "domingo"),
+    ("live.usage", "Uso: /live <símbolo> [minutos], p. ej. /live btc 10 (hasta {max} minutos)"),
+    ("live.limit", "Ya hay {max} precios en vivo en este chat, detén uno antes de empezar otro."),
+    ("live.last_change", "Último cambio: {time} UTC"),
+    ("live.status.one", "En vivo durante {count} minuto"),
+    ("live.status", "En vivo durante {count} minutos"),
+    ("live.stop", "Detener"),
+    ("live.final_stopped", "Detenido."),
+    ("live.final_ended", "Actualizaciones terminadas."),
+    ("convert.usage", "Uso: /convert <importe> <desde> <hacia>, p. ej. /convert 0.35 btc eur o /convert 500 usd eth"),
+    ("convert.rates", "Precios usados:"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn numbers_use_the_separators_of_each_language() {
+        assert_eq!(localize_number(Locale::En, "1234.56"), "1,234.56");
+        assert_eq!(localize_number(Locale::Es, "1234.56"), "1.234,56");
+        assert_eq!(localize_number(Locale::Es, "1234567"), "1.234.567");
+        assert_eq!(localize_number(Locale::Es, "999"), "999");
+        assert_eq!(localize_number(Locale::Es, "0.00012345"), "0,00012345");
+    }
+
+    #[test]
+    fn the_sign_stays_in_front_of_the_grouped_digits() {
+        assert_eq!(localize_number(Locale::Es, "-1234.5"), "-1.234,5");
+        assert_eq!(localize_number(Locale::En, "+123456.78"), "+123,456.78");
+        assert_eq!(localize_number(Locale::En, "-123"), "-123");
+    }
+
+    #[test]
+    fn prices_keep_two_decimals_above_one_and_eight_below() {
+        assert_eq!(price(Locale::Es, d("67187.3349")), "67.187,33");
+        assert_eq!(price(Locale::En, d("1234")), "1,234.00");
+        assert_eq!(price(Locale::Es, d("0.123456789")), "0,12345679");
+        assert_eq!(price(Locale::En, d("-1234.5")), "-1,234.50");
+        assert_eq!(change(Locale::Es, d("1234.5")), "+1.234,50");
+        assert_eq!(amount(Locale::Es, d("1200.500")), "1.200,5");
+    }
+
+    #[test]
+    fn number_rounds_and_signs_as_asked() {
+        assert_eq!(number(Locale::Es, d("1234.567"), 2, false), "1.234,57");
+        assert_eq!(number(Locale::En, d("2.345"), 2, false), "2.35");
+        assert_eq!(number(Locale::En, d("-2.345"), 2, false), "-2.35");
+        assert_eq!(number(Locale::En, d("1234.5"), 0, false), "1,235");
+        assert_eq!(number(Locale::Es, d("5.5"), 2, true), "+5,50");
+        assert_eq!(number(Locale::Es, d("-5.5"), 2, true), "-5,50");
+        // Lo que se redondea a cero no lleva signo
+        assert_eq!(number(Locale::En, d("0.001"), 2, true), "0.00");
+        assert_eq!(number(Locale::En, d("-0.001"), 2, true), "0.00");
+    }
+}
@@ -0,0 +1,56 @@
+// Modo inline (@bot btc en cualquier chat): busca los pares que se parecen a lo escrito y ofrece
+// un artículo por par con su precio actual, con el mismo texto que la respuesta de /price.
+use teloxide::types::{InlineQueryResult, InlineQueryResultArticle, InputMessageContent, InputMessageContentText};
+
+use crate::config::{env_list, env_or};
+use crate::i18n::{self, Locale};
+use crate::prices::{PriceService, Quote};
+use crate::providers::Pair;
+
+pub struct InlineSettings {
+    // Resultados como mucho por búsqueda; cada uno supone consultar un precio
+    pub max_results: usize,
+    // Segundos que Telegram puede reutilizar la respuesta a la misma búsqueda
+    pub cache_time: u32,
+    // Símbolos que se muestran cuando aún no se ha escrito nada
+    default_symbols: Vec<String>,
+}
+
+impl InlineSettings {
+    // INLINE_MAX_RESULTS (por defecto 8, como mucho 50, el límite de Telegram), INLINE_CACHE_SECS
+    // (por defecto 10, los precios caducan enseguida) e INLINE_DEFAULT_SYMBOLS (por defecto btc,eth,sol,bnb,xrp)
+    pub fn from_env() -> Self {
+        InlineSettings {
+            max_results: env_or("INLINE_MAX_RESULTS", 8).clamp(1, 50),
+            cache_time: env_or("INLINE_CACHE_SECS", 10),
+            default_symbols: env_list("INLINE_DEFAULT_SYMBOLS")
+                .unwrap_or_else(|| ["btc", "eth", "sol", "bnb", "xrp"].map(String::from).to_vec()),
+        }
+    }
+}
+
+// Pares que se ofrecen para lo escrito; sin texto se ofrecen los símbolos por defecto
+pub async fn matching_pairs(prices: &PriceService, settings: &InlineSettings, query: &str) -> Vec<Pair> {
+    if query.trim().is_empty() {
+        let resolved = futures_util::future::join_all(settings.default_symbols.iter().map(|s| prices.resolve(s))).await;
+        return resolved.into_iter().filter_map(Result::ok).take(settings.max_results).collect();
+    }
+    match prices.search(query, settings.max_results).await {
+        Ok(pairs) => pairs,
+        Err(err) => {
+            log::warn!("Inline query '{}' could not search symbols: {}", query, err);
+            Vec::new()
+        }
+    }
+}
+
+// Artículo de un par: el título muestra el precio y al elegirlo se envía `text`
+pub fn article(locale: Locale, quote: &Quote, text: String) -> InlineQueryResult {
+    let title = format!("{}: {}", quote.pair, i18n::price(locale, quote.price));
+    let description = i18n::t(locale, "inline.description", &[("pair", &quote.pair), ("source", &quote.source)]);
+    InlineQueryResult::Article(InlineQueryResultArticle::new(
+        quote.pair.to_string(),
+        title,
+        InputMessageContent::Text(InputMessageContentText::new(text)),
+    ).description(description))
+}
@@ -0,0 +1,341 @@
+// Registro de operaciones de cada usuario: "/buy btc 0.5 @ 42000 fee 5", "/sell btc 0.2 @ 50000",
+// "/fee btc 0.0005" y "/pnl [año]" con las ganancias realizadas y no realizadas. Los cálculos los
+// hace el módulo gains; aquí solo se interpretan los comandos y se guarda y muestra el resultado.
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, SubsecRound, Utc};
+use rust_decimal::Decimal;
+use teloxide::types::UserId;
+
+use crate::gains::{self, CostMethod, Gains, GainsError, Trade, TradeKind};
+use crate::i18n::{self, Locale};
+use crate::portfolio::parse_positive;
+use crate::prices::PriceService;
+use crate::storage::{NewTrade, Storage};
+
+// Clave del ajuste de usuario con el método de cálculo
+const COST_METHOD_SETTING: &str = "cost_method";
+
+// Método de cálculo elegido por el usuario, FIFO si no ha elegido ninguno
+pub fn cost_method(storage: &dyn Storage, user_id: UserId) -> CostMethod {
+    match storage.user_setting(user_id, COST_METHOD_SETTING) {
+        Ok(Some(value)) => value.parse().unwrap_or_default(),
+        Ok(None) => CostMethod::default(),
+        Err(err) => {
+            log::error!("Could not load cost method of user {}: {}", user_id, err);
+            CostMethod::default()
+        }
+    }
+}
+
+// Nombre del método de cálculo en el idioma del usuario
+pub fn method_name(locale: Locale, method: CostMethod) -> String {
+    i18n::t(locale, &format!("method.{}", method), &[])
+}
+
+// Error del cálculo de ganancias para el usuario
+pub fn gains_error(locale: Locale, err: &GainsError) -> String {
+    match err {
+        GainsError::Oversold { trade_id, pair, available, requested } => i18n::t(locale, "gains.oversold", &[
+            ("id", trade_id),
+            ("requested", &i18n::amount(locale, *requested)),
+            ("base", &pair.base),
+            ("available", &i18n::amount(locale, *available)),
+        ]),
+        GainsError::InvalidTrade { trade_id } => i18n::t(locale, "gains.invalid", &[("id", trade_id)]),
+    }
+}
+
+// Interpreta "<símbolo> <cantidad> [@ <precio>] [fee <importe>]"; /fee solo lleva símbolo y cantidad
+fn parse_trade_args(locale: Locale, kind: TradeKind, input: &str) -> Result<(String, Decimal, Option<Decimal>, Decimal), String> {
+    let usage = || i18n::t(locale, &format!("trade.usage.{}", kind), &[]);
+    let input = input.replace('@', " @ ");
+    let args: Vec<&str> = input.split_whitespace().collect();
+    let [symbol, quantity, rest @ ..] = args.as_slice() else {
+        return Err(usage());
+    };
+    let quantity = parse_positive(locale, quantity, "quantity")?;
+
+    let mut rest = rest;
+    let mut price = None;
+    let mut fee = Decimal::ZERO;
+    while !rest.is_empty() {
+        match rest {
+            ["@", value, tail @ ..] if kind != TradeKind::Fee && price.is_none() => {
+                price = Some(parse_positive(locale, value, "price")?);
+                rest = tail;
+            }
+            [word, value, tail @ ..] if kind != TradeKind::Fee && word.eq_ignore_ascii_case("fee") => {
+                fee = parse_positive(locale, value, "fee")?;
+                rest = tail;
+            }
+            _ => return Err(usage()),
+        }
+    }
+    Ok((symbol.to_string(), quantity, price, fee))
+}
+
+// Procesa /buy, /sell y /fee. La operación solo se guarda si el registro sigue cuadrando con ella
+// (no se puede vender más de lo que se tiene).
+pub async fn trade_command(prices: &PriceService, storage: &dyn Storage, user_id: UserId, locale: Locale, kind: TradeKind, input: &str) -> String {
+    let (symbol, quantity, price, fee) = match parse_trade_args(locale, kind, input) {
+        Ok(args) => args,
+        Err(usage) => return usage,
+    };
+    let pair = match prices.resolve(&symbol).await {
+        Ok(pair) => pair,
+        Err(err) => return i18n::t(locale, "error.resolve", &[("error", &err.user_message(locale))]),
+    };
+    // Sin precio se toma el actual; las comisiones en el activo no tienen precio
+    let price = match (kind, price) {
+        (TradeKind::Fee, _) => Decimal::ZERO,
+        (_, Some(price)) => price,
+        (_, None) => match prices.price(&pair).await {
+            Ok(quote) => quote.price,
+            Err(err) => return i18n::t(locale, "error.fetch_price", &[("pair", &pair), ("error", &err.user_message(locale))]),
+        },
+    };
+
+    let mut trades = match storage.trades(user_id) {
+        Ok(trades) => trades,
+        Err(err) => {
+            log::error!("Could not load trades of user {}: {}", user_id, err);
+            return i18n::t(locale, "trade.load_failed", &[]);
+        }
+    };
+    let new_trade = NewTrade { time: Utc::now().trunc_subsecs(0), kind, pair: pair.clone(), quantity, price, fee };
+    let method = cost_method(storage, user_id);
+    trades.push(Trade {
+        id: u64::MAX,
+        time: new_trade.time,
+        kind,
+        pair: pair.clone(),
+        quantity,
+        price,
+        fee,
+    });
+    let gains = match gains::compute(&trades, method) {
+        Ok(gains) => gains,
+        Err(GainsError::Oversold { available, .. }) =>
+            return i18n::t(locale, "trade.oversold", &[("available", &i18n::amount(locale, available)), ("base", &pair.base)]),
+        Err(err) => return i18n::t(locale, "trade.invalid", &[("error", &gains_error(locale, &err))]),
+    };
+
+    let trade = match storage.insert_trade(user_id, new_trade) {
+        Ok(trade) => trade,
+        Err(err) => {
+            log::error!("Could not save trade of user {}: {}", user_id, err);
+            return i18n::t(locale, "trade.save_failed", &[]);
+        }
+    };
+    let held = gains.positions.get(&pair).map(|p| p.quantity()).unwrap_or_default();
+    let fee = if fee.is_zero() {
+        String::new()
+    } else {
+        i18n::t(locale, "trade.fee", &[("fee", &i18n::price(locale, fee)), ("quote", &pair.quote)])
+    };
+    let recorded = i18n::t(locale, &format!("trade.recorded.{}", kind), &[
+        ("id", &trade.id),
+        ("quantity", &i18n::amount(locale, quantity)),
+        ("base", &pair.base),
+        ("price", &i18n::price(locale, price)),
+        ("quote", &pair.quote),
+        ("fee", &fee),
+    ]);
+    let mut lines = vec![recorded, i18n::t(locale, "trade.held", &[("quantity", &i18n::amount(locale, held)), ("base", &pair.base)])];
+    if let Some(disposal) = gains.disposals.iter().find(|d| d.trade_id == u64::MAX) {
+        lines.push(i18n::t(locale, "trade.realized", &[
+            ("method", &method_name(locale, method)),
+            ("gain", &i18n::change(locale, disposal.gain())),
+            ("quote", &pair.quote),
+        ]));
+    }
+    lines.join("\n")
+}
+
+// Ganancia en porcentaje sobre el coste
+fn percent(locale: Locale, gain: Decimal, cost: Decimal) -> String {
+    if cost.is_zero() {
+        "-".to_string()
+    } else {
+        format!("{}%", i18n::number(locale, gain / cost * Decimal::ONE_HUNDRED, 2, true))
+    }
+}
+
+// Procesa "/pnl [año]" y "/pnl method [fifo|lifo|average]"
+pub async fn pnl_command(prices: &PriceService, storage: &dyn Storage, user_id: UserId, locale: Locale, input: &str) -> String {
+    let args: Vec<&str> = input.split_whitespace().collect();
+    let year = match args.as_slice() {
+        [] => None,
+        [word] if word.eq_ignore_ascii_case("method") =>
+            return i18n::t(locale, "pnl.method", &[("method", &method_name(locale, cost_method(storage, user_id)))]),
+        [word, method] if word.eq_ignore_ascii_case("method") => {
+            let method = match method.parse::<CostMethod>() {
+                Ok(method) => method,
+                Err(_) => return i18n::t(locale, "pnl.invalid_method", &[("value", method)]),
+            };
+            return match storage.set_user_setting(user_id, COST_METHOD_SETTING, &method.to_string()) {
+                Ok(()) => i18n::t(locale, "pnl.method_set", &[("method", &method_name(locale, method))]),
+                Err(err) => {
+                    log::error!("Could not save cost method of user {}: {}", user_id, err);
+                    i18n::t(locale, "pnl.method_save_failed", &[])
+                }
+            };
+        }
+        [year] => match year.parse::<i32>() {
+            Ok(year) if (1970..=9999).contains(&year) => Some(year),
+            _ => return i18n::t(locale, "pnl.usage", &[]),
+        },
+        _ => return i18n::t(locale, "pnl.usage", &[]),
+    };
+
+    let trades = match storage.trades(user_id) {
+        Ok(trades) => trades,
+        Err(err) => {
+            log::error!("Could not load trades of user {}: {}", user_id, err);
+            return i18n::t(locale, "trade.load_failed", &[]);
+        }
+    };
+    if trades.is_empty() {
+        return i18n::t(locale, "pnl.empty", &[]);
+    }
+    let method = cost_method(storage, user_id);
+    match gains::compute(&trades, method) {
+        Ok(gains) => pnl_text(prices, &gains, locale, method, year).await,
+        Err(err) => {
+            log::warn!("Trade ledger of user {} is inconsistent: {}", user_id, err);
+            i18n::t(locale, "pnl.inconsistent", &[("error", &gains_error(locale, &err))])
+        }
+    }
+}
+
+async fn pnl_text(prices: &PriceService, gains: &Gains, locale: Locale, method: CostMethod, year: Option<i32>) -> String {
+    let method = method_name(locale, method);
+    let mut lines = vec![match year {
+        Some(year) => i18n::t(locale, "pnl.realized_year", &[("year", &year), ("method", &method)]),
+        None => i18n::t(locale, "pnl.realized", &[("method", &method)]),
+    }];
+
+    // Ganancias realizadas por par y totales por moneda de cotización
+    let mut realized: BTreeMap<_, (Decimal, Decimal, Decimal)> = BTreeMap::new();
+    for disposal in gains.disposals.iter().filter(|d| year.is_none_or(|year| d.time.year() == year)) {
+        let entry = realized.entry(&disposal.pair).or_default();
+        entry.0 += disposal.quantity;
+        entry.1 += disposal.gain();
+        entry.2 += disposal.cost;
+    }
+    let mut realized_totals: BTreeMap<&str, Decimal> = BTreeMap::new();
+    for (pair, (quantity, gain, cost)) in &realized {
+        lines.push(i18n::t(locale, "pnl.realized_line", &[
+            ("pair", pair),
+            ("gain", &i18n::change(locale, *gain)),
+            ("quote", &pair.quote),
+            ("percent", &percent(locale, *gain, *cost)),
+            ("quantity", &i18n::amount(locale, *quantity)),
+            ("base", &pair.base),
+        ]));
+        *realized_totals.entry(pair.quote.as_str()).or_default() += *gain;
+    }
+    if realized.is_empty() {
+        lines.push(i18n::t(locale, "pnl.no_sales", &[]));
+    }
+    for (quote, gain) in &realized_totals {
+        lines.push(i18n::t(locale, "pnl.total", &[("gain", &i18n::change(locale, *gain)), ("quote", quote)]));
+    }
+
+    // Ganancias no realizadas de las posiciones abiertas a precio actual
+    lines.push(String::new());
+    lines.push(i18n::t(locale, "pnl.unrealized", &[]));
+    if gains.positions.is_empty() {
+        lines.push(i18n::t(locale, "pnl.no_positions", &[]));
+    }
+    let quotes = futures_util::future::join_all(gains.positions.keys().map(|pair| prices.price(pair))).await;
+    let mut unrealized_totals: BTreeMap<&str, Decimal> = BTreeMap::new();
+    for ((pair, position), quote) in gains.positions.iter().zip(quotes) {
+        let (quantity, cost) = (position.quantity(), position.cost());
+        match quote {
+            Ok(quote) => {
+                let gain = quote.price * quantity - cost;
+                lines.push(i18n::t(locale, "pnl.unrealized_line", &[
+                    ("pair", pair),
+                    ("quantity", &i18n::amount(locale, quantity)),
+                    ("base", &pair.base),
+                    ("cost", &i18n::price(locale, cost)),
+                    ("value", &i18n::price(locale, quote.price * quantity)),
+                    ("gain", &i18n::change(locale, gain)),
+                    ("quote", &pair.quote),
+                    ("percent", &percent(locale, gain, cost)),
+                ]));
+                *unrealized_totals.entry(pair.quote.as_str()).or_default() += gain;
+            }
+            Err(err) => {
+                log::warn!("P&L could not fetch {}: {}", pair, err);
+                lines.push(i18n::t(locale, "pnl.unrealized_unavailable", &[
+                    ("pair", pair),
+                    ("quantity", &i18n::amount(locale, quantity)),
+                    ("base", &pair.base),
+                    ("cost", &i18n::price(locale, cost)),
+                ]));
+            }
+        }
+    }
+    for (quote, gain) in &unrealized_totals {
+        lines.push(i18n::t(locale, "pnl.total", &[("gain", &i18n::change(locale, *gain)), ("quote", quote)]));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    fn usage(kind: TradeKind) -> String {
+        i18n::t(Locale::En, &format!("trade.usage.{}", kind), &[])
+    }
+
+    #[test]
+    fn price_can_be_written_with_or_without_spaces() {
+        let expected = Ok(("btc".to_string(), d("0.5"), Some(d("42000")), Decimal::ZERO));
+        assert_eq!(parse_trade_args(Locale::En, TradeKind::Buy, "btc 0.5@42000"), expected);
+        assert_eq!(parse_trade_args(Locale::En, TradeKind::Buy, "btc 0.5 @42000"), expected);
+        assert_eq!(parse_trade_args(Locale::En, TradeKind::Buy, "btc 0.5 @ 42000"), expected);
+        assert_eq!(parse_trade_args(Locale::En, TradeKind::Sell, "eth 2"), Ok(("eth".to_string(), d("2"), None, Decimal::ZERO)));
+    }
+
+    #[test]
+    fn fee_goes_before_or_after_the_price() {
+        let expected = Ok(("btc".to_string(), d("0.5"), Some(d("42000")), d("5")));
+        assert_eq!(parse_trade_args(Locale::En, TradeKind::Buy, "btc 0.5 @ 42000 fee 5"), expected);
+        assert_eq!(parse_trade_args(Locale::En, TradeKind::Buy, "btc 0.5 FEE 5 @42000"), expected);
+        assert_eq!(parse_trade_args(Locale::En, TradeKind::Sell, "btc 0.5 fee 5"), Ok(("btc".to_string(), d("0.5"), None, d("5"))));
+    }
+
+    #[test]
+    fn repeated_or_incomplete_arguments_show_usage() {
+        for input in ["btc 0.5 @ 42000 @ 43000", "btc 0.5@1@2", "btc 0.5 @", "btc 0.5 fee", "btc 0.5 42000", "btc"] {
+            assert_eq!(parse_trade_args(Locale::En, TradeKind::Buy, input), Err(usage(TradeKind::Buy)), "{}", input);
+        }
+    }
+
+    #[test]
+    fn invalid_numbers_name_the_field() {
+        assert_eq!(parse_trade_args(Locale::En, TradeKind::Buy, "btc 1,5 @ 42000"),
+            Err(i18n::field_error(Locale::En, "number.decimal_comma", "1,5", "quantity")));
+        assert_eq!(parse_trade_args(Locale::En, TradeKind::Buy, "btc 1 @ 0"),
+            Err(i18n::field_error(Locale::En, "number.invalid", "0", "price")));
+        assert_eq!(parse_trade_args(Locale::En, TradeKind::Sell, "btc 1 fee -2"),
+            Err(i18n::field_error(Locale::En, "number.invalid", "-2", "fee")));
+    }
+
+    #[test]
+    fn fee_command_takes_only_symbol_and_quantity() {
+        assert_eq!(parse_trade_args(Locale::En, TradeKind::Fee, "btc 0.0005"), Ok(("btc".to_string(), d("0.0005"), None, Decimal::ZERO)));
+        for input in ["btc 0.0005 @ 42000", "btc 0.0005@42000", "btc 0.0005 fee 1"] {
+            assert_eq!(parse_trade_args(Locale::En, TradeKind::Fee, input), Err(usage(TradeKind::Fee)), "{}", input);
+        }
+    }
+}
@@ -0,0 +1,222 @@
+// Mensajes de precio que se actualizan solos (/live). Cada sesión edita su mensaje periódicamente
+// hasta que se acaba el tiempo o alguien pulsa "Stop"; si el precio no cambia no se edita.
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::Utc;
+use teloxide::prelude::*;
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, MessageId};
+use teloxide::{ApiError, RequestError};
+use tokio::sync::oneshot;
+use tokio::time::Instant;
+
+use crate::config::env_or;
+use crate::i18n::{self, Locale};
+use crate::prices::{PriceService, Quote};
+use crate::providers::Pair;
+
+// Callback data del botón "Stop"
+pub const STOP_LIVE: &str = "live_stop";
+// Duración por defecto y máxima de una sesión, en minutos
+const DEFAULT_MINUTES: u64 = 5;
+const MAX_MINUTES: u64 = 60;
+// Telegram limita las ediciones por chat (unas 20 por minuto en grupos); el presupuesto se reparte
+// entre las sesiones activas del chat
+const MAX_EDITS_PER_MINUTE: u32 = 20;
+
+pub struct LiveSessions {
+    // Aviso de parada de cada mensaje en vivo
+    sessions: Mutex<HashMap<(ChatId, MessageId), oneshot::Sender<()>>>,
+    interval: Duration,
+    max_per_chat: usize,
+}
+
+impl LiveSessions {
+    // LIVE_UPDATE_SECS (por defecto 5) es el ritmo de edición y LIVE_MAX_PER_CHAT (por defecto 3)
+    // el número de mensajes en vivo simultáneos en un chat
+    pub fn from_env() -> Self {
+        LiveSessions {
+            sessions: Mutex::new(HashMap::new()),
+            interval: Duration::from_secs(env_or("LIVE_UPDATE_SECS", 5).max(1)),
+            max_per_chat: env_or("LIVE_MAX_PER_CHAT", 3),
+        }
+    }
+
+    fn active_in(&self, chat_id: ChatId) -> usize {
+        self.sessions.lock().unwrap().keys().filter(|(chat, _)| *chat == chat_id).count()
+    }
+
+    // Espera entre ediciones de cada sesión de un chat: la configurada, alargada lo necesario para que
+    // entre todas no pasen de MAX_EDITS_PER_MINUTE. Se recalcula en cada vuelta al empezar o acabar sesiones.
+    fn interval_in(&self, chat_id: ChatId) -> Duration {
+        let active = self.active_in(chat_id).max(1) as u32;
+        self.interval.max(Duration::from_secs(60) * active / MAX_EDITS_PER_MINUTE)
+    }
+
+    // Detiene la sesión de un mensaje; devuelve false si ya había terminado
+    pub fn stop(&self, chat_id: ChatId, message_id: MessageId) -> bool {
+        match self.sessions.lock().unwrap().remove(&(chat_id, message_id)) {
+            Some(stop) => stop.send(()).is_ok(),
+            None => false,
+        }
+    }
+}
+
+// Interpreta "/live <símbolo> [minutos]"
+pub fn parse_live_args(locale: Locale, input: &str) -> Result<(String, Duration), String> {
+    let args: Vec<&str> = input.split_whitespace().collect();
+    let minutes = match args.as_slice() {
+        [_] => Some(DEFAULT_MINUTES),
+        [_, minutes] => minutes.parse::<u64>().ok().filter(|m| (1..=MAX_MINUTES).contains(m)),
+        _ => None,
+    };
+    match minutes {
+        Some(minutes) => Ok((args[0].to_string(), Duration::from_secs(minutes * 60))),
+        None => Err(i18n::t(locale, "live.usage", &[("max", &MAX_MINUTES)])),
+    }
+}
+
+fn stop_keyboard(locale: Locale) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::default()
+        .append_row(vec![InlineKeyboardButton::callback(i18n::t(locale, "live.stop", &[]), STOP_LIVE)])
+}
+
+// Texto del mensaje en vivo; la hora es la del último cambio de precio, no la de la última consulta
+fn live_text(locale: Locale, quote: &Quote, status: &str) -> String {
+    let price = i18n::t(locale, "price", &[("pair", &quote.pair), ("price", &i18n::price(locale, quote.price)), ("source", &quote.source)]);
+    let changed = i18n::t(locale, "live.last_change", &[("time", &Utc::now().format("%H:%M:%S"))]);
+    format!("{}\n{}\n{}", price, changed, status)
+}
+
+// Publica el mensaje en vivo y lanza la tarea que lo mantiene actualizado
+pub async fn start(bot: Bot, prices: Arc<PriceService>, sessions: Arc<LiveSessions>, chat_id: ChatId, locale: Locale, pair: Pair, duration: Duration) -> ResponseResult<()> {
+    if sessions.active_in(chat_id) >= sessions.max_per_chat {
+        bot.send_message(chat_id, i18n::t(locale, "live.limit", &[("max", &sessions.max_per_chat)])).await?;
+        return Ok(());
+    }
+    let quote = match prices.price(&pair).await {
+        Ok(quote) => quote,
+        Err(err) => {
+            bot.send_message(chat_id, i18n::t(locale, "error.fetch_price", &[("pair", &pair), ("error", &err.user_message(locale))])).await?;
+            return Ok(());
+        }
+    };
+
+    let minutes = (duration.as_secs() / 60) as usize;
+    let status = i18n::tn(locale, "live.status", minutes, &[]);
+    let message = bot.send_message(chat_id, live_text(locale, &quote, &status))
+        .reply_markup(stop_keyboard(locale))
+        .await?;
+
+    let (stop_tx, stop_rx) = oneshot::channel();
+    sessions.sessions.lock().unwrap().insert((chat_id, message.id), stop_tx);
+    tokio::spawn(run(bot, prices, sessions, chat_id, message.id, locale, quote, status, duration, stop_rx));
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run(
+    bot: Bot,
+    prices: Arc<PriceService>,
+    sessions: Arc<LiveSessions>,
+    chat_id: ChatId,
+    message_id: MessageId,
+    locale: Locale,
+    mut last: Quote,
+    status: String,
+    duration: Duration,
+    mut stop: oneshot::Receiver<()>,
+) {
+    let deadline = Instant::now() + duration;
+    let mut last_text = live_text(locale, &last, &status);
+
+    let reason = loop {
+        tokio::select! {
+            _ = &mut stop => break i18n::t(locale, "live.final_stopped", &[]),
+            _ = tokio::time::sleep_until(deadline) => break i18n::t(locale, "live.final_ended", &[]),
+            _ = tokio::time::sleep(sessions.interval_in(chat_id)) => {}
+        }
+
+        let quote = match prices.price(&last.pair).await {
+            Ok(quote) => quote,
+            Err(err) => {
+                log::warn!("Live price of {} in chat {} could not be updated: {}", last.pair, chat_id, err);
+                continue;
+            }
+        };
+        // Sin cambio de precio no se edita, para no gastar el límite de ediciones
+        if quote.price == last.price && quote.source == last.source {
+            continue;
+        }
+        let text = live_text(locale, &quote, &status);
+        match bot.edit_message_text(chat_id, message_id, text.clone()).reply_markup(stop_keyboard(locale)).await {
+            Ok(_) | Err(RequestError::Api(ApiError::MessageNotModified)) => {
+                last = quote;
+                last_text = text;
+            }
+            Err(RequestError::RetryAfter(wait)) => {
+                log::warn!("Live price in chat {} rate limited, waiting {:?}", chat_id, wait.duration());
+                tokio::time::sleep(wait.duration()).await;
+            }
+            Err(RequestError::Api(ApiError::MessageToEditNotFound | ApiError::MessageIdInvalid)) => {
+                // El mensaje se ha borrado, no queda nada que actualizar
+                sessions.sessions.lock().unwrap().remove(&(chat_id, message_id));
+                return;
+            }
+            Err(err) => log::warn!("Could not edit live price in chat {}: {}", chat_id, err),
+        }
+    };
+
+    sessions.sessions.lock().unwrap().remove(&(chat_id, message_id));
+    // Último estado sin el botón "Stop"
+    let final_text = format!("{}{}", last_text.strip_suffix(status.as_str()).unwrap_or(&last_text), reason);
+    if let Err(err) = bot.edit_message_text(chat_id, message_id, final_text).await {
+        log::warn!("Could not close live price in chat {}: {}", chat_id, err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sessions(interval_secs: u64) -> LiveSessions {
+        LiveSessions { sessions: Mutex::new(HashMap::new()), interval: Duration::from_secs(interval_secs), max_per_chat: 10 }
+    }
+
+    fn open(sessions: &LiveSessions, chat_id: ChatId, count: i32) {
+        for id in 0..count {
+            sessions.sessions.lock().unwrap().insert((chat_id, MessageId(id)), oneshot::channel().0);
+        }
+    }
+
+    // Ediciones por minuto que hacen entre todas las sesiones de un chat
+    fn edits_per_minute(sessions: &LiveSessions, chat_id: ChatId) -> f64 {
+        sessions.active_in(chat_id) as f64 * 60.0 / sessions.interval_in(chat_id).as_secs_f64()
+    }
+
+    #[test]
+    fn the_edit_budget_is_shared_by_the_sessions_of_a_chat() {
+        let live = sessions(5);
+        let (group, other) = (ChatId(-100), ChatId(7));
+        open(&live, group, 1);
+        assert_eq!(live.interval_in(group), Duration::from_secs(5));
+
+        open(&live, other, 1);
+        assert_eq!(live.interval_in(group), Duration::from_secs(5), "sessions of other chats do not count");
+
+        for active in [3, 5, 10] {
+            open(&live, group, active);
+            assert_eq!(live.interval_in(group), Duration::from_secs(3 * active as u64).max(Duration::from_secs(5)));
+            assert!(edits_per_minute(&live, group) <= MAX_EDITS_PER_MINUTE as f64);
+        }
+    }
+
+    #[test]
+    fn a_short_configured_interval_is_capped_by_the_budget() {
+        let live = sessions(1);
+        assert_eq!(live.interval_in(ChatId(1)), Duration::from_secs(3));
+        open(&live, ChatId(1), 4);
+        assert_eq!(live.interval_in(ChatId(1)), Duration::from_secs(12));
+    }
+}
@@ -1,17 +1,47 @@
 // Se importan las librerías y módulos necesarios
-use rust_decimal::prelude::*;                               // para trabajar con números decimales
-use serde::Deserialize;                                     // para deserializar (convertir) datos desde formato JSON
-use dotenv;                                                 // para cargar variables de entorno desde un archivo .env
-use pretty_env_logger;                                      // para gestionar el log con colores y formato bonito
-use log;                                                    // para registrar mensajes en el log
+use std::sync::Arc;                                         // para compartir el servicio de precios entre tareas
+use rust_decimal::Decimal;                                  // para trabajar con números decimales
 
 // Importa los structs o enums para InlineKeyboardMarkup y InlineKeyboardButton
 use teloxide::{prelude::*, utils::command::BotCommands};    // Librería para crear bots de Telegram
-use teloxide::types::{WebAppInfo, InlineKeyboardMarkup, InlineKeyboardButton, Update, UpdateKind};
-use teloxide::update_listeners::Polling;
-use teloxide::update_listeners::AsUpdateStream;
-use futures_util::stream::StreamExt;
-use std::pin::Pin;
+use teloxide::dispatching::UpdateHandler;
+use teloxide::net::Download;
+use teloxide::types::{InlineKeyboardMarkup, InlineKeyboardButton, ParseMode, InputFile, ChatAction};
+
+mod aggregate;                                              // mediana de precios entre varias fuentes
+mod alerts;                                                 // alertas de precio y tarea de vigilancia
+mod cache;                                                  // caché de precios con agrupación de peticiones
+mod chart;                                                  // gráficos de velas en PNG
+mod config;                                                 // lectura de la configuración desde variables de entorno
+mod convert;                                                // conversión de importes entre activos y divisas
+mod digest;                                                 // resúmenes de mercado programados por chat
+mod gains;                                                  // cálculo de ganancias realizadas por FIFO, LIFO o coste medio
+mod history;                                                // historial de precios para las alertas de movimiento
+mod i18n;                                                   // traducciones de los mensajes (es, en) y formato de números
+mod inline;                                                 // modo inline con búsqueda aproximada de símbolos
+mod live;                                                   // mensajes de precio que se actualizan solos
+mod ledger;                                                 // registro de operaciones y /pnl
+mod market;                                                 // precios en tiempo real por WebSocket
+mod menu;                                                   // lista de comandos y botón de menú registrados en Telegram
+mod portfolio;                                              // carteras por usuario con su valoración
+mod prices;                                                 // resolución de símbolos y consulta de precios
+mod providers;                                              // proveedores de precios (Binance, Coinbase, Kraken, CoinGecko)
+mod storage;                                                // persistencia de chats, alertas, carteras y ajustes
+mod transactions;                                           // importación y exportación CSV de operaciones
+mod watchlist;                                              // listas de seguimiento por chat
+mod webapp;                                                 // Web App con precios y gráficos y su API JSON
+mod webhook;                                                // modo webhook como alternativa al polling
+
+use aggregate::Aggregate;
+use alerts::Alerts;
+use gains::TradeKind;
+use history::PriceHistory;
+use i18n::Locale;
+use inline::InlineSettings;
+use live::LiveSessions;
+use prices::{DailyQuote, PriceService, Quote, SourceError};
+use providers::{BinanceProvider, FxProvider, Pair};
+use storage::Storage;
 
 // La función main es el punto de entrada del programa.
 // La anotación #[tokio::main] indica que se ejecutará en el runtime asíncrono de Tokio
@@ -28,36 +58,141 @@ async fn main() {
     log::info!("Starting command bot...");
 
     // Crea una instancia del bot usando el token almacenado en las variables de entorno
-    let bot = Bot::from_env();
-
-    // Listener para procesar los comandos
-    let bot_commands = bot.clone();
-    let commands_fut = Command::repl(bot_commands, |bot, msg, cmd| async move {
-        answer(bot, msg, cmd).await
-    });
-
-    // Listener para callback queries usando un update listener que es un Stream
-    let bot_callbacks = bot.clone();
-    let cb_fut = async move {
-        let mut polling = Polling::builder(bot_callbacks.clone())
-            .drop_pending_updates()
-            .timeout(std::time::Duration::from_secs(30))
-            .build();
-
-        let mut stream = Box::pin(polling.as_stream());
-        
-        while let Some(update_result) = stream.next().await {
-            if let Ok(update) = update_result {
-                if let Update { kind: UpdateKind::CallbackQuery(query), .. } = update {
-                    if let Err(err) = handle_callback_query(bot_callbacks.clone(), query).await {
-                        log::error!("Error in callback query handler: {:?}", err);
-                    }
-                }
+    let mut bot = Bot::from_env();
+    // TELEGRAM_API_URL permite usar un servidor Bot API propio (o un doble local en pruebas)
+    if let Ok(api_url) = std::env::var("TELEGRAM_API_URL") {
+        match reqwest::Url::parse(api_url.trim()) {
+            Ok(url) => bot = bot.set_api_url(url),
+            Err(err) => {
+                log::error!("Invalid TELEGRAM_API_URL '{}': {}", api_url, err);
+                std::process::exit(1);
             }
         }
+    }
+
+    // Crea la cadena de proveedores de precios configurada en PRICE_PROVIDERS
+    let providers = match providers::providers_from_env() {
+        Ok(providers) => providers,
+        Err(err) => {
+            log::error!("Invalid price provider configuration: {}", err);
+            std::process::exit(1);
+        }
+    };
+    // Stream opcional de precios en tiempo real (PRICE_STREAM=true)
+    let stream = market::StreamSettings::from_env()
+        .map(|settings| (Arc::new(market::PriceBook::new(settings.max_age)), settings.url));
+    let mut prices = PriceService::from_env(providers);
+    if let Some((book, _)) = &stream {
+        prices = prices.with_book(book.clone());
+    }
+    let prices = Arc::new(prices);
+    log::info!("Price sources: {}", prices.source_names());
+
+    // Abre el almacenamiento y aplica las migraciones de esquema pendientes antes de cargar nada
+    let storage = match storage::storage_from_env() {
+        Ok(storage) => storage,
+        Err(err) => {
+            log::error!("Could not open storage: {}", err);
+            std::process::exit(1);
+        }
     };
+    if let Err(err) = storage.migrate() {
+        log::error!("Could not migrate the database: {}", err);
+        std::process::exit(1);
+    }
+
+    // Registro de alertas compartido entre los comandos y la tarea de vigilancia.
+    // MOVE_ALERT_REARM_RATIO controla la histéresis de las alertas de movimiento (por defecto 0.5) y
+    // MOVE_SAMPLE_SECS cada cuánto se muestrean sus precios (por defecto cada 60 segundos), que es
+    // también la ventana más corta que se acepta.
+    let sample_interval = std::time::Duration::from_secs(config::env_or("MOVE_SAMPLE_SECS", 60).max(1));
+    let alerts = match Alerts::load(storage.clone(), config::env_or("MOVE_ALERT_REARM_RATIO", Decimal::new(5, 1)), sample_interval) {
+        Ok(alerts) => Arc::new(alerts),
+        Err(err) => {
+            log::error!("Could not load alerts: {}", err);
+            std::process::exit(1);
+        }
+    };
+    let history = Arc::new(PriceHistory::default());
+
+    // Los gráficos usan el endpoint de velas de Binance, que no tiene equivalente en el resto de proveedores
+    let charts = Arc::new(BinanceProvider::from_env());
+
+    // Tarea que vigila las alertas de precio (ALERT_POLL_SECS, por defecto cada 30 segundos)
+    let alert_interval = std::time::Duration::from_secs(config::env_or("ALERT_POLL_SECS", 30).max(1));
+    tokio::spawn(alerts::watch(bot.clone(), prices.clone(), alerts.clone(), history.clone(), alert_interval));
+
+    // Tarea que muestrea los precios para las alertas de movimiento
+    tokio::spawn(history::sample(prices.clone(), alerts.clone(), history, sample_interval));
+
+    // Tarea que envía los resúmenes programados (DIGEST_POLL_SECS, por defecto cada 30 segundos). Un resumen
+    // perdido por una caída se envía con retraso si no han pasado más de DIGEST_MAX_DELAY_MINS (por defecto 60)
+    let digest_interval = std::time::Duration::from_secs(config::env_or("DIGEST_POLL_SECS", 30).max(1));
+    let digest_max_delay = std::time::Duration::from_secs(config::env_or("DIGEST_MAX_DELAY_MINS", 60) * 60);
+    tokio::spawn(digest::run(bot.clone(), prices.clone(), storage.clone(), digest_interval, digest_max_delay));
+
+    // Tarea que mantiene el libro de precios en tiempo real suscrito a los pares en uso
+    if let Some((book, url)) = stream {
+        tokio::spawn(market::run(book, storage.clone(), alerts.clone(), url));
+    }
+
+    // Tarea que escribe en el log los aciertos y fallos de la caché de precios (PRICE_CACHE_STATS_SECS, por defecto cada 5 minutos)
+    let stats_interval = std::time::Duration::from_secs(config::env_or("PRICE_CACHE_STATS_SECS", 300).max(1));
+    tokio::spawn(cache::log_stats(prices.clone(), stats_interval));
+
+    // Registra en Telegram la lista de comandos por idioma y tipo de chat y el botón de menú
+    tokio::spawn(menu::setup(bot.clone()));
+
+    // Servidor opcional de la Web App (WEBAPP_ADDR), con la página y su API JSON
+    match webapp::WebAppSettings::from_env() {
+        Ok(Some(settings)) => {
+            tokio::spawn(webapp::serve(settings, prices.clone(), storage.clone(), charts.clone(), bot.token().to_string()));
+        }
+        Ok(None) => {}
+        Err(err) => {
+            log::error!("Invalid web app configuration: {}", err);
+            std::process::exit(1);
+        }
+    }
+
+    // Un único dispatcher recibe todas las actualizaciones y las reparte por tipo.
+    // Las dependencias compartidas se inyectan una sola vez y cada handler pide las que necesita.
+    let mut dispatcher = Dispatcher::builder(bot.clone(), handler_tree())
+        .dependencies(dptree::deps![prices, alerts, storage, charts, Arc::new(LiveSessions::from_env()), Arc::new(FxProvider::from_env()), Arc::new(InlineSettings::from_env())])
+        .default_handler(|update| async move {
+            log::trace!("Unhandled update: {:?}", update);
+        })
+        .error_handler(LoggingErrorHandler::with_custom_text("An error has occurred in the dispatcher"))
+        .enable_ctrlc_handler()
+        .build();
+
+    // Las actualizaciones llegan por long polling salvo que BOT_MODE=webhook
+    match webhook::WebhookSettings::from_env() {
+        Ok(None) => dispatcher.dispatch().await,
+        Ok(Some(settings)) => {
+            let listener = match webhook::listen(bot, settings).await {
+                Ok(listener) => listener,
+                Err(err) => {
+                    log::error!("Could not set up the webhook: {}", err);
+                    std::process::exit(1);
+                }
+            };
+            dispatcher.dispatch_with_listener(listener, LoggingErrorHandler::with_custom_text("An error from the update listener")).await;
+        }
+        Err(err) => {
+            log::error!("Invalid webhook configuration: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
 
-    tokio::join!(commands_fut, cb_fut);
+// Árbol de handlers: comandos en mensajes, pulsaciones de botones y consultas inline
+fn handler_tree() -> UpdateHandler<teloxide::RequestError> {
+    dptree::entry()
+        .branch(Update::filter_message().filter_command::<Command>().endpoint(answer))
+        .branch(Update::filter_message().filter(is_import_document).endpoint(handle_import))
+        .branch(Update::filter_callback_query().endpoint(handle_callback_query))
+        .branch(Update::filter_inline_query().endpoint(handle_inline_query))
 }
 
 // Se define una enumeración que representa los comandos que el bot soporta.
@@ -71,116 +206,585 @@ enum Command {
     Help,
     #[command(description = "Get USDT/BTC price.")]
     GetBtcPrice,
+    #[command(description = "Get the price of a trading pair, e.g. /price ethusdt or /price eth. Add 'median' to aggregate all sources.")]
+    Price(String),
+    #[command(description = "Post a price that keeps updating for some minutes, e.g. /live btc 10.")]
+    Live(String),
+    #[command(description = "Convert an amount between assets or fiat currencies, e.g. /convert 0.35 btc eur.")]
+    Convert(String),
+    #[command(description = "Get 24h statistics of a trading pair, e.g. /stats btc.")]
+    Stats(String),
+    #[command(description = "Get a candlestick chart, e.g. /chart btc 1h 7d.")]
+    Chart(String),
+    #[command(description = "Set a price alert, e.g. /alert btcusdt above 70000.")]
+    Alert(String),
+    #[command(description = "Alert on a percentage move within a time window, e.g. /movealert btc 5% 1h.")]
+    MoveAlert(String),
+    #[command(description = "List the price alerts of this chat.")]
+    Alerts,
+    #[command(description = "Remove a price alert by id, e.g. /unalert 3.")]
+    Unalert(String),
+    #[command(description = "Add or remove watchlist symbols, e.g. /watch add btc eth sol or /watch remove sol.")]
+    Watch(String),
+    #[command(description = "Show prices and 24h change of the watchlist.")]
+    Watchlist,
+    #[command(description = "Add or remove holdings of your portfolio, e.g. /hold add btc 0.5 @ 42000 or /hold remove btc.")]
+    Hold(String),
+    #[command(description = "Show your portfolio with its current value and unrealized P&L.")]
+    Portfolio,
+    #[command(description = "Record a buy in your trade ledger, e.g. /buy btc 0.5 @ 42000 fee 5.")]
+    Buy(String),
+    #[command(description = "Record a sale in your trade ledger, e.g. /sell btc 0.2 @ 50000.")]
+    Sell(String),
+    #[command(description = "Record a fee paid in an asset, e.g. /fee btc 0.0005.")]
+    Fee(String),
+    #[command(description = "Show realized and unrealized P&L of your trades, e.g. /pnl 2024. Use /pnl method lifo to change the cost method.")]
+    Pnl(String),
+    #[command(description = "Schedule a market digest, e.g. /digest daily 08:00 Europe/Madrid btc eth, /digest list or /digest cancel 1.")]
+    Digest(String),
+    #[command(description = "Download your trade ledger as a CSV file.")]
+    Export,
+    #[command(description = "Import trades from a CSV file (Binance trade history or the /export format).")]
+    Import,
+    #[command(description = "Choose the language of this chat, e.g. /lang es, or /lang auto to follow each user.")]
+    Lang(String),
 }
 
-pub enum MenuButton {
-    Commands,
-    WebApp {
-        text: String,
-        web_app: WebAppInfo,
-    },
-    Default,
-}
+// Prefijo del callback data del botón "Update Price"; el par va a continuación (p. ej. "update_price:ETH/USDT")
+const UPDATE_PRICE_PREFIX: &str = "update_price:";
+// Igual que el anterior pero para los mensajes con la mediana de todas las fuentes
+const UPDATE_MEDIAN_PREFIX: &str = "update_median:";
+// Callback data de los mensajes enviados por versiones anteriores del bot
+const LEGACY_UPDATE_BTC_PRICE: &str = "update_btc_price";
 
-// Se define una estructura para deserializar la respuesta del API.
-// El atributo Deserialize permite transformar el JSON recibido en una instancia de esta estructura.
-#[allow(non_snake_case)]
-#[derive(Deserialize, Debug)]
-struct PriceResponse {
-    price: String, // Aquí se espera que el JSON tenga una propiedad "price" que es un String
-}
+// Esta función procesa el comando recibido y envía la respuesta al usuario.
+// Los servicios compartidos llegan inyectados por el dispatcher, de ahí el número de argumentos.
+#[allow(clippy::too_many_arguments)]
+async fn answer(bot: Bot, msg: Message, cmd: Command, prices: Arc<PriceService>, alerts: Arc<Alerts>, storage: Arc<dyn Storage>, charts: Arc<BinanceProvider>, live: Arc<LiveSessions>, fx: Arc<FxProvider>) -> ResponseResult<()> {
+    // Registra el chat para que sobreviva a los reinicios junto con sus alertas y ajustes
+    if let Err(err) = storage.touch_chat(msg.chat.id) {
+        log::error!("Could not save chat {}: {}", msg.chat.id, err);
+    }
+    let language_code = msg.from.as_ref().and_then(|user| user.language_code.as_deref());
+    let locale = i18n::locale_for(storage.as_ref(), msg.chat.id, language_code);
 
-// Esta función procesa el comando recibido y envía la respuesta al usuario
-async fn answer(bot: Bot, msg: Message, cmd: Command) -> ResponseResult<()> {
     match cmd { // Se evalúa qué comando fue recibido
         Command::Info => {
             // Envía un mensaje con la info del bot tomando las variables de entorno APP_NAME y APP_VERSION
             bot.send_message(
                 msg.chat.id,
-                format!("Meow! Soy {}, en mi Version: {}. Solo puedo obtener el precio del Bitcoin por ahora. (BTC/USDT)",
-                    std::env::var("APP_NAME").unwrap_or("Bot".to_string()),
-                    std::env::var("APP_VERSION").unwrap_or("0.1".to_string())
-                ))
+                i18n::t(locale, "info", &[
+                    ("name", &std::env::var("APP_NAME").unwrap_or("Bot".to_string())),
+                    ("version", &std::env::var("APP_VERSION").unwrap_or("0.1".to_string())),
+                    ("sources", &prices.source_names()),
+                ]))
             .await?
         }
         Command::Help => {
             // Envía un mensaje de ayuda con la descripción de los comandos disponibles
-            bot.send_message(msg.chat.id, Command::descriptions().to_string()).await?
+            bot.send_message(msg.chat.id, menu::help_text(locale)).await?
         }
         Command::GetBtcPrice => {
-            // Define un botón con callback data "update_btc_price"
-            let keyboard = InlineKeyboardMarkup::default()
-                .append_row(vec![
-                    InlineKeyboardButton::callback("Update Price", "update_btc_price".to_string()),
-                ]);
-        
-            match get_bitcoin_price().await {
-                Ok(val) => {
-                    let price = format!("{:.2}", val);
-                    // Envía el mensaje inicial con el precio y el teclado adjunto
-                    bot.send_message(
-                        msg.chat.id, 
-                        format!("The price of the bitcoin is: {}", price)
-                    )
-                    .reply_markup(keyboard)
+            // Se mantiene por compatibilidad: equivale a /price btcusdt
+            send_price(&bot, msg.chat.id, locale, &prices, &Pair::new("BTC", "USDT")).await?
+        }
+        Command::Price(input) => {
+            // Formato: /price <símbolo> [median]
+            let args: Vec<&str> = input.split_whitespace().collect();
+            match args.as_slice() {
+                [symbol] => match prices.resolve(symbol).await {
+                    Ok(pair) => send_price(&bot, msg.chat.id, locale, &prices, &pair).await?,
+                    Err(err) => {
+                        bot.send_message(msg.chat.id, resolve_error(locale, &err)).await?
+                    }
+                },
+                [symbol, mode] if mode.eq_ignore_ascii_case("median") => match prices.resolve(symbol).await {
+                    Ok(pair) => send_median_price(&bot, msg.chat.id, locale, &prices, &pair).await?,
+                    Err(err) => {
+                        bot.send_message(msg.chat.id, resolve_error(locale, &err)).await?
+                    }
+                },
+                _ => {
+                    bot.send_message(msg.chat.id, i18n::t(locale, "price.usage", &[])).await?
+                }
+            }
+        }
+        Command::Live(input) => {
+            match live::parse_live_args(locale, &input) {
+                Ok((symbol, duration)) => match prices.resolve(&symbol).await {
+                    Ok(pair) => live::start(bot, prices, live, msg.chat.id, locale, pair, duration).await?,
+                    Err(err) => {
+                        bot.send_message(msg.chat.id, resolve_error(locale, &err)).await?;
+                    }
+                },
+                Err(usage) => {
+                    bot.send_message(msg.chat.id, usage).await?;
+                }
+            }
+            return Ok(());
+        }
+        Command::Convert(input) => {
+            let text = match convert::parse_convert_args(locale, &input) {
+                Ok((amount, from, to)) => match convert::convert(&prices, &fx, amount, &from, &to).await {
+                    Ok(conversion) => convert::conversion_text(locale, &conversion, &fx),
+                    Err(err) => i18n::t(locale, "error.convert", &[("from", &from), ("to", &to), ("error", &err.user_message(locale))]),
+                },
+                Err(usage) => usage,
+            };
+            bot.send_message(msg.chat.id, text).await?
+        }
+        Command::Stats(input) => {
+            let text = match input.split_whitespace().collect::<Vec<_>>().as_slice() {
+                [symbol] => match prices.resolve(symbol).await {
+                    Ok(pair) => match prices.ticker_24h(&pair).await {
+                        Ok(quote) => stats_text(locale, &quote),
+                        Err(err) => i18n::t(locale, "error.fetch_stats", &[("pair", &pair), ("error", &err.user_message(locale))]),
+                    },
+                    Err(err) => resolve_error(locale, &err),
+                },
+                _ => i18n::t(locale, "stats.usage", &[]),
+            };
+            bot.send_message(msg.chat.id, text).await?
+        }
+        Command::Chart(input) => {
+            let request = match chart::parse_chart_args(locale, &input) {
+                Ok(request) => request,
+                Err(usage) => {
+                    bot.send_message(msg.chat.id, usage).await?;
+                    return Ok(());
+                }
+            };
+            let pair = match prices.resolve(&request.symbol).await {
+                Ok(pair) => pair,
+                Err(err) => {
+                    bot.send_message(msg.chat.id, resolve_error(locale, &err)).await?;
+                    return Ok(());
+                }
+            };
+            bot.send_chat_action(msg.chat.id, ChatAction::UploadPhoto).await?;
+            let candles = match charts.klines(&pair, request.interval, request.candles).await {
+                Ok(candles) if !candles.is_empty() => candles,
+                Ok(_) => {
+                    bot.send_message(msg.chat.id, i18n::t(locale, "chart.no_data", &[("pair", &pair)])).await?;
+                    return Ok(());
+                }
+                Err(err) => {
+                    log::warn!("Could not fetch {} candles: {}", pair, err);
+                    bot.send_message(msg.chat.id, i18n::t(locale, "error.fetch_candles", &[("pair", &pair), ("error", &err.user_message(locale))])).await?;
+                    return Ok(());
+                }
+            };
+            let caption = chart::caption(locale, &pair, &request, &candles);
+            // El dibujo es trabajo de CPU, así que se hace fuera del runtime asíncrono
+            let png = tokio::task::spawn_blocking(move || chart::render(&candles, chart::WIDTH, chart::HEIGHT)).await;
+            match png {
+                Ok(Ok(png)) => {
+                    bot.send_photo(msg.chat.id, InputFile::memory(png).file_name("chart.png"))
+                        .caption(caption)
+                        .await?
+                }
+                Ok(Err(err)) => {
+                    log::error!("Could not render chart of {}: {}", pair, err);
+                    bot.send_message(msg.chat.id, i18n::t(locale, "chart.render_failed", &[])).await?
+                }
+                Err(err) => {
+                    log::error!("Chart rendering task failed: {}", err);
+                    bot.send_message(msg.chat.id, i18n::t(locale, "chart.render_failed", &[])).await?
+                }
+            }
+        }
+        Command::Alert(input) => {
+            let text = match alerts::parse_alert_args(locale, &input) {
+                Ok((symbol, direction, threshold)) => match prices.resolve(&symbol).await {
+                    // Se consulta el precio actual para saber de qué lado del umbral se parte
+                    Ok(pair) => match prices.price(&pair).await {
+                        Ok(quote) => match alerts.add(msg.chat.id, pair, direction, threshold, quote.price) {
+                            Ok(alert) if alert.armed => i18n::t(locale, "alert.created", &[
+                                ("alert", &alert.describe(locale)),
+                                ("price", &i18n::price(locale, quote.price)),
+                            ]),
+                            Ok(alert) => i18n::t(locale, "alert.created_met", &[
+                                ("alert", &alert.describe(locale)),
+                                ("side", &direction.side(locale)),
+                                ("price", &i18n::price(locale, quote.price)),
+                            ]),
+                            Err(err) => {
+                                log::error!("Could not save alert: {}", err);
+                                i18n::t(locale, "alert.save_failed", &[])
+                            }
+                        },
+                        Err(err) => i18n::t(locale, "error.fetch_price", &[("pair", &symbol), ("error", &err.user_message(locale))]),
+                    },
+                    Err(err) => resolve_error(locale, &err),
+                },
+                Err(usage) => usage,
+            };
+            bot.send_message(msg.chat.id, text).await?
+        }
+        Command::MoveAlert(input) => {
+            let text = match alerts::parse_move_alert_args(locale, &input, alerts.sample_interval()) {
+                Ok((symbol, percent, window)) => match prices.resolve(&symbol).await {
+                    Ok(pair) => match alerts.add_move(msg.chat.id, pair, percent, window) {
+                        Ok(alert) => i18n::t(locale, "alert.created_move", &[("alert", &alert.describe(locale))]),
+                        Err(err) => {
+                            log::error!("Could not save alert: {}", err);
+                            i18n::t(locale, "alert.save_failed", &[])
+                        }
+                    },
+                    Err(err) => resolve_error(locale, &err),
+                },
+                Err(usage) => usage,
+            };
+            bot.send_message(msg.chat.id, text).await?
+        }
+        Command::Alerts => {
+            let list = alerts.list(msg.chat.id);
+            let text = if list.is_empty() {
+                i18n::t(locale, "alerts.empty", &[])
+            } else {
+                let lines: Vec<String> = list.iter().map(|a| a.describe(locale)).collect();
+                format!("{}\n{}", i18n::t(locale, "alerts.title", &[]), lines.join("\n"))
+            };
+            bot.send_message(msg.chat.id, text).await?
+        }
+        Command::Unalert(input) => {
+            let text = match input.trim().trim_start_matches('#').parse::<u64>() {
+                Ok(id) => match alerts.remove(msg.chat.id, id) {
+                    Ok(true) => i18n::t(locale, "unalert.removed", &[("id", &id)]),
+                    Ok(false) => i18n::t(locale, "unalert.not_found", &[("id", &id)]),
+                    Err(err) => {
+                        log::error!("Could not remove alert #{}: {}", id, err);
+                        i18n::t(locale, "unalert.remove_failed", &[])
+                    }
+                },
+                Err(_) => i18n::t(locale, "unalert.usage", &[]),
+            };
+            bot.send_message(msg.chat.id, text).await?
+        }
+        Command::Watch(input) => {
+            let text = watchlist::watch_command(&prices, storage.as_ref(), msg.chat.id, locale, &input).await;
+            bot.send_message(msg.chat.id, text).await?
+        }
+        Command::Watchlist => match storage.watchlist(msg.chat.id) {
+            Ok(pairs) => {
+                let mut request = bot.send_message(msg.chat.id, watchlist::watchlist_table(&prices, &pairs, locale).await)
+                    .parse_mode(ParseMode::Html);
+                if !pairs.is_empty() {
+                    request = request.reply_markup(watchlist::watchlist_keyboard(locale));
+                }
+                request.await?
+            }
+            Err(err) => {
+                log::error!("Could not load watchlist of chat {}: {}", msg.chat.id, err);
+                bot.send_message(msg.chat.id, i18n::t(locale, "watchlist.load_failed", &[])).await?
+            }
+        },
+        // La cartera es de quien escribe; en los canales no hay remitente y no se puede usar
+        Command::Hold(input) => {
+            let text = match msg.from.as_ref() {
+                Some(user) => portfolio::hold_command(&prices, storage.as_ref(), user.id, locale, &input).await,
+                None => i18n::t(locale, "personal.portfolio", &[("command", &"/hold")]),
+            };
+            bot.send_message(msg.chat.id, text).await?
+        }
+        Command::Portfolio => match msg.from.as_ref().map(|user| (user.id, storage.holdings(user.id))) {
+            Some((_, Ok(holdings))) => {
+                bot.send_message(msg.chat.id, portfolio::portfolio_table(&prices, &holdings, locale).await)
+                    .parse_mode(ParseMode::Html)
                     .await?
+            }
+            Some((user_id, Err(err))) => {
+                log::error!("Could not load holdings of user {}: {}", user_id, err);
+                bot.send_message(msg.chat.id, i18n::t(locale, "portfolio.load_failed", &[])).await?
+            }
+            None => bot.send_message(msg.chat.id, i18n::t(locale, "personal.portfolio", &[("command", &"/portfolio")])).await?,
+        },
+        Command::Buy(input) => send_trade(&bot, &msg, &prices, storage.as_ref(), locale, TradeKind::Buy, &input).await?,
+        Command::Sell(input) => send_trade(&bot, &msg, &prices, storage.as_ref(), locale, TradeKind::Sell, &input).await?,
+        Command::Fee(input) => send_trade(&bot, &msg, &prices, storage.as_ref(), locale, TradeKind::Fee, &input).await?,
+        Command::Digest(input) => {
+            let text = digest::digest_command(&prices, storage.as_ref(), msg.chat.id, locale, &input).await;
+            bot.send_message(msg.chat.id, text).await?
+        }
+        Command::Export => {
+            let Some(user) = msg.from.as_ref() else {
+                bot.send_message(msg.chat.id, i18n::t(locale, "personal.ledger", &[("command", &"/export")])).await?;
+                return Ok(());
+            };
+            match transactions::export_for(storage.as_ref(), user.id) {
+                Ok(Some(csv)) => {
+                    let name = format!("trades-{}.csv", chrono::Utc::now().format("%Y-%m-%d"));
+                    bot.send_document(msg.chat.id, InputFile::memory(csv).file_name(name)).await?
                 }
+                Ok(None) => bot.send_message(msg.chat.id, i18n::t(locale, "export.empty", &[])).await?,
                 Err(err) => {
-                    bot.send_message(
-                        msg.chat.id, 
-                        format!("Error fetching bitcoin price: {:?}", err)
-                    ).await?
+                    log::error!("Could not export trades of user {}: {}", user.id, err);
+                    bot.send_message(msg.chat.id, i18n::t(locale, "export.failed", &[])).await?
                 }
             }
         }
+        Command::Import => bot.send_message(msg.chat.id, i18n::t(locale, "import.help", &[])).await?,
+        Command::Lang(input) => {
+            bot.send_message(msg.chat.id, i18n::lang_command(storage.as_ref(), msg.chat.id, language_code, &input)).await?
+        }
+        Command::Pnl(input) => {
+            let text = match msg.from.as_ref() {
+                Some(user) => ledger::pnl_command(&prices, storage.as_ref(), user.id, locale, &input).await,
+                None => i18n::t(locale, "personal.ledger", &[("command", &"/pnl")]),
+            };
+            bot.send_message(msg.chat.id, text).await?
+        }
     };
     Ok(())
 }
 
-// Esta función se encarga de obtener el precio del bitcoin desde el API de Binance.
-// Utiliza reqwest para hacer una petición HTTP asíncrona.
-pub async fn get_bitcoin_price() -> Result<Decimal, Box<dyn std::error::Error + Send + Sync>> {
-    // Hace una petición GET al API de Binance
-    let resp = reqwest::get("https://api.binance.com/api/v3/ticker/price?symbol=BTCUSDT").await?;
-    // Deserializa la respuesta JSON en la estructura PriceResponse
-    let body = resp.json::<PriceResponse>().await?;
-    // Intenta convertir el precio (String) a un tipo Decimal para manejo numérico
-    let price = match Decimal::from_str(&body.price) {
-        Ok(num) => num,
-        Err(_) => {
-            println!("Error on converting");
-            // En caso de error al convertir, retorna un valor por defecto
-            Decimal::new(0, 1)
+// Registra una operación de /buy, /sell o /fee en el registro de quien escribe
+async fn send_trade(bot: &Bot, msg: &Message, prices: &PriceService, storage: &dyn Storage, locale: Locale, kind: TradeKind, input: &str) -> ResponseResult<Message> {
+    let text = match msg.from.as_ref() {
+        Some(user) => ledger::trade_command(prices, storage, user.id, locale, kind, input).await,
+        None => i18n::t(locale, "personal.trade", &[]),
+    };
+    bot.send_message(msg.chat.id, text).await
+}
+
+// Fichero enviado con "/import" como pie, que Telegram no trata como comando
+fn is_import_document(msg: Message) -> bool {
+    msg.document().is_some() && msg.caption().is_some_and(|caption| caption.trim_start().starts_with("/import"))
+}
+
+// Descarga el CSV adjunto e importa sus operaciones en el registro de quien lo envía
+async fn handle_import(bot: Bot, msg: Message, prices: Arc<PriceService>, storage: Arc<dyn Storage>) -> ResponseResult<()> {
+    let (Some(document), Some(user)) = (msg.document(), msg.from.as_ref()) else {
+        return Ok(());
+    };
+    let locale = i18n::locale_for(storage.as_ref(), msg.chat.id, user.language_code.as_deref());
+    if document.file.size > transactions::MAX_IMPORT_BYTES {
+        bot.send_message(msg.chat.id, i18n::t(locale, "import.too_large", &[("limit", &(transactions::MAX_IMPORT_BYTES / 1024))])).await?;
+        return Ok(());
+    }
+    bot.send_chat_action(msg.chat.id, ChatAction::Typing).await?;
+    let file = bot.get_file(document.file.id.clone()).await?;
+    let mut data = Vec::new();
+    if let Err(err) = bot.download_file(&file.path, &mut data).await {
+        log::warn!("Could not download import file of user {}: {}", user.id, err);
+        bot.send_message(msg.chat.id, i18n::t(locale, "import.download_failed", &[])).await?;
+        return Ok(());
+    }
+    let text = transactions::import_csv(&prices, storage.as_ref(), user.id, locale, &data).await;
+    bot.send_message(msg.chat.id, text).await?;
+    Ok(())
+}
+
+// Envía el precio actual de un par junto con el botón "Update Price" para ese mismo par
+async fn send_price(bot: &Bot, chat_id: ChatId, locale: Locale, prices: &PriceService, pair: &Pair) -> ResponseResult<Message> {
+    match prices.price(pair).await {
+        Ok(quote) => {
+            // Envía el mensaje inicial con el precio y el teclado adjunto
+            bot.send_message(chat_id, price_text(locale, &quote))
+                .reply_markup(price_keyboard(locale, pair))
+                .await
         }
+        Err(err) => bot.send_message(chat_id, price_error(locale, pair, &err)).await,
+    }
+}
+
+// Envía la mediana del precio entre todas las fuentes con el detalle por exchange
+async fn send_median_price(bot: &Bot, chat_id: ChatId, locale: Locale, prices: &PriceService, pair: &Pair) -> ResponseResult<Message> {
+    match prices.median_price(pair).await {
+        Ok((result, errors)) => {
+            bot.send_message(chat_id, median_text(locale, pair, &result, &errors))
+                .reply_markup(median_keyboard(locale, pair))
+                .await
+        }
+        Err(err) => bot.send_message(chat_id, price_error(locale, pair, &err)).await,
+    }
+}
+
+// Define un botón cuyo callback data lleva el par, para que cada mensaje se actualice por separado
+fn price_keyboard(locale: Locale, pair: &Pair) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::default()
+        .append_row(vec![
+            InlineKeyboardButton::callback(i18n::t(locale, "price.update", &[]), format!("{}{}", UPDATE_PRICE_PREFIX, pair)),
+        ])
+}
+
+fn median_keyboard(locale: Locale, pair: &Pair) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::default()
+        .append_row(vec![
+            InlineKeyboardButton::callback(i18n::t(locale, "price.update", &[]), format!("{}{}", UPDATE_MEDIAN_PREFIX, pair)),
+        ])
+}
+
+fn resolve_error(locale: Locale, err: &providers::ProviderError) -> String {
+    i18n::t(locale, "error.resolve", &[("error", &err.user_message(locale))])
+}
+
+fn price_error(locale: Locale, pair: &Pair, err: &providers::ProviderError) -> String {
+    i18n::t(locale, "error.fetch_price", &[("pair", pair), ("error", &err.user_message(locale))])
+}
+
+// Texto común para la respuesta inicial y para la edición al pulsar "Update Price".
+// Se indica la fuente porque con el failover no siempre responde el mismo exchange.
+fn price_text(locale: Locale, quote: &Quote) -> String {
+    i18n::t(locale, "price", &[("pair", &quote.pair), ("price", &i18n::price(locale, quote.price)), ("source", &quote.source)])
+}
+
+// Texto de /stats; las líneas de datos que el proveedor no ofrece se omiten
+fn stats_text(locale: Locale, quote: &DailyQuote) -> String {
+    let ticker = &quote.ticker;
+    let value = |key: &str, value: Decimal| i18n::t(locale, key, &[("value", &i18n::price(locale, value))]);
+    let mut lines = vec![
+        i18n::t(locale, "stats.title", &[("pair", &quote.pair), ("source", &quote.source)]),
+        value("stats.last", ticker.last),
+        value("stats.open", ticker.open),
+        value("stats.high", ticker.high),
+        value("stats.low", ticker.low),
+        i18n::t(locale, "stats.change", &[
+            ("change", &i18n::change(locale, ticker.change)),
+            ("percent", &i18n::change(locale, ticker.change_percent.round_dp(2))),
+        ]),
+    ];
+    if let Some(avg) = ticker.weighted_avg {
+        lines.push(value("stats.weighted_avg", avg));
+    }
+    if let Some(volume) = ticker.volume {
+        lines.push(i18n::t(locale, "stats.volume", &[("value", &i18n::price(locale, volume)), ("asset", &quote.pair.base)]));
+    }
+    if let Some(volume) = ticker.quote_volume {
+        lines.push(i18n::t(locale, "stats.quote_volume", &[("value", &i18n::price(locale, volume)), ("asset", &quote.pair.quote)]));
+    }
+    lines.join("\n")
+}
+
+// Texto de la mediana: precio, detalle por fuente, fuentes descartadas o caídas y dispersión
+fn median_text(locale: Locale, pair: &Pair, result: &Aggregate, errors: &[SourceError]) -> String {
+    let total = result.used.len() + result.outliers.len() + errors.len();
+    let mut lines = vec![i18n::t(locale, "median.title", &[
+        ("pair", pair),
+        ("price", &i18n::price(locale, result.median)),
+        ("used", &result.used.len()),
+        ("total", &total),
+    ])];
+    for source in &result.used {
+        lines.push(format!("• {}: {} ({}%)", source.source, i18n::price(locale, source.price), i18n::change(locale, source.deviation_pct)));
+    }
+    for source in &result.outliers {
+        lines.push(i18n::t(locale, "median.outlier", &[
+            ("source", &source.source),
+            ("price", &i18n::price(locale, source.price)),
+            ("deviation", &i18n::change(locale, source.deviation_pct)),
+        ]));
+    }
+    for (source, error) in errors {
+        lines.push(i18n::t(locale, "median.unavailable", &[("source", source), ("error", &error.user_message(locale))]));
+    }
+    lines.push(i18n::t(locale, "median.spread", &[
+        ("spread", &i18n::price(locale, result.spread)),
+        ("percent", &i18n::number(locale, result.spread_pct, 2, false)),
+    ]));
+    lines.join("\n")
+}
+
+async fn handle_callback_query(bot: Bot, query: CallbackQuery, prices: Arc<PriceService>, storage: Arc<dyn Storage>, live: Arc<LiveSessions>) -> ResponseResult<()> {
+    let (Some(data), Some(message)) = (&query.data, &query.message) else {
+        return Ok(());
     };
-    Ok(price)
-}
-
-async fn handle_callback_query(bot: Bot, query: CallbackQuery) -> ResponseResult<()> {
-    if let Some(data) = &query.data {
-        if data == "update_btc_price" {
-            if let Some(message) = query.message {
-                // Clona el id de la callback para poder reutilizarlo
-                let callback_id = query.id.clone();
-                // Obtiene el precio actualizado
-                match get_bitcoin_price().await {
-                    Ok(val) => {
-                        let price = format!("{:.2}", val);
-                        // Edita el mensaje para actualizar el precio
-                        bot.edit_message_text(message.chat().id, message.id(), format!("The price of the bitcoin is: {}", price))
-                            .await?;
-                    }
-                    Err(err) => {
-                        // En caso de error, responde a la callback query
-                        bot.answer_callback_query(query.id.clone())
-                           .text(format!("Error fetching bitcoin price: {:?}", err))
-                           .await?;
-                    }
+    let locale = i18n::locale_for(storage.as_ref(), message.chat().id, query.from.language_code.as_deref());
+
+    // El botón "Stop" de /live detiene la sesión; la propia sesión deja el mensaje en su estado final
+    if data == live::STOP_LIVE {
+        let key = if live.stop(message.chat().id, message.id()) { "live.stopped" } else { "live.already_ended" };
+        let text = i18n::t(locale, key, &[]);
+        bot.answer_callback_query(query.id.clone()).text(text).await?;
+        return Ok(());
+    }
+
+    // El botón "Refresh" de /watchlist vuelve a generar la tabla del chat en el mismo mensaje
+    if data == watchlist::REFRESH_WATCHLIST {
+        let chat_id = message.chat().id;
+        match storage.watchlist(chat_id) {
+            Ok(pairs) => {
+                // Con los mismos precios (p. ej. dentro del TTL de la caché) Telegram rechaza la edición, lo que
+                // no es un error; la callback query se contesta siempre para que el botón no se quede cargando
+                match bot.edit_message_text(chat_id, message.id(), watchlist::watchlist_table(&prices, &pairs, locale).await)
+                    .parse_mode(ParseMode::Html)
+                    .reply_markup(watchlist::watchlist_keyboard(locale))
+                    .await
+                {
+                    Ok(_) | Err(teloxide::RequestError::Api(teloxide::ApiError::MessageNotModified)) => {}
+                    Err(err) => log::warn!("Could not refresh watchlist of chat {}: {}", chat_id, err),
                 }
-                // Confirma la recepción de la callback query
-                bot.answer_callback_query(callback_id).await?;
+                bot.answer_callback_query(query.id.clone()).await?;
+            }
+            Err(err) => {
+                log::error!("Could not load watchlist of chat {}: {}", chat_id, err);
+                bot.answer_callback_query(query.id.clone())
+                    .text(i18n::t(locale, "watchlist.load_failed", &[]))
+                    .await?;
             }
         }
+        return Ok(());
     }
+
+    // Obtiene el par codificado en el callback data y calcula el nuevo texto del mensaje
+    let price_pair = if data == LEGACY_UPDATE_BTC_PRICE {
+        Some(Pair::new("BTC", "USDT"))
+    } else {
+        data.strip_prefix(UPDATE_PRICE_PREFIX).and_then(|p| p.parse::<Pair>().ok())
+    };
+    let update = if let Some(pair) = price_pair {
+        Some(prices.price(&pair).await.map(|quote| (price_text(locale, &quote), price_keyboard(locale, &pair))).map_err(|err| (pair, err)))
+    } else if let Some(pair) = data.strip_prefix(UPDATE_MEDIAN_PREFIX).and_then(|p| p.parse::<Pair>().ok()) {
+        Some(prices.median_price(&pair).await
+            .map(|(result, errors)| (median_text(locale, &pair, &result, &errors), median_keyboard(locale, &pair)))
+            .map_err(|err| (pair, err)))
+    } else {
+        None
+    };
+
+    match update {
+        Some(Ok((text, keyboard))) => {
+            // Edita el mensaje para actualizar el precio (se vuelve a adjuntar el teclado para no perderlo).
+            // Si el precio no ha cambiado Telegram rechaza la edición, lo que no es un error.
+            match bot.edit_message_text(message.chat().id, message.id(), text)
+                .reply_markup(keyboard)
+                .await
+            {
+                Ok(_) | Err(teloxide::RequestError::Api(teloxide::ApiError::MessageNotModified)) => {}
+                Err(err) => return Err(err),
+            }
+            // Confirma la recepción de la callback query
+            bot.answer_callback_query(query.id.clone()).await?;
+        }
+        Some(Err((pair, err))) => {
+            // En caso de error, responde a la callback query
+            bot.answer_callback_query(query.id.clone())
+               .text(price_error(locale, &pair, &err))
+               .await?;
+        }
+        None => {}
+    }
+    Ok(())
+}
+
+// Modo inline (@bot btc): responde con un artículo con el precio de cada par parecido a lo escrito
+async fn handle_inline_query(bot: Bot, query: InlineQuery, prices: Arc<PriceService>, settings: Arc<InlineSettings>) -> ResponseResult<()> {
+    let locale = i18n::user_locale(query.from.language_code.as_deref());
+    let pairs = inline::matching_pairs(&prices, &settings, &query.query).await;
+    let quotes = futures_util::future::join_all(pairs.iter().map(|pair| prices.price(pair))).await;
+    let results: Vec<_> = pairs.iter().zip(quotes)
+        .filter_map(|(pair, quote)| match quote {
+            Ok(quote) => Some(inline::article(locale, &quote, price_text(locale, &quote))),
+            Err(err) => {
+                log::warn!("Inline query could not fetch {}: {}", pair, err);
+                None
+            }
+        })
+        .collect();
+    // La respuesta va en el idioma de cada usuario, así que Telegram no debe compartirla entre usuarios
+    bot.answer_inline_query(query.id, results)
+        .cache_time(settings.cache_time)
+        .is_personal(true)
+        .await?;
     Ok(())
-}
\ No newline at end of file
+}
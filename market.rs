@@ -0,0 +1,289 @@
+// Precios en tiempo real desde los streams WebSocket de Binance. Es opcional (PRICE_STREAM=true):
+// se suscribe a los pares en uso (listas de seguimiento, alertas y consultas recientes) y mantiene
+// un libro con el último precio de cada uno, que /price usa mientras esté fresco.
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures_util::{SinkExt, StreamExt};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::alerts::Alerts;
+use crate::config::env_or;
+use crate::providers::{parse_decimal, Pair};
+use crate::storage::Storage;
+
+// Tiempo durante el que un par consultado sigue suscrito aunque nadie lo vuelva a pedir
+const RECENT_QUERY_TTL: Duration = Duration::from_secs(60 * 60);
+// Cada cuánto se recalculan las suscripciones a partir de los pares en uso
+const RESUBSCRIBE_INTERVAL: Duration = Duration::from_secs(5);
+// Espera entre reconexiones: empieza en MIN_BACKOFF y se duplica hasta MAX_BACKOFF
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+// Binance no admite más de 1024 streams por conexión
+const MAX_STREAMS: usize = 1024;
+
+// Nombre del proveedor que se muestra para los precios servidos desde el stream
+pub const SOURCE_NAME: &str = "Binance stream";
+
+// Configuración del stream leída del entorno
+pub struct StreamSettings {
+    pub url: String,
+    pub max_age: Duration,
+}
+
+impl StreamSettings {
+    // PRICE_STREAM=true lo activa; BINANCE_WS_URL cambia el servidor (p. ej. por un doble local en pruebas)
+    // y PRICE_STREAM_MAX_AGE_SECS (por defecto 10) es la antigüedad máxima de un precio para servirlo
+    pub fn from_env() -> Option<Self> {
+        if !env_or("PRICE_STREAM", false) {
+            return None;
+        }
+        Some(StreamSettings {
+            url: std::env::var("BINANCE_WS_URL").unwrap_or("wss://stream.binance.com:9443/ws".to_string()),
+            max_age: Duration::from_secs(env_or("PRICE_STREAM_MAX_AGE_SECS", 10)),
+        })
+    }
+}
+
+// Último precio de cada par recibido por el stream
+pub struct PriceBook {
+    max_age: Duration,
+    prices: Mutex<HashMap<Pair, (Decimal, Instant)>>,
+    // Pares consultados recientemente y cuándo se pidieron por última vez
+    recent: Mutex<HashMap<Pair, Instant>>,
+}
+
+impl PriceBook {
+    pub fn new(max_age: Duration) -> Self {
+        PriceBook { max_age, prices: Mutex::new(HashMap::new()), recent: Mutex::new(HashMap::new()) }
+    }
+
+    // Precio de un par si se recibió hace menos de `max_age`
+    pub fn fresh(&self, pair: &Pair) -> Option<Decimal> {
+        self.prices.lock().unwrap().get(pair)
+            .filter(|(_, received_at)| received_at.elapsed() < self.max_age)
+            .map(|(price, _)| *price)
+    }
+
+    fn update(&self, pair: Pair, price: Decimal) {
+        self.prices.lock().unwrap().insert(pair, (price, Instant::now()));
+    }
+
+    // Olvida los precios de los pares a los que ya no se está suscrito
+    fn retain(&self, pairs: &HashSet<Pair>) {
+        self.prices.lock().unwrap().retain(|pair, _| pairs.contains(pair));
+    }
+
+    fn clear(&self) {
+        self.prices.lock().unwrap().clear();
+    }
+
+    // Anota que se ha pedido un par para suscribirse a él en la siguiente vuelta
+    pub fn track(&self, pair: &Pair) {
+        self.recent.lock().unwrap().insert(pair.clone(), Instant::now());
+    }
+
+    fn recent_pairs(&self) -> Vec<Pair> {
+        let mut recent = self.recent.lock().unwrap();
+        recent.retain(|_, requested_at| requested_at.elapsed() < RECENT_QUERY_TTL);
+        recent.keys().cloned().collect()
+    }
+}
+
+// Petición de suscripción del API de streams de Binance
+#[derive(Serialize)]
+struct StreamRequest<'a> {
+    method: &'a str,
+    params: Vec<String>,
+    id: u64,
+}
+
+// Evento <símbolo>@miniTicker; "c" es el último precio
+#[derive(Deserialize)]
+struct MiniTicker {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "c")]
+    close: String,
+}
+
+// Respuesta a una petición con error, p. ej. {"error": {"code": 2, "msg": "Invalid request"}, "id": 1}
+#[derive(Deserialize)]
+struct StreamError {
+    error: serde_json::Value,
+}
+
+// Nombre del stream de un par: BTC/USDT -> btcusdt@miniTicker
+fn stream_name(pair: &Pair) -> String {
+    format!("{}{}@miniTicker", pair.base.to_lowercase(), pair.quote.to_lowercase())
+}
+
+// Todos los pares en uso: listas de seguimiento, alertas y consultas recientes
+fn wanted_pairs(book: &PriceBook, storage: &dyn Storage, alerts: &Alerts) -> HashSet<Pair> {
+    let mut pairs: HashSet<Pair> = alerts.pairs().into_iter().collect();
+    match storage.watched_pairs() {
+        Ok(watched) => pairs.extend(watched),
+        Err(err) => log::warn!("Price stream could not load the watchlists: {}", err),
+    }
+    pairs.extend(book.recent_pairs());
+    pairs
+}
+
+// Tarea del stream: conecta, mantiene las suscripciones y vuelve a conectar con backoff exponencial
+pub async fn run(book: Arc<PriceBook>, storage: Arc<dyn Storage>, alerts: Arc<Alerts>, url: String) {
+    let mut backoff = MIN_BACKOFF;
+    loop {
+        match tokio_tungstenite::connect_async(url.as_str()).await {
+            Ok((socket, _)) => {
+                log::info!("Connected to price stream {}", url);
+                let connected_at = Instant::now();
+                let err = session(socket, &book, storage.as_ref(), &alerts).await;
+                log::warn!("Price stream disconnected: {}", err);
+                // Una conexión que ha durado un rato no cuenta como fallo seguido
+                if connected_at.elapsed() > MAX_BACKOFF {
+                    backoff = MIN_BACKOFF;
+                }
+            }
+            Err(err) => log::warn!("Could not connect to price stream {}: {}", url, err),
+        }
+        book.clear();
+        log::info!("Reconnecting to price stream in {:?}", backoff);
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+}
+
+// Una conexión al stream; devuelve el motivo por el que terminó
+async fn session<S>(socket: tokio_tungstenite::WebSocketStream<S>, book: &PriceBook, storage: &dyn Storage, alerts: &Alerts) -> String
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    let (mut write, mut read) = socket.split();
+    // Streams suscritos y el par de cada uno (btcusdt@miniTicker -> BTC/USDT)
+    let mut subscribed: HashMap<String, Pair> = HashMap::new();
+    let mut next_id = 1;
+    let mut resubscribe = tokio::time::interval(RESUBSCRIBE_INTERVAL);
+
+    loop {
+        tokio::select! {
+            _ = resubscribe.tick() => {
+                let wanted = wanted_pairs(book, storage, alerts);
+                let wanted_streams: HashMap<String, Pair> = wanted.iter()
+                    .take(MAX_STREAMS)
+                    .map(|pair| (stream_name(pair), pair.clone()))
+                    .collect();
+                let added: Vec<String> = wanted_streams.keys().filter(|s| !subscribed.contains_key(*s)).cloned().collect();
+                let removed: Vec<String> = subscribed.keys().filter(|s| !wanted_streams.contains_key(*s)).cloned().collect();
+
+                for (method, params) in [("UNSUBSCRIBE", removed), ("SUBSCRIBE", added)] {
+                    if params.is_empty() {
+                        continue;
+                    }
+                    log::debug!("Price stream {} {}", method, params.join(", "));
+                    let request = serde_json::to_string(&StreamRequest { method, params, id: next_id })
+                        .expect("stream request is always serializable");
+                    next_id += 1;
+                    if let Err(err) = write.send(Message::Text(request)).await {
+                        return err.to_string();
+                    }
+                }
+                book.retain(&wanted);
+                subscribed = wanted_streams;
+            }
+            message = read.next() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    if let Ok(ticker) = serde_json::from_str::<MiniTicker>(&text) {
+                        let pair = subscribed.get(&format!("{}@miniTicker", ticker.symbol.to_lowercase()));
+                        match (pair, parse_decimal(&ticker.close)) {
+                            (Some(pair), Ok(price)) => book.update(pair.clone(), price),
+                            (Some(pair), Err(err)) => log::warn!("Price stream sent an invalid price for {}: {}", pair, err),
+                            (None, _) => {}
+                        }
+                    } else if let Ok(response) = serde_json::from_str::<StreamError>(&text) {
+                        log::warn!("Price stream rejected a request: {}", response.error);
+                    }
+                }
+                // Los ping del servidor los contesta tungstenite al leer
+                Some(Ok(Message::Close(frame))) => return format!("closed by server ({:?})", frame),
+                Some(Ok(_)) => {}
+                Some(Err(err)) => return err.to_string(),
+                None => return "connection closed".to_string(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use teloxide::types::ChatId;
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::WebSocketStream;
+
+    use super::*;
+    use crate::storage::MemoryStorage;
+
+    type ServerSocket = WebSocketStream<tokio::net::TcpStream>;
+
+    // Acepta la siguiente conexión del bot y comprueba que se suscribe a los pares en uso
+    async fn accept_and_expect_subscription(listener: &TcpListener) -> ServerSocket {
+        let (tcp, _) = tokio::time::timeout(Duration::from_secs(10), listener.accept()).await
+            .expect("the stream did not connect")
+            .unwrap();
+        let mut socket = tokio_tungstenite::accept_async(tcp).await.unwrap();
+        let Some(Ok(Message::Text(request))) = socket.next().await else {
+            panic!("expected a subscription request");
+        };
+        let request: serde_json::Value = serde_json::from_str(&request).unwrap();
+        assert_eq!(request["method"], "SUBSCRIBE");
+        assert_eq!(request["params"], serde_json::json!(["btcusdt@miniTicker"]));
+        socket
+    }
+
+    async fn send_ticker(socket: &mut ServerSocket, symbol: &str, price: &str) {
+        let event = serde_json::json!({ "e": "24hrMiniTicker", "E": 1700000000000i64, "s": symbol, "c": price, "o": "1", "h": "1", "l": "1", "v": "1", "q": "1" });
+        socket.send(Message::Text(event.to_string())).await.unwrap();
+    }
+
+    // Espera a que el libro tenga (o deje de tener) el precio indicado
+    async fn wait_for_price(book: &PriceBook, pair: &Pair, expected: Option<Decimal>) {
+        for _ in 0..200 {
+            if book.fresh(pair) == expected {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("the price of {} is {:?}, expected {:?}", pair, book.fresh(pair), expected);
+    }
+
+    #[tokio::test]
+    async fn subscribes_updates_the_book_and_reconnects() {
+        let btc = Pair::new("BTC", "USDT");
+        let storage: Arc<dyn Storage> = Arc::new(MemoryStorage::default());
+        storage.add_to_watchlist(ChatId(1), &btc).unwrap();
+        let alerts = Arc::new(Alerts::load(storage.clone(), Decimal::new(5, 1), Duration::from_secs(60)).unwrap());
+        let book = Arc::new(PriceBook::new(Duration::from_secs(60)));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let stream = tokio::spawn(run(book.clone(), storage, alerts, url));
+
+        let mut socket = accept_and_expect_subscription(&listener).await;
+        // Los eventos de pares no suscritos se ignoran
+        send_ticker(&mut socket, "ETHUSDT", "3500.00").await;
+        send_ticker(&mut socket, "BTCUSDT", "67000.50").await;
+        wait_for_price(&book, &btc, Some(Decimal::new(6700050, 2))).await;
+        assert_eq!(book.fresh(&Pair::new("ETH", "USDT")), None);
+
+        // Al caer la conexión se vacía el libro y el bot vuelve a conectar y suscribirse
+        drop(socket);
+        wait_for_price(&book, &btc, None).await;
+        let mut socket = accept_and_expect_subscription(&listener).await;
+        send_ticker(&mut socket, "BTCUSDT", "68000.00").await;
+        wait_for_price(&book, &btc, Some(Decimal::new(68000, 0))).await;
+
+        stream.abort();
+    }
+}
@@ -0,0 +1,123 @@
+// Registro en Telegram de la lista de comandos (setMyCommands) y del botón de menú del chat
+// (setChatMenuButton) al arrancar. Las descripciones salen del enum Command, en inglés, y del
+// catálogo de i18n para el resto de idiomas; cada tipo de chat recibe solo los comandos que tienen sentido en él.
+use reqwest::Url;
+use teloxide::prelude::*;
+use teloxide::types::{BotCommand, BotCommandScope, WebAppInfo};
+use teloxide::utils::command::BotCommands;
+
+use crate::config::env_or;
+use crate::i18n::{self, Locale};
+use crate::Command;
+
+// Comandos que se muestran a cualquier miembro de un grupo. Los de cartera y operaciones son
+// personales y se quedan en los chats privados.
+const GROUP_COMMANDS: &[&str] = &["info", "help", "price", "live", "convert", "stats", "chart", "alerts", "watchlist"];
+// Comandos que además se muestran a los administradores, porque cambian la configuración del grupo
+const ADMIN_COMMANDS: &[&str] = &["alert", "movealert", "unalert", "watch", "digest", "lang"];
+
+// Botón de menú que se configura para los chats privados con el bot
+pub enum MenuButton {
+    Commands,
+    WebApp {
+        text: String,
+        web_app: WebAppInfo,
+    },
+    Default,
+}
+
+impl MenuButton {
+    // MENU_BUTTON=commands (por defecto), default o webapp; con webapp se abre WEBAPP_URL y el botón
+    // lleva el texto de MENU_BUTTON_TEXT (por defecto "Prices")
+    pub fn from_env() -> Result<Self, String> {
+        let kind = std::env::var("MENU_BUTTON").unwrap_or("commands".to_string());
+        match kind.trim().to_lowercase().as_str() {
+            "commands" => Ok(MenuButton::Commands),
+            "default" => Ok(MenuButton::Default),
+            "webapp" => {
+                let url = std::env::var("WEBAPP_URL").map_err(|_| "WEBAPP_URL is required with MENU_BUTTON=webapp".to_string())?;
+                let url = Url::parse(url.trim()).map_err(|err| format!("invalid WEBAPP_URL '{}': {}", url, err))?;
+                if url.scheme() != "https" {
+                    return Err(format!("WEBAPP_URL must use https, got '{}'", url));
+                }
+                Ok(MenuButton::WebApp {
+                    text: std::env::var("MENU_BUTTON_TEXT").unwrap_or("Prices".to_string()),
+                    web_app: WebAppInfo { url },
+                })
+            }
+            other => Err(format!("invalid MENU_BUTTON '{}', expected commands, default or webapp", other)),
+        }
+    }
+}
+
+impl From<MenuButton> for teloxide::types::MenuButton {
+    fn from(button: MenuButton) -> Self {
+        match button {
+            MenuButton::Commands => teloxide::types::MenuButton::Commands,
+            MenuButton::WebApp { text, web_app } => teloxide::types::MenuButton::WebApp { text, web_app },
+            MenuButton::Default => teloxide::types::MenuButton::Default,
+        }
+    }
+}
+
+// Comandos de una lista en un idioma; `names` None incluye todos
+fn commands(locale: Locale, names: Option<&[&str]>) -> Vec<BotCommand> {
+    Command::bot_commands().into_iter()
+        .map(|mut command| {
+            // Telegram quiere el nombre sin la barra
+            command.command = command.command.trim_start_matches('/').to_string();
+            if let Some(description) = i18n::translation(locale, &format!("command.{}", command.command)) {
+                command.description = description.to_string();
+            }
+            command
+        })
+        .filter(|command| names.is_none_or(|names| names.contains(&command.command.as_str())))
+        .collect()
+}
+
+// Texto de /help con las descripciones en el idioma indicado
+pub fn help_text(locale: Locale) -> String {
+    let mut lines = vec![i18n::t(locale, "help.title", &[]), String::new()];
+    lines.extend(commands(locale, None).into_iter()
+        .map(|command| format!("/{} — {}", command.command, command.description)));
+    lines.join("\n")
+}
+
+// Registra las listas de comandos por ámbito e idioma y el botón de menú. Los fallos solo se
+// anotan en el log: el bot funciona igual sin menú. COMMANDS_SETUP=false lo desactiva.
+pub async fn setup(bot: Bot) {
+    if !env_or("COMMANDS_SETUP", true) {
+        return;
+    }
+    let admins: Vec<&str> = GROUP_COMMANDS.iter().chain(ADMIN_COMMANDS).copied().collect();
+    let scopes = [
+        (BotCommandScope::Default, None),
+        (BotCommandScope::AllPrivateChats, None),
+        (BotCommandScope::AllGroupChats, Some(GROUP_COMMANDS)),
+        (BotCommandScope::AllChatAdministrators, Some(admins.as_slice())),
+    ];
+    for (scope, names) in &scopes {
+        // Sin código de idioma es la lista para los idiomas sin una propia, en el idioma por defecto
+        let request = bot.set_my_commands(commands(Locale::from_env(), *names)).scope(scope.clone());
+        if let Err(err) = request.await {
+            log::error!("Could not set the commands of scope {:?}: {}", scope, err);
+        }
+        for locale in Locale::ALL {
+            let request = bot.set_my_commands(commands(locale, *names))
+                .scope(scope.clone())
+                .language_code(locale.code());
+            if let Err(err) = request.await {
+                log::error!("Could not set the {} commands of scope {:?}: {}", locale, scope, err);
+            }
+        }
+    }
+
+    match MenuButton::from_env() {
+        Ok(button) => {
+            if let Err(err) = bot.set_chat_menu_button().menu_button(button.into()).await {
+                log::error!("Could not set the menu button: {}", err);
+            }
+        }
+        Err(err) => log::error!("Invalid menu button configuration: {}", err),
+    }
+}
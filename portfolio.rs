@@ -0,0 +1,320 @@
+// Cartera de cada usuario: "/hold add btc 0.5 @ 42000", "/hold remove btc [cantidad]" y "/portfolio",
+// que responde con la cantidad, el coste, el valor actual, la ganancia o pérdida no realizada y el
+// peso de cada activo. Las posiciones son del usuario, no del chat, así que le siguen entre grupos.
+use std::collections::BTreeMap;
+
+use rust_decimal::prelude::*;
+use teloxide::types::UserId;
+use teloxide::utils::html;
+
+use crate::i18n::{self, Locale};
+use crate::prices::PriceService;
+use crate::providers::Pair;
+use crate::storage::Storage;
+
+// Número máximo de posiciones por usuario, para que la tabla quepa en un mensaje
+const MAX_HOLDINGS: usize = 30;
+
+// Posición en un activo; el coste es el total pagado, en la moneda de cotización del par
+#[derive(Debug, Clone, PartialEq)]
+pub struct Holding {
+    pub pair: Pair,
+    pub quantity: Decimal,
+    pub cost: Decimal,
+}
+
+impl Holding {
+    // Precio medio de compra
+    pub fn average_price(&self) -> Decimal {
+        if self.quantity.is_zero() { Decimal::ZERO } else { self.cost / self.quantity }
+    }
+}
+
+// Lee una cantidad, un precio o una comisión mayor que cero. Las comas se rechazan igual que al
+// importar, porque "1,000" puede ser mil o uno según el idioma del usuario
+pub fn parse_positive(locale: Locale, value: &str, field: &str) -> Result<Decimal, String> {
+    let number = i18n::parse_number(locale, value, field)?;
+    if number.is_zero() {
+        return Err(i18n::field_error(locale, "number.invalid", value, field));
+    }
+    Ok(number)
+}
+
+// Procesa "/hold add <símbolo> <cantidad> [@ <precio>]" y "/hold remove <símbolo> [cantidad]"
+pub async fn hold_command(prices: &PriceService, storage: &dyn Storage, user_id: UserId, locale: Locale, input: &str) -> String {
+    let usage = || i18n::t(locale, "hold.usage", &[]);
+    // "@42000" y "@ 42000" se tratan igual
+    let input = input.replace('@', " @ ");
+    let args: Vec<&str> = input.split_whitespace().collect();
+    let Some((action, args)) = args.split_first() else {
+        return usage();
+    };
+
+    let holdings = match storage.holdings(user_id) {
+        Ok(holdings) => holdings,
+        Err(err) => {
+            log::error!("Could not load holdings of user {}: {}", user_id, err);
+            return i18n::t(locale, "portfolio.load_failed", &[]);
+        }
+    };
+
+    match (action.to_lowercase().as_str(), args) {
+        ("add", [symbol, quantity, rest @ ..]) => {
+            let quantity = match parse_positive(locale, quantity, "quantity") {
+                Ok(quantity) => quantity,
+                Err(err) => return err,
+            };
+            let price = match rest {
+                [] => None,
+                ["@", price] => match parse_positive(locale, price, "price") {
+                    Ok(price) => Some(price),
+                    Err(err) => return err,
+                },
+                _ => return usage(),
+            };
+            let pair = match prices.resolve(symbol).await {
+                Ok(pair) => pair,
+                Err(err) => return i18n::t(locale, "error.resolve", &[("error", &err.user_message(locale))]),
+            };
+            // Sin precio de compra se toma el precio actual
+            let price = match price {
+                Some(price) => price,
+                None => match prices.price(&pair).await {
+                    Ok(quote) => quote.price,
+                    Err(err) => return i18n::t(locale, "error.fetch_price", &[("pair", &pair), ("error", &err.user_message(locale))]),
+                },
+            };
+
+            let holding = match holdings.iter().find(|h| h.pair == pair) {
+                Some(current) => Holding {
+                    pair: pair.clone(),
+                    quantity: current.quantity + quantity,
+                    cost: current.cost + quantity * price,
+                },
+                None if holdings.len() >= MAX_HOLDINGS =>
+                    return i18n::t(locale, "hold.full", &[("max", &MAX_HOLDINGS)]),
+                None => Holding { pair: pair.clone(), quantity, cost: quantity * price },
+            };
+            if let Err(err) = storage.save_holding(user_id, &holding) {
+                log::error!("Could not save holding {} of user {}: {}", pair, user_id, err);
+                return i18n::t(locale, "hold.save_failed", &[]);
+            }
+            i18n::t(locale, "hold.added", &[
+                ("quantity", &i18n::amount(locale, quantity)),
+                ("base", &pair.base),
+                ("price", &i18n::price(locale, price)),
+                ("quote", &pair.quote),
+                ("total", &i18n::amount(locale, holding.quantity)),
+                ("average", &i18n::price(locale, holding.average_price())),
+            ])
+        }
+        ("remove", [symbol, rest @ ..]) => {
+            let Some(current) = find_holding(&holdings, symbol) else {
+                return i18n::t(locale, "hold.not_found", &[("symbol", &symbol.to_uppercase())]);
+            };
+            let quantity = match rest {
+                [] => None,
+                [quantity] => match parse_positive(locale, quantity, "quantity") {
+                    Ok(quantity) => Some(quantity),
+                    Err(err) => return err,
+                },
+                _ => return usage(),
+            };
+
+            // Una venta parcial reduce el coste en proporción, manteniendo el precio medio
+            let result = match quantity {
+                Some(quantity) if quantity < current.quantity => {
+                    let holding = Holding {
+                        pair: current.pair.clone(),
+                        quantity: current.quantity - quantity,
+                        cost: current.cost - current.average_price() * quantity,
+                    };
+                    storage.save_holding(user_id, &holding).map(|_| {
+                        i18n::t(locale, "hold.reduced", &[
+                            ("quantity", &i18n::amount(locale, quantity)),
+                            ("base", &holding.pair.base),
+                            ("total", &i18n::amount(locale, holding.quantity)),
+                        ])
+                    })
+                }
+                _ => storage.delete_holding(user_id, &current.pair)
+                    .map(|_| i18n::t(locale, "hold.removed", &[("base", &current.pair.base)])),
+            };
+            result.unwrap_or_else(|err| {
+                log::error!("Could not update holding {} of user {}: {}", current.pair, user_id, err);
+                i18n::t(locale, "hold.update_failed", &[])
+            })
+        }
+        _ => usage(),
+    }
+}
+
+// Busca una posición por lo que escribe el usuario ("btc", "BTCUSDT", "btc/usdt")
+fn find_holding<'a>(holdings: &'a [Holding], symbol: &str) -> Option<&'a Holding> {
+    let normalized: String = symbol.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_uppercase();
+    holdings.iter().find(|h| format!("{}{}", h.pair.base, h.pair.quote) == normalized)
+        .or_else(|| holdings.iter().find(|h| h.pair.base == normalized))
+}
+
+// Ganancia o pérdida en porcentaje sobre el coste
+fn pnl_percent(locale: Locale, pnl: Decimal, cost: Decimal) -> String {
+    if cost.is_zero() {
+        "-".to_string()
+    } else {
+        format!("{}%", i18n::number(locale, pnl / cost * Decimal::ONE_HUNDRED, 2, true))
+    }
+}
+
+// Tabla de la cartera valorada a precio actual, en HTML para que Telegram use fuente monoespaciada.
+// Los totales y el peso de cada activo se calculan por moneda de cotización, ya que no se pueden sumar
+// importes en monedas distintas.
+pub async fn portfolio_table(prices: &PriceService, holdings: &[Holding], locale: Locale) -> String {
+    if holdings.is_empty() {
+        return i18n::t(locale, "portfolio.empty", &[]);
+    }
+
+    let quotes = futures_util::future::join_all(holdings.iter().map(|h| prices.price(&h.pair))).await;
+    let values: Vec<Option<Decimal>> = holdings.iter().zip(&quotes)
+        .map(|(holding, quote)| match quote {
+            Ok(quote) => Some(quote.price * holding.quantity),
+            Err(err) => {
+                log::warn!("Portfolio could not fetch {}: {}", holding.pair, err);
+                None
+            }
+        })
+        .collect();
+
+    // Valor y coste total por moneda de cotización, solo de los activos con precio
+    let mut totals: BTreeMap<&str, (Decimal, Decimal)> = BTreeMap::new();
+    for (holding, value) in holdings.iter().zip(&values) {
+        if let Some(value) = value {
+            let total = totals.entry(holding.pair.quote.as_str()).or_default();
+            total.0 += *value;
+            total.1 += holding.cost;
+        }
+    }
+
+    let header = |key: &str| i18n::t(locale, key, &[]);
+    let mut rows = vec![format!("{:<6} {:>12} {:>12} {:>12} {:>12} {:>8} {:>6}",
+        header("portfolio.asset"), header("portfolio.quantity"), header("portfolio.cost"), header("portfolio.value"),
+        header("portfolio.pnl"), header("portfolio.pnl_percent"), header("portfolio.allocation"))];
+    for (holding, value) in holdings.iter().zip(&values) {
+        let quantity = i18n::amount(locale, holding.quantity);
+        match value {
+            Some(value) => {
+                let pnl = *value - holding.cost;
+                let total = totals[holding.pair.quote.as_str()].0;
+                let allocation = if total.is_zero() { Decimal::ZERO } else { *value / total * Decimal::ONE_HUNDRED };
+                rows.push(format!("{:<6} {:>12} {:>12} {:>12} {:>12} {:>8} {:>5}%",
+                    holding.pair.base, quantity, i18n::price(locale, holding.cost), i18n::price(locale, *value),
+                    i18n::change(locale, pnl), pnl_percent(locale, pnl, holding.cost), i18n::number(locale, allocation, 1, false)));
+            }
+            None => rows.push(format!("{:<6} {:>12} {:>12} {:>12}",
+                holding.pair.base, quantity, i18n::price(locale, holding.cost), header("table.unavailable"))),
+        }
+    }
+
+    // Se escapa el texto que se inserta (cabeceras como "P&L", activos y cotizaciones) y no las etiquetas;
+    // la tabla se escapa ya alineada para que las entidades no descuadren las columnas
+    let mut lines = vec![format!("<pre>{}</pre>", html::escape(&rows.join("\n")))];
+    for (quote, (value, cost)) in &totals {
+        let pnl = *value - *cost;
+        lines.push(html::escape(&i18n::t(locale, "portfolio.total", &[
+            ("value", &i18n::price(locale, *value)),
+            ("quote", quote),
+            ("cost", &i18n::price(locale, *cost)),
+            ("pnl", &i18n::change(locale, pnl)),
+            ("percent", &pnl_percent(locale, pnl, *cost)),
+        ])));
+    }
+    if values.iter().any(Option::is_none) {
+        lines.push(html::escape(&header("portfolio.partial")));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+
+    use super::*;
+    use crate::cache::PriceCache;
+    use crate::prices::FailoverSettings;
+    use crate::providers::{PriceProvider, ProviderError, ProviderResult, Ticker24h};
+
+    // Fuente que solo cotiza BTC/USDT
+    struct FixedPrices;
+
+    #[async_trait]
+    impl PriceProvider for FixedPrices {
+        fn name(&self) -> &'static str {
+            "Fixed"
+        }
+
+        async fn pairs(&self) -> ProviderResult<Vec<Pair>> {
+            Ok(vec![Pair::new("BTC", "USDT")])
+        }
+
+        async fn price(&self, pair: &Pair) -> ProviderResult<Decimal> {
+            match pair.base.as_str() {
+                "BTC" => Ok(Decimal::from(50_000)),
+                _ => Err(ProviderError::InvalidSymbol(pair.to_string())),
+            }
+        }
+
+        async fn ticker_24h(&self, pair: &Pair) -> ProviderResult<Ticker24h> {
+            Err(ProviderError::InvalidSymbol(pair.to_string()))
+        }
+    }
+
+    fn unescape(text: &str) -> String {
+        text.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&amp;", "&")
+    }
+
+    #[tokio::test]
+    async fn only_the_inserted_text_is_escaped() {
+        let settings = FailoverSettings { timeout: Duration::from_secs(1), failure_threshold: 1, cooldown: Duration::from_secs(1) };
+        let prices = PriceService::new(vec![Arc::new(FixedPrices)], settings, "USDT", Decimal::TWO, PriceCache::new(Duration::ZERO));
+        let holdings = [
+            Holding { pair: Pair::new("BTC", "USDT"), quantity: Decimal::new(5, 1), cost: Decimal::from(20_000) },
+            Holding { pair: Pair::new("<B&>", "USDT"), quantity: Decimal::ONE, cost: Decimal::ONE },
+        ];
+        let text = portfolio_table(&prices, &holdings, Locale::En).await;
+
+        // Las etiquetas quedan intactas y el texto insertado se escapa
+        let (table, rest) = text.strip_prefix("<pre>").and_then(|t| t.split_once("</pre>")).expect("missing <pre> block");
+        assert!(!text.contains("P&L") && !text.contains("<B&>"), "{}", text);
+        assert!(table.contains("P&amp;L") && table.contains("&lt;B&amp;&gt;"), "{}", table);
+        assert!(rest.contains("P&amp;L +5,000.00 USDT"), "{}", rest);
+
+        // Las columnas se alinean con el texto ya sin escapar
+        let table = unescape(table);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[0].chars().count(), lines[1].chars().count(), "{}", table);
+        assert!(lines[2].starts_with("<B&> "), "{}", table);
+    }
+
+    #[test]
+    fn quantities_with_commas_are_rejected_instead_of_misread() {
+        // Antes "1,000" se leía como 1 y "0,5" como 0.5
+        assert_eq!(parse_positive(Locale::En, "1,000", "quantity").unwrap_err(),
+            i18n::field_error(Locale::En, "number.decimal_comma", "1,000", "quantity"));
+        assert_eq!(parse_positive(Locale::Es, "0,5", "price").unwrap_err(),
+            i18n::field_error(Locale::Es, "number.decimal_comma", "0,5", "price"));
+        assert_eq!(parse_positive(Locale::En, "0.5", "quantity"), Ok(Decimal::new(5, 1)));
+        assert_eq!(parse_positive(Locale::Es, "1000", "quantity"), Ok(Decimal::from(1000)));
+    }
+
+    #[test]
+    fn zero_and_negative_values_are_rejected() {
+        for value in ["0", "0.00", "-1", "abc", ""] {
+            assert!(parse_positive(Locale::En, value, "fee").is_err(), "{}", value);
+        }
+    }
+}
@@ -0,0 +1,608 @@
+// Servicio de precios: resuelve lo que escribe el usuario a un par válido y consulta los proveedores
+// configurados en orden, saltando a la siguiente fuente cuando una falla (failover).
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use rust_decimal::prelude::*;
+use tokio::sync::RwLock;
+
+use crate::aggregate::{self, Aggregate};
+use crate::cache::{CacheStats, Lookup, PriceCache};
+use crate::config::env_or;
+use crate::market::{self, PriceBook};
+use crate::providers::{Pair, PriceProvider, ProviderError, ProviderResult, Ticker24h};
+
+// La lista de pares apenas cambia, así que se guarda durante una hora
+const PAIRS_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+// Monedas de cotización preferidas (después de la cotización por defecto) al ordenar resultados de búsqueda
+const POPULAR_QUOTES: [&str; 6] = ["USDT", "USDC", "USD", "EUR", "BTC", "ETH"];
+
+// Caché de la lista de pares junto con el momento en que se descargó
+struct PairsCache {
+    fetched_at: Instant,
+    pairs: Arc<Vec<Pair>>,
+}
+
+// Parámetros de la cadena de failover
+#[derive(Debug, Clone)]
+pub struct FailoverSettings {
+    // Tiempo máximo que se espera a cada fuente
+    pub timeout: Duration,
+    // Fallos consecutivos tras los que se abre el circuito de una fuente
+    pub failure_threshold: u32,
+    // Tiempo durante el que se salta una fuente con el circuito abierto
+    pub cooldown: Duration,
+}
+
+impl FailoverSettings {
+    // PROVIDER_TIMEOUT_SECS, PROVIDER_FAILURE_THRESHOLD y PROVIDER_COOLDOWN_SECS
+    pub fn from_env() -> Self {
+        FailoverSettings {
+            timeout: Duration::from_secs(env_or("PROVIDER_TIMEOUT_SECS", 5)),
+            failure_threshold: env_or("PROVIDER_FAILURE_THRESHOLD", 3).max(1),
+            cooldown: Duration::from_secs(env_or("PROVIDER_COOLDOWN_SECS", 60)),
+        }
+    }
+}
+
+// Estado del circuit breaker de una fuente
+#[derive(Default)]
+struct Breaker {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+}
+
+// Un proveedor dentro de la cadena junto con su circuit breaker
+struct Source {
+    provider: Arc<dyn PriceProvider>,
+    breaker: Mutex<Breaker>,
+}
+
+impl Source {
+    // El circuito está abierto mientras no haya pasado el tiempo de enfriamiento
+    fn is_open(&self) -> bool {
+        let breaker = self.breaker.lock().unwrap();
+        breaker.open_until.is_some_and(|until| Instant::now() < until)
+    }
+
+    fn record_success(&self) {
+        *self.breaker.lock().unwrap() = Breaker::default();
+    }
+
+    fn record_failure(&self, settings: &FailoverSettings) {
+        let mut breaker = self.breaker.lock().unwrap();
+        breaker.consecutive_failures += 1;
+        // Al superar el umbral se abre el circuito; si falla de nuevo tras el enfriamiento se vuelve a abrir
+        if breaker.consecutive_failures >= settings.failure_threshold {
+            if breaker.open_until.is_none_or(|until| Instant::now() >= until) {
+                log::warn!("Price source {} disabled for {:?} after {} consecutive failures",
+                    self.provider.name(), settings.cooldown, breaker.consecutive_failures);
+            }
+            breaker.open_until = Some(Instant::now() + settings.cooldown);
+        }
+    }
+}
+
+// Error de una fuente concreta, para los mensajes que detallan qué fuentes no respondieron
+pub type SourceError = (&'static str, ProviderError);
+
+// Precio obtenido junto con la fuente que lo proporcionó
+#[derive(Debug, Clone)]
+pub struct Quote {
+    pub pair: Pair,
+    pub price: Decimal,
+    pub source: &'static str,
+}
+
+// Estadísticas de 24 horas junto con la fuente que las proporcionó
+#[derive(Debug, Clone)]
+pub struct DailyQuote {
+    pub pair: Pair,
+    pub ticker: Ticker24h,
+    pub source: &'static str,
+}
+
+pub struct PriceService {
+    sources: Vec<Source>,
+    settings: FailoverSettings,
+    default_quote: String,
+    // Desviación máxima (en %) respecto a la mediana antes de descartar una fuente en el modo agregado
+    max_deviation_pct: Decimal,
+    pairs: RwLock<Option<PairsCache>>,
+    // Últimos precios por fuente, compartidos entre comandos, botones y alertas
+    cache: PriceCache,
+    // Precios en tiempo real del stream de Binance, si está activado
+    book: Option<Arc<PriceBook>>,
+}
+
+impl PriceService {
+    pub fn new(providers: Vec<Arc<dyn PriceProvider>>, settings: FailoverSettings, default_quote: &str, max_deviation_pct: Decimal, cache: PriceCache) -> Self {
+        PriceService {
+            sources: providers.into_iter()
+                .map(|provider| Source { provider, breaker: Mutex::new(Breaker::default()) })
+                .collect(),
+            settings,
+            default_quote: default_quote.to_uppercase(),
+            max_deviation_pct,
+            pairs: RwLock::new(None),
+            cache,
+            book: None,
+        }
+    }
+
+    // Sirve los precios desde el libro del stream mientras estén frescos
+    pub fn with_book(mut self, book: Arc<PriceBook>) -> Self {
+        self.book = Some(book);
+        self
+    }
+
+    // Moneda de cotización que se usa cuando el usuario solo indica el activo base (DEFAULT_QUOTE_ASSET, por defecto USDT)
+    // y desviación máxima del modo agregado (AGGREGATE_MAX_DEVIATION_PCT, por defecto 2%)
+    pub fn from_env(providers: Vec<Arc<dyn PriceProvider>>) -> Self {
+        Self::new(providers, FailoverSettings::from_env(),
+            &std::env::var("DEFAULT_QUOTE_ASSET").unwrap_or("USDT".to_string()),
+            env_or("AGGREGATE_MAX_DEVIATION_PCT", Decimal::TWO),
+            PriceCache::from_env())
+    }
+
+    // Nombres de las fuentes en orden de preferencia, p. ej. "Binance → Kraken"
+    pub fn source_names(&self) -> String {
+        self.sources.iter()
+            .map(|s| s.provider.name())
+            .collect::<Vec<_>>()
+            .join(" → ")
+    }
+
+    // Contadores de aciertos y fallos de la caché de precios
+    pub fn cache_stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
+
+    // Ejecuta una petición contra cada fuente en orden hasta que una responda, respetando el
+    // tiempo máximo por fuente y saltando las que tienen el circuito abierto.
+    // Un símbolo desconocido no cuenta como fallo de la fuente, pero sí se prueba con la siguiente.
+    async fn first_available<'s, T, F>(&'s self, what: &str, request: F) -> ProviderResult<(T, &'static str)>
+    where
+        F: Fn(&'s Source) -> futures_util::future::BoxFuture<'s, ProviderResult<T>>,
+    {
+        let mut errors = Vec::new();
+        for source in &self.sources {
+            let name = source.provider.name();
+            if source.is_open() {
+                errors.push((name, ProviderError::Disabled));
+                continue;
+            }
+            match request(source).await {
+                Ok(value) => return Ok((value, name)),
+                Err(err) => {
+                    log::warn!("{} failed on {}: {}", what, name, err);
+                    errors.push((name, err));
+                }
+            }
+        }
+        Err(ProviderError::Unavailable(errors))
+    }
+
+    // Espera la respuesta de una fuente con su tiempo máximo y actualiza su circuit breaker
+    async fn request_source<T>(&self, source: &Source, request: impl std::future::Future<Output = ProviderResult<T>>) -> ProviderResult<T> {
+        let result = with_timeout(self.settings.timeout, request).await;
+        self.record_result(source, &result);
+        result
+    }
+
+    fn record_result<T>(&self, source: &Source, result: &ProviderResult<T>) {
+        match result {
+            Err(err) if err.is_source_failure() => source.record_failure(&self.settings),
+            _ => source.record_success(),
+        }
+    }
+
+    // Precio de un par en una fuente pasando por la caché. Solo la llamada que lanza la consulta
+    // actualiza el circuit breaker, para que un fallo no cuente una vez por cada petición agrupada.
+    async fn source_price(&self, source: &Source, pair: &Pair) -> ProviderResult<Decimal> {
+        let provider = source.provider.clone();
+        let request_pair = pair.clone();
+        let timeout = self.settings.timeout;
+        let (result, lookup) = self.cache.get_or_fetch(source.provider.name(), pair, move || {
+            Box::pin(async move { with_timeout(timeout, provider.price(&request_pair)).await })
+        }).await;
+        if lookup == Lookup::Miss {
+            self.record_result(source, &result);
+        }
+        result
+    }
+
+    // Devuelve la lista de pares de la primera fuente disponible, descargándola de nuevo cuando la caché ha caducado
+    async fn pairs(&self) -> ProviderResult<Arc<Vec<Pair>>> {
+        if let Some(cache) = self.pairs.read().await.as_ref() {
+            if cache.fetched_at.elapsed() < PAIRS_CACHE_TTL {
+                return Ok(cache.pairs.clone());
+            }
+        }
+        let (pairs, _) = self.first_available("Pair list", |source| {
+            Box::pin(self.request_source(source, source.provider.pairs()))
+        }).await?;
+        let pairs = Arc::new(pairs);
+        *self.pairs.write().await = Some(PairsCache { fetched_at: Instant::now(), pairs: pairs.clone() });
+        Ok(pairs)
+    }
+
+    // Indica si algún proveedor cotiza el par exacto
+    pub async fn has_pair(&self, pair: &Pair) -> ProviderResult<bool> {
+        Ok(self.pairs().await?.contains(pair))
+    }
+
+    // Convierte lo que escribe el usuario ("ethusdt", "ETH/USDT", "eth-usdt", "eth") en un par válido.
+    // Si no coincide con ningún par, se interpreta como activo base y se completa con la cotización por defecto.
+    pub async fn resolve(&self, input: &str) -> ProviderResult<Pair> {
+        let pairs = self.pairs().await?;
+        let input = input.trim();
+
+        // Con separador explícito no hay ambigüedad entre base y cotización
+        if let Some((base, quote)) = input.split_once(['/', '-', '_']) {
+            let pair = Pair::new(base.trim(), quote.trim());
+            return if pairs.contains(&pair) {
+                Ok(pair)
+            } else {
+                Err(ProviderError::InvalidSymbol(input.to_string()))
+            };
+        }
+
+        let candidate = normalize_symbol(input);
+        if candidate.is_empty() {
+            return Err(ProviderError::InvalidSymbol(input.to_string()));
+        }
+
+        if let Some(pair) = pairs.iter().find(|p| p.base.len() + p.quote.len() == candidate.len()
+            && candidate.starts_with(&p.base) && candidate.ends_with(&p.quote)) {
+            return Ok(pair.clone());
+        }
+
+        let pair = Pair::new(&candidate, &self.default_quote);
+        if pairs.contains(&pair) {
+            Ok(pair)
+        } else {
+            Err(ProviderError::InvalidSymbol(input.to_string()))
+        }
+    }
+
+    // Búsqueda aproximada para el modo inline: devuelve hasta `limit` pares parecidos a lo escrito,
+    // primero las coincidencias exactas, luego los prefijos ("eth" -> ETH/USDT, ETH/BTC...), los que
+    // contienen el texto y por último los que están a una o dos letras de distancia ("etj" -> ETH).
+    pub async fn search(&self, input: &str, limit: usize) -> ProviderResult<Vec<Pair>> {
+        let pairs = self.pairs().await?;
+        let (base, quote) = match input.trim().split_once(['/', '-', '_', ' ']) {
+            Some((base, quote)) => (normalize_symbol(base), Some(normalize_symbol(quote)).filter(|q| !q.is_empty())),
+            None => (normalize_symbol(input), None),
+        };
+        if base.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut matches: Vec<_> = pairs.iter()
+            .filter_map(|pair| {
+                let score = match_score(pair, &base, quote.as_deref())?;
+                Some(((score, self.quote_rank(&pair.quote), pair.base.len()), pair))
+            })
+            .collect();
+        matches.sort_by(|(a, pa), (b, pb)| a.cmp(b).then_with(|| pa.cmp(pb)));
+        Ok(matches.into_iter().take(limit).map(|(_, pair)| pair.clone()).collect())
+    }
+
+    // Orden de las monedas de cotización cuando varios pares se parecen igual a la búsqueda
+    fn quote_rank(&self, quote: &str) -> usize {
+        if quote == self.default_quote {
+            return 0;
+        }
+        POPULAR_QUOTES.iter().position(|q| *q == quote).map_or(POPULAR_QUOTES.len() + 1, |i| i + 1)
+    }
+
+    // Consulta el precio de un par en todas las fuentes a la vez (las que tienen el circuito abierto se omiten).
+    // Devuelve los precios obtenidos y los errores de las fuentes que fallaron.
+    pub async fn price_from_all(&self, pair: &Pair) -> (Vec<Quote>, Vec<SourceError>) {
+        let requests = self.sources.iter().map(|source| async move {
+            let name = source.provider.name();
+            if source.is_open() {
+                return Err((name, ProviderError::Disabled));
+            }
+            match self.source_price(source, pair).await {
+                Ok(price) => Ok(Quote { pair: pair.clone(), price, source: name }),
+                Err(err) => Err((name, err)),
+            }
+        });
+
+        let mut quotes = Vec::new();
+        let mut errors = Vec::new();
+        for result in futures_util::future::join_all(requests).await {
+            match result {
+                Ok(quote) => quotes.push(quote),
+                Err(err) => errors.push(err),
+            }
+        }
+        (quotes, errors)
+    }
+
+    // Mediana del precio de un par entre todas las fuentes, junto con los errores de las que no respondieron
+    pub async fn median_price(&self, pair: &Pair) -> ProviderResult<(Aggregate, Vec<SourceError>)> {
+        let (quotes, errors) = self.price_from_all(pair).await;
+        match aggregate::aggregate(&quotes, self.max_deviation_pct) {
+            Some(result) => Ok((result, errors)),
+            None => Err(ProviderError::Unavailable(errors)),
+        }
+    }
+
+    // Último precio de un par: del stream si hay un precio fresco, o de la primera fuente que responda
+    pub async fn price(&self, pair: &Pair) -> ProviderResult<Quote> {
+        if let Some(book) = &self.book {
+            book.track(pair);
+            if let Some(price) = book.fresh(pair) {
+                return Ok(Quote { pair: pair.clone(), price, source: market::SOURCE_NAME });
+            }
+        }
+        let (price, source) = self.first_available("Price request", |source| {
+            let pair = pair.clone();
+            Box::pin(async move { self.source_price(source, &pair).await })
+        }).await?;
+        Ok(Quote { pair: pair.clone(), price, source })
+    }
+
+    // Último precio y variación de 24 horas según la primera fuente que responda
+    pub async fn ticker_24h(&self, pair: &Pair) -> ProviderResult<DailyQuote> {
+        let (ticker, source) = self.first_available("24h ticker request", |source| {
+            let pair = pair.clone();
+            Box::pin(async move { self.request_source(source, source.provider.ticker_24h(&pair)).await })
+        }).await?;
+        Ok(DailyQuote { pair: pair.clone(), ticker, source })
+    }
+}
+
+// Deja solo letras y números en mayúsculas: "eth-usdt " -> "ETHUSDT"
+fn normalize_symbol(input: &str) -> String {
+    input.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_uppercase()
+}
+
+// Parecido de un par con la búsqueda (menor es mejor) o None si no se parece. Sin cotización
+// explícita el texto se compara también con el símbolo completo ("ethu" -> ETHUSDT).
+fn match_score(pair: &Pair, base: &str, quote: Option<&str>) -> Option<u8> {
+    match quote {
+        Some(quote) if !pair.quote.starts_with(quote) => return None,
+        Some(quote) if pair.base == base && pair.quote == quote => return Some(0),
+        Some(_) => {}
+        None if pair.base.len() + pair.quote.len() == base.len()
+            && base.starts_with(&pair.base) && base.ends_with(&pair.quote) => return Some(0),
+        None if base.starts_with(&pair.base) && pair.quote.starts_with(&base[pair.base.len()..]) && pair.base != base => return Some(2),
+        None => {}
+    }
+    if pair.base == base {
+        Some(1)
+    } else if pair.base.starts_with(base) {
+        Some(3)
+    } else if base.len() >= 2 && pair.base.contains(base) {
+        Some(4)
+    } else if base.len() >= 3 && edit_distance(&pair.base, base) <= if base.len() >= 6 { 2 } else { 1 } {
+        Some(5)
+    } else {
+        None
+    }
+}
+
+// Distancia de Levenshtein entre dos símbolos (solo ASCII)
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+// Espera una petición como mucho `timeout`, convirtiendo la espera agotada en ProviderError::Timeout
+async fn with_timeout<T>(timeout: Duration, request: impl std::future::Future<Output = ProviderResult<T>>) -> ProviderResult<T> {
+    tokio::time::timeout(timeout, request).await
+        .unwrap_or(Err(ProviderError::Timeout(timeout)))
+}
+
+// Igual que format_price pero con signo explícito, para variaciones
+pub fn format_change(change: Decimal) -> String {
+    if change.is_sign_positive() && !change.is_zero() {
+        format!("+{}", format_price(change))
+    } else {
+        format_price(change)
+    }
+}
+
+// Formatea un precio para mostrarlo: dos decimales para precios normales y más precisión
+// para monedas que valen menos de una unidad (p. ej. SHIB), que si no se verían como 0.00
+pub fn format_price(price: Decimal) -> String {
+    let decimals = if price.abs() >= Decimal::ONE { 2 } else { 8 };
+    let rounded = price.round_dp(decimals);
+    if decimals == 2 {
+        format!("{:.2}", rounded)
+    } else {
+        rounded.normalize().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use axum::{Json, Router};
+    use serde_json::json;
+
+    use super::*;
+    use crate::providers::{stub_server, BinanceProvider, KrakenProvider, Ticker24h};
+
+    // Binance falla siempre (y cuenta las peticiones que recibe) y Kraken responde
+    async fn failover_service(binance_hits: Arc<AtomicUsize>) -> PriceService {
+        let binance = Router::new().route("/api/v3/ticker/24hr", get(move || async move {
+            binance_hits.fetch_add(1, Ordering::SeqCst);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "code": -1000, "msg": "An unknown error occurred." })))
+        }));
+        let kraken = Router::new()
+            .route("/0/public/AssetPairs", get(|| async {
+                Json(json!({ "error": [], "result": { "XXBTZUSD": { "altname": "XBTUSD", "wsname": "XBT/USD" } } }))
+            }))
+            .route("/0/public/Ticker", get(|| async {
+                Json(json!({ "error": [], "result": { "XXBTZUSD": {
+                    "c": ["67000.1", "0.01"], "o": "66000", "v": ["10", "100"],
+                    "p": ["66500", "66600"], "h": ["67500", "68000"], "l": ["65500", "65000"],
+                }}}))
+            }));
+        let providers: Vec<Arc<dyn PriceProvider>> = vec![
+            Arc::new(BinanceProvider::new(stub_server(binance).await)),
+            Arc::new(KrakenProvider::new(stub_server(kraken).await)),
+        ];
+        let settings = FailoverSettings { timeout: Duration::from_secs(5), failure_threshold: 2, cooldown: Duration::from_secs(60) };
+        PriceService::new(providers, settings, "USD", Decimal::TWO, PriceCache::new(Duration::ZERO))
+    }
+
+    #[tokio::test]
+    async fn fails_over_and_opens_the_circuit_of_a_failing_source() {
+        let binance_hits = Arc::new(AtomicUsize::new(0));
+        let prices = failover_service(binance_hits.clone()).await;
+        let btc = Pair::new("BTC", "USD");
+
+        // Mientras no se alcanza el umbral se sigue probando Binance antes de pasar a Kraken
+        for attempt in 1..=2 {
+            let quote = prices.ticker_24h(&btc).await.unwrap();
+            assert_eq!((quote.source, quote.ticker.last), ("Kraken", Decimal::new(670001, 1)));
+            assert_eq!(binance_hits.load(Ordering::SeqCst), attempt);
+        }
+
+        // Con el circuito abierto Binance se salta sin llegar a pedírselo
+        let quote = prices.ticker_24h(&btc).await.unwrap();
+        assert_eq!(quote.source, "Kraken");
+        assert_eq!(binance_hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn reports_every_source_error_when_none_responds() {
+        let prices = failover_service(Arc::new(AtomicUsize::new(0))).await;
+        match prices.ticker_24h(&Pair::new("ETH", "USD")).await {
+            Err(ProviderError::Unavailable(errors)) => {
+                assert!(matches!(errors.as_slice(), [
+                    ("Binance", ProviderError::Api { status: 500, .. }),
+                    ("Kraken", ProviderError::InvalidSymbol(_)),
+                ]), "{:?}", errors);
+            }
+            other => panic!("unexpected result {:?}", other.map(|quote| quote.source)),
+        }
+    }
+
+    #[tokio::test]
+    async fn median_price_fails_with_every_source_error_when_all_sources_fail() {
+        let prices = failover_service(Arc::new(AtomicUsize::new(0))).await;
+        match prices.median_price(&Pair::new("ETH", "USD")).await {
+            Err(ProviderError::Unavailable(errors)) => {
+                assert_eq!(errors.iter().map(|(source, _)| *source).collect::<Vec<_>>(), vec!["Binance", "Kraken"]);
+            }
+            other => panic!("unexpected result {:?}", other.map(|(result, _)| result.median)),
+        }
+
+        // Si alguna responde, la mediana sale solo de las que respondieron y se informa del resto
+        let (result, errors) = prices.median_price(&Pair::new("BTC", "USD")).await.unwrap();
+        assert_eq!((result.median, result.used.len()), (Decimal::new(670001, 1), 1));
+        assert_eq!(errors.len(), 1);
+    }
+
+    // Fuente que solo sirve la lista de pares, para las búsquedas
+    struct ListedPairs(Vec<Pair>);
+
+    #[async_trait::async_trait]
+    impl PriceProvider for ListedPairs {
+        fn name(&self) -> &'static str {
+            "Listed"
+        }
+
+        async fn pairs(&self) -> ProviderResult<Vec<Pair>> {
+            Ok(self.0.clone())
+        }
+
+        async fn price(&self, pair: &Pair) -> ProviderResult<Decimal> {
+            Err(ProviderError::InvalidSymbol(pair.to_string()))
+        }
+
+        async fn ticker_24h(&self, pair: &Pair) -> ProviderResult<Ticker24h> {
+            Err(ProviderError::InvalidSymbol(pair.to_string()))
+        }
+    }
+
+    fn search_service() -> PriceService {
+        let pairs = ["BTC/USDT", "BTC/EUR", "BTCST/USDT", "WBTC/USDT", "ETH/BTC", "ETH/USDT", "ETHFI/USDT", "SETH/USDT",
+            "ETC/USDT", "1000SATS/USDT"];
+        let listed = ListedPairs(pairs.iter().map(|p| p.split_once('/').map(|(base, quote)| Pair::new(base, quote)).unwrap()).collect());
+        let settings = FailoverSettings { timeout: Duration::from_secs(5), failure_threshold: 2, cooldown: Duration::from_secs(60) };
+        PriceService::new(vec![Arc::new(listed)], settings, "USDT", Decimal::TWO, PriceCache::new(Duration::ZERO))
+    }
+
+    async fn search(prices: &PriceService, input: &str, limit: usize) -> Vec<String> {
+        prices.search(input, limit).await.unwrap().iter().map(|pair| pair.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn search_ranks_exact_then_prefix_then_contains_then_typos() {
+        let prices = search_service();
+        // Entre coincidencias iguales va primero la cotización por defecto
+        assert_eq!(search(&prices, "eth", 10).await, ["ETH/USDT", "ETH/BTC", "ETHFI/USDT", "SETH/USDT", "ETC/USDT"]);
+        assert_eq!(search(&prices, "btc", 10).await, ["BTC/USDT", "BTC/EUR", "BTCST/USDT", "WBTC/USDT", "ETC/USDT"]);
+        // Con el símbolo completo o la cotización explícita el par exacto va delante
+        assert_eq!(search(&prices, "ethbtc", 10).await, ["ETH/BTC"]);
+        assert_eq!(search(&prices, "btc/eu", 10).await, ["BTC/EUR"]);
+        assert_eq!(search(&prices, "eth usdt", 10).await, ["ETH/USDT", "ETHFI/USDT", "SETH/USDT", "ETC/USDT"]);
+    }
+
+    #[tokio::test]
+    async fn search_tolerates_typos_by_symbol_length() {
+        let prices = search_service();
+        assert_eq!(search(&prices, "btcc", 10).await, ["BTC/USDT", "BTC/EUR"]);
+        assert_eq!(search(&prices, "etj", 10).await, ["ETC/USDT", "ETH/USDT", "ETH/BTC"]);
+        // Los símbolos largos admiten dos errores, los cortos solo uno
+        assert_eq!(search(&prices, "1000sast", 10).await, ["1000SATS/USDT"]);
+        // Se busca por símbolo, no por nombre: "etereum" está a cinco letras de ETH
+        assert!(search(&prices, "etereum", 10).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn search_respects_the_limit_and_ignores_empty_queries() {
+        let prices = search_service();
+        assert_eq!(search(&prices, "eth", 2).await, ["ETH/USDT", "ETH/BTC"]);
+        assert_eq!(search(&prices, "e", 3).await.len(), 3);
+        for input in ["", "   ", "/", "-usdt"] {
+            assert!(search(&prices, input, 10).await.is_empty(), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn edit_distance_counts_insertions_deletions_and_substitutions() {
+        assert_eq!(edit_distance("ETH", "ETH"), 0);
+        assert_eq!(edit_distance("ETH", "ETC"), 1);
+        assert_eq!(edit_distance("BTC", "BTCC"), 1);
+        assert_eq!(edit_distance("ETHEREUM", "ETEREUM"), 1);
+        assert_eq!(edit_distance("ETH", "ETEREUM"), 5);
+        assert_eq!(edit_distance("", "ABC"), 3);
+    }
+
+    #[test]
+    fn match_score_orders_exact_prefix_and_typo_matches() {
+        let btc = Pair::new("BTC", "USDT");
+        assert_eq!(match_score(&btc, "BTCUSDT", None), Some(0));
+        assert_eq!(match_score(&btc, "BTC", None), Some(1));
+        assert_eq!(match_score(&btc, "BTCU", None), Some(2));
+        assert_eq!(match_score(&btc, "BT", None), Some(3));
+        assert_eq!(match_score(&btc, "BTX", None), Some(5));
+        assert_eq!(match_score(&btc, "BT", Some("EUR")), None);
+        assert_eq!(match_score(&btc, "XYZ", None), None);
+    }
+}
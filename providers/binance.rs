@@ -0,0 +1,223 @@
+// Proveedor de precios basado en el API REST de Binance
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+use super::{http_client, parse_decimal, read_json, Pair, PriceProvider, ProviderError, ProviderResult, Ticker24h};
+
+pub struct BinanceProvider {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+// Respuesta de /api/v3/ticker/price
+#[derive(Deserialize, Debug)]
+struct PriceResponse {
+    price: String, // Binance envía el precio como String
+}
+
+// Respuesta de /api/v3/ticker/24hr; Binance envía todos los importes como String
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Ticker24hResponse {
+    price_change: String,
+    price_change_percent: String,
+    weighted_avg_price: String,
+    open_price: String,
+    high_price: String,
+    low_price: String,
+    last_price: String,
+    volume: String,
+    quote_volume: String,
+}
+
+// Elemento de /api/v3/klines: [apertura (ms), open, high, low, close, volumen, cierre (ms),
+// volumen de cotización, nº de operaciones, volumen comprador base, volumen comprador cotización, ignorado]
+type KlineRow = (i64, String, String, String, String, String, i64, String, u64, String, String, String);
+
+// Vela de un gráfico de precios
+#[derive(Debug, Clone)]
+pub struct Candle {
+    pub open_time: DateTime<Utc>,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    pub volume: Decimal,
+}
+
+// Respuesta de /api/v3/exchangeInfo. Solo nos interesa la lista de símbolos.
+#[derive(Deserialize, Debug)]
+struct ExchangeInfoResponse {
+    symbols: Vec<SymbolInfo>,
+}
+
+// Datos de un par de trading tal como los devuelve Binance (p. ej. ETHUSDT = ETH + USDT)
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SymbolInfo {
+    status: String,
+    base_asset: String,
+    quote_asset: String,
+}
+
+impl BinanceProvider {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        BinanceProvider { client: http_client(), base_url: base_url.into() }
+    }
+
+    // La URL base se puede cambiar con BINANCE_API_URL (p. ej. para un mirror o un servidor de pruebas)
+    pub fn from_env() -> Self {
+        Self::new(std::env::var("BINANCE_API_URL").unwrap_or("https://api.binance.com".to_string()))
+    }
+
+    // Símbolo en formato Binance: BTC/USDT -> BTCUSDT
+    fn symbol(pair: &Pair) -> String {
+        format!("{}{}", pair.base, pair.quote)
+    }
+
+    // Últimas `limit` velas de un par con el intervalo indicado (1m, 1h, 1d...)
+    pub async fn klines(&self, pair: &Pair, interval: &str, limit: u32) -> ProviderResult<Vec<Candle>> {
+        let url = format!("{}/api/v3/klines", self.base_url);
+        let rows: Vec<KlineRow> = read_json(self.client.get(url)
+            .query(&[("symbol", Self::symbol(pair)), ("interval", interval.to_string()), ("limit", limit.to_string())])
+            .send().await?).await
+            .map_err(|err| err.or_invalid_symbol(pair))?;
+        rows.into_iter()
+            .map(|(open_time, open, high, low, close, volume, ..)| Ok(Candle {
+                open_time: DateTime::from_timestamp_millis(open_time)
+                    .ok_or_else(|| ProviderError::InvalidResponse(format!("invalid kline open time {}", open_time)))?,
+                open: parse_decimal(&open)?,
+                high: parse_decimal(&high)?,
+                low: parse_decimal(&low)?,
+                close: parse_decimal(&close)?,
+                volume: parse_decimal(&volume)?,
+            }))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl PriceProvider for BinanceProvider {
+    fn name(&self) -> &'static str {
+        "Binance"
+    }
+
+    async fn pairs(&self) -> ProviderResult<Vec<Pair>> {
+        let url = format!("{}/api/v3/exchangeInfo", self.base_url);
+        let body: ExchangeInfoResponse = read_json(self.client.get(url).send().await?).await?;
+        // Solo interesan los pares que se pueden operar en este momento
+        Ok(body.symbols.into_iter()
+            .filter(|s| s.status == "TRADING")
+            .map(|s| Pair::new(&s.base_asset, &s.quote_asset))
+            .collect())
+    }
+
+    async fn price(&self, pair: &Pair) -> ProviderResult<Decimal> {
+        let url = format!("{}/api/v3/ticker/price", self.base_url);
+        let body: PriceResponse = read_json(self.client.get(url)
+            .query(&[("symbol", Self::symbol(pair))])
+            .send().await?).await
+            .map_err(|err| err.or_invalid_symbol(pair))?;
+        parse_decimal(&body.price)
+    }
+
+    async fn ticker_24h(&self, pair: &Pair) -> ProviderResult<Ticker24h> {
+        let url = format!("{}/api/v3/ticker/24hr", self.base_url);
+        let body: Ticker24hResponse = read_json(self.client.get(url)
+            .query(&[("symbol", Self::symbol(pair))])
+            .send().await?).await
+            .map_err(|err| err.or_invalid_symbol(pair))?;
+        Ok(Ticker24h {
+            open: parse_decimal(&body.open_price)?,
+            high: parse_decimal(&body.high_price)?,
+            low: parse_decimal(&body.low_price)?,
+            last: parse_decimal(&body.last_price)?,
+            change: parse_decimal(&body.price_change)?,
+            change_percent: parse_decimal(&body.price_change_percent)?,
+            weighted_avg: Some(parse_decimal(&body.weighted_avg_price)?),
+            volume: Some(parse_decimal(&body.volume)?),
+            quote_volume: Some(parse_decimal(&body.quote_volume)?),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    use axum::extract::Query;
+    use axum::http::{header, StatusCode};
+    use axum::response::IntoResponse;
+    use axum::routing::get;
+    use axum::{Json, Router};
+    use serde_json::json;
+
+    use super::*;
+    use crate::providers::stub_server;
+
+    // Responde como Binance: BTCUSDT existe, RATEUSDT está limitado y el resto no existe
+    async fn ticker_price(Query(query): Query<HashMap<String, String>>) -> axum::response::Response {
+        match query.get("symbol").map(String::as_str) {
+            Some("BTCUSDT") => Json(json!({ "symbol": "BTCUSDT", "price": "67187.33000000" })).into_response(),
+            Some("RATEUSDT") => (StatusCode::TOO_MANY_REQUESTS, [(header::RETRY_AFTER, "7")], "").into_response(),
+            _ => (StatusCode::BAD_REQUEST, Json(json!({ "code": -1121, "msg": "Invalid symbol." }))).into_response(),
+        }
+    }
+
+    async fn provider() -> BinanceProvider {
+        let router = Router::new()
+            .route("/api/v3/ticker/price", get(ticker_price))
+            .route("/api/v3/ticker/24hr", get(|| async {
+                Json(json!({
+                    "symbol": "BTCUSDT", "priceChange": "1200.50000000", "priceChangePercent": "1.820",
+                    "weightedAvgPrice": "66800.10000000", "openPrice": "65986.83000000", "highPrice": "67500.00000000",
+                    "lowPrice": "65800.00000000", "lastPrice": "67187.33000000", "volume": "12345.67800000",
+                    "quoteVolume": "824690000.12000000",
+                }))
+            }))
+            .route("/api/v3/klines", get(|| async {
+                Json(json!([
+                    [1700000000000i64, "100.0", "112.5", "99.0", "110.0", "5.5", 1700003599999i64, "600.0", 42, "2.0", "220.0", "0"],
+                    [1700003600000i64, "110.0", "111.0", "103.5", "104.0", "8.0", 1700007199999i64, "850.0", 37, "3.0", "320.0", "0"],
+                ]))
+            }))
+            .route("/api/v3/exchangeInfo", get(|| async {
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "code": -1000, "msg": "An unknown error occurred." })))
+            }));
+        BinanceProvider::new(stub_server(router).await)
+    }
+
+    fn d(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn parses_prices_tickers_and_candles() {
+        let binance = provider().await;
+        let btc = Pair::new("BTC", "USDT");
+        assert_eq!(binance.price(&btc).await.unwrap(), d("67187.33"));
+
+        let ticker = binance.ticker_24h(&btc).await.unwrap();
+        assert_eq!((ticker.open, ticker.last, ticker.change_percent), (d("65986.83"), d("67187.33"), d("1.82")));
+        assert_eq!(ticker.quote_volume, Some(d("824690000.12")));
+
+        let candles = binance.klines(&btc, "1h", 2).await.unwrap();
+        assert_eq!(candles.len(), 2);
+        assert_eq!(candles[0].open_time.timestamp(), 1_700_000_000);
+        assert_eq!((candles[1].open, candles[1].close, candles[1].volume), (d("110"), d("104"), d("8")));
+    }
+
+    #[tokio::test]
+    async fn maps_error_payloads_to_provider_errors() {
+        let binance = provider().await;
+        assert!(matches!(binance.price(&Pair::new("FOO", "BAR")).await,
+            Err(ProviderError::InvalidSymbol(symbol)) if symbol == "FOO/BAR"));
+        assert!(matches!(binance.price(&Pair::new("RATE", "USDT")).await,
+            Err(ProviderError::RateLimited { retry_after: Some(wait) }) if wait == Duration::from_secs(7)));
+        assert!(matches!(binance.pairs().await,
+            Err(ProviderError::Api { status: 500, code: Some(-1000), .. })));
+    }
+}
@@ -0,0 +1,152 @@
+// Proveedor de precios basado en el API público de Coinbase Exchange
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+use super::{http_client, parse_decimal, read_json, Pair, PriceProvider, ProviderResult, Ticker24h};
+
+pub struct CoinbaseProvider {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+// Elemento de /products
+#[derive(Deserialize, Debug)]
+struct Product {
+    base_currency: String,
+    quote_currency: String,
+    status: String,
+    #[serde(default)]
+    trading_disabled: bool,
+}
+
+// Respuesta de /products/{id}/ticker
+#[derive(Deserialize, Debug)]
+struct TickerResponse {
+    price: String,
+}
+
+// Respuesta de /products/{id}/stats (ventana de 24 horas); el volumen está en el activo base
+#[derive(Deserialize, Debug)]
+struct StatsResponse {
+    open: String,
+    high: String,
+    low: String,
+    last: String,
+    volume: String,
+}
+
+impl CoinbaseProvider {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        CoinbaseProvider { client: http_client(), base_url: base_url.into() }
+    }
+
+    // La URL base se puede cambiar con COINBASE_API_URL
+    pub fn from_env() -> Self {
+        Self::new(std::env::var("COINBASE_API_URL").unwrap_or("https://api.exchange.coinbase.com".to_string()))
+    }
+
+    // Identificador de producto en formato Coinbase: BTC/USD -> BTC-USD
+    fn product_id(pair: &Pair) -> String {
+        format!("{}-{}", pair.base, pair.quote)
+    }
+}
+
+#[async_trait]
+impl PriceProvider for CoinbaseProvider {
+    fn name(&self) -> &'static str {
+        "Coinbase"
+    }
+
+    async fn pairs(&self) -> ProviderResult<Vec<Pair>> {
+        let url = format!("{}/products", self.base_url);
+        let products: Vec<Product> = read_json(self.client.get(url).send().await?).await?;
+        Ok(products.into_iter()
+            .filter(|p| p.status == "online" && !p.trading_disabled)
+            .map(|p| Pair::new(&p.base_currency, &p.quote_currency))
+            .collect())
+    }
+
+    async fn price(&self, pair: &Pair) -> ProviderResult<Decimal> {
+        let url = format!("{}/products/{}/ticker", self.base_url, Self::product_id(pair));
+        let body: TickerResponse = read_json(self.client.get(url).send().await?).await
+            .map_err(|err| err.or_invalid_symbol(pair))?;
+        parse_decimal(&body.price)
+    }
+
+    async fn ticker_24h(&self, pair: &Pair) -> ProviderResult<Ticker24h> {
+        let url = format!("{}/products/{}/stats", self.base_url, Self::product_id(pair));
+        let body: StatsResponse = read_json(self.client.get(url).send().await?).await
+            .map_err(|err| err.or_invalid_symbol(pair))?;
+        let mut ticker = Ticker24h::from_prices(
+            parse_decimal(&body.open)?,
+            parse_decimal(&body.high)?,
+            parse_decimal(&body.low)?,
+            parse_decimal(&body.last)?,
+        );
+        ticker.volume = Some(parse_decimal(&body.volume)?);
+        Ok(ticker)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::extract::Path;
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+    use axum::routing::get;
+    use axum::{Json, Router};
+    use serde_json::json;
+
+    use super::*;
+    use crate::providers::{stub_server, ProviderError};
+
+    // Responde como Coinbase Exchange: solo existe BTC-USD y el resto de productos da 404
+    async fn ticker(Path(id): Path<String>) -> axum::response::Response {
+        match id.as_str() {
+            "BTC-USD" => Json(json!({
+                "trade_id": 86326522, "price": "67187.33", "size": "0.00013", "bid": "67187.32", "ask": "67187.33",
+                "volume": "9356.2", "time": "2026-10-18T10:00:00.000000Z",
+            })).into_response(),
+            _ => (StatusCode::NOT_FOUND, Json(json!({ "message": "NotFound" }))).into_response(),
+        }
+    }
+
+    async fn provider() -> CoinbaseProvider {
+        let router = Router::new()
+            .route("/products", get(|| async {
+                Json(json!([
+                    { "id": "BTC-USD", "base_currency": "BTC", "quote_currency": "USD", "status": "online", "trading_disabled": false },
+                    { "id": "ETH-EUR", "base_currency": "ETH", "quote_currency": "EUR", "status": "online", "trading_disabled": true },
+                    { "id": "OLD-USD", "base_currency": "OLD", "quote_currency": "USD", "status": "delisted" },
+                ]))
+            }))
+            .route("/products/:id/ticker", get(ticker))
+            .route("/products/:id/stats", get(|| async {
+                Json(json!({ "open": "66000.00", "high": "68000.00", "low": "65000.00", "last": "67187.33", "volume": "9356.2" }))
+            }));
+        CoinbaseProvider::new(stub_server(router).await)
+    }
+
+    fn d(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn parses_products_tickers_and_stats() {
+        let coinbase = provider().await;
+        assert_eq!(coinbase.pairs().await.unwrap(), vec![Pair::new("BTC", "USD")]);
+
+        let btc = Pair::new("BTC", "USD");
+        assert_eq!(coinbase.price(&btc).await.unwrap(), d("67187.33"));
+        let ticker = coinbase.ticker_24h(&btc).await.unwrap();
+        assert_eq!((ticker.open, ticker.high, ticker.low, ticker.last), (d("66000"), d("68000"), d("65000"), d("67187.33")));
+        assert_eq!(ticker.volume, Some(d("9356.2")));
+    }
+
+    #[tokio::test]
+    async fn unknown_products_are_invalid_symbols() {
+        let coinbase = provider().await;
+        assert!(matches!(coinbase.price(&Pair::new("NOPE", "USD")).await, Err(ProviderError::InvalidSymbol(symbol)) if symbol == "NOPE/USD"));
+    }
+}
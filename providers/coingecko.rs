@@ -0,0 +1,224 @@
+// Proveedor de precios basado en el API de CoinGecko.
+// CoinGecko no es un exchange: identifica las monedas por id ("bitcoin") y cotiza contra
+// un conjunto fijo de divisas, así que solo se ofrecen las monedas con mayor capitalización.
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use tokio::sync::RwLock;
+
+use super::{http_client, read_json, Pair, PriceProvider, ProviderError, ProviderResult, Ticker24h};
+
+// Divisas de cotización que se ofrecen para cada moneda
+const VS_CURRENCIES: [&str; 7] = ["USD", "USDT", "EUR", "GBP", "JPY", "BTC", "ETH"];
+// Número de monedas (por capitalización) que se cargan en la lista de pares
+const TOP_COINS: usize = 250;
+
+pub struct CoinGeckoProvider {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+    // Traducción de símbolo (BTC) a id de CoinGecko (bitcoin)
+    coin_ids: RwLock<HashMap<String, String>>,
+}
+
+// Elemento de /coins/markets
+#[derive(Deserialize, Debug)]
+struct Market {
+    id: String,
+    symbol: String,
+}
+
+// Elemento de /coins/markets con los datos de mercado de una moneda; total_volume está en la divisa de cotización
+#[derive(Deserialize, Debug)]
+struct MarketData {
+    current_price: Option<Decimal>,
+    high_24h: Option<Decimal>,
+    low_24h: Option<Decimal>,
+    price_change_24h: Option<Decimal>,
+    total_volume: Option<Decimal>,
+}
+
+impl CoinGeckoProvider {
+    pub fn new(base_url: impl Into<String>, api_key: Option<String>) -> Self {
+        CoinGeckoProvider { client: http_client(), base_url: base_url.into(), api_key, coin_ids: RwLock::new(HashMap::new()) }
+    }
+
+    // La URL base se puede cambiar con COINGECKO_API_URL; COINGECKO_API_KEY es opcional
+    pub fn from_env() -> Self {
+        Self::new(
+            std::env::var("COINGECKO_API_URL").unwrap_or("https://api.coingecko.com/api/v3".to_string()),
+            std::env::var("COINGECKO_API_KEY").ok(),
+        )
+    }
+
+    // CoinGecko no cotiza contra stablecoins, así que USDT se aproxima con USD
+    fn vs_currency(quote: &str) -> String {
+        match quote {
+            "USDT" => "usd".to_string(),
+            other => other.to_lowercase(),
+        }
+    }
+
+    fn request(&self, path: &str) -> reqwest::RequestBuilder {
+        let request = self.client.get(format!("{}{}", self.base_url, path));
+        match &self.api_key {
+            Some(key) => request.header("x-cg-demo-api-key", key),
+            None => request,
+        }
+    }
+
+    // Id de CoinGecko de la moneda base de un par
+    async fn coin_id(&self, pair: &Pair) -> ProviderResult<String> {
+        if self.coin_ids.read().await.is_empty() {
+            self.load_coin_ids().await?;
+        }
+        self.coin_ids.read().await.get(&pair.base).cloned()
+            .ok_or_else(|| ProviderError::InvalidSymbol(pair.to_string()))
+    }
+
+    async fn load_coin_ids(&self) -> ProviderResult<HashMap<String, String>> {
+        let markets: Vec<Market> = read_json(self.request("/coins/markets")
+            .query(&[("vs_currency", "usd"), ("order", "market_cap_desc"), ("per_page", &TOP_COINS.to_string()), ("page", "1")])
+            .send().await?).await?;
+        let mut ids = HashMap::new();
+        for market in markets {
+            // Varios tokens comparten símbolo; se queda el de mayor capitalización (el primero)
+            ids.entry(market.symbol.to_uppercase()).or_insert(market.id);
+        }
+        *self.coin_ids.write().await = ids.clone();
+        Ok(ids)
+    }
+}
+
+#[async_trait]
+impl PriceProvider for CoinGeckoProvider {
+    fn name(&self) -> &'static str {
+        "CoinGecko"
+    }
+
+    async fn pairs(&self) -> ProviderResult<Vec<Pair>> {
+        let ids = self.load_coin_ids().await?;
+        Ok(ids.keys()
+            .flat_map(|base| VS_CURRENCIES.iter()
+                .filter(move |quote| *quote != base)
+                .map(move |quote| Pair::new(base, quote)))
+            .collect())
+    }
+
+    async fn price(&self, pair: &Pair) -> ProviderResult<Decimal> {
+        let id = self.coin_id(pair).await?;
+        let vs = Self::vs_currency(&pair.quote);
+
+        // Respuesta de /simple/price: {"bitcoin": {"usd": 67187.33}}
+        let body: HashMap<String, HashMap<String, Decimal>> = read_json(self.request("/simple/price")
+            .query(&[("ids", id.as_str()), ("vs_currencies", vs.as_str()), ("precision", "full")])
+            .send().await?).await?;
+        body.get(&id)
+            .and_then(|prices| prices.get(&vs))
+            .copied()
+            .ok_or_else(|| ProviderError::InvalidSymbol(pair.to_string()))
+    }
+
+    async fn ticker_24h(&self, pair: &Pair) -> ProviderResult<Ticker24h> {
+        let id = self.coin_id(pair).await?;
+        let vs = Self::vs_currency(&pair.quote);
+        let markets: Vec<MarketData> = read_json(self.request("/coins/markets")
+            .query(&[("vs_currency", vs.as_str()), ("ids", id.as_str())])
+            .send().await?).await?;
+        let market = markets.into_iter().next()
+            .ok_or_else(|| ProviderError::InvalidSymbol(pair.to_string()))?;
+        let last = market.current_price
+            .ok_or_else(|| ProviderError::InvalidResponse(format!("CoinGecko has no {} price for {}", vs, id)))?;
+        // CoinGecko no da el precio de apertura, se obtiene a partir de la variación absoluta
+        let open = last - market.price_change_24h.unwrap_or_default();
+        let mut ticker = Ticker24h::from_prices(
+            open,
+            market.high_24h.unwrap_or(last),
+            market.low_24h.unwrap_or(last),
+            last,
+        );
+        ticker.quote_volume = market.total_volume;
+        Ok(ticker)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::extract::Query;
+    use axum::routing::get;
+    use axum::{Json, Router};
+    use serde_json::json;
+
+    use super::*;
+    use crate::providers::stub_server;
+
+    // Con "ids" responde los datos de mercado de esa moneda y sin él la lista por capitalización,
+    // en la que dos tokens comparten el símbolo BTC
+    async fn markets(Query(query): Query<HashMap<String, String>>) -> Json<serde_json::Value> {
+        match (query.get("ids").map(String::as_str), query.get("vs_currency").map(String::as_str)) {
+            (None, _) => Json(json!([
+                { "id": "bitcoin", "symbol": "btc", "name": "Bitcoin" },
+                { "id": "ethereum", "symbol": "eth", "name": "Ethereum" },
+                { "id": "batcat", "symbol": "btc", "name": "Batcat" },
+            ])),
+            (Some("bitcoin"), Some("eur")) => Json(json!([{
+                "id": "bitcoin", "current_price": 61000.5, "high_24h": 62000, "low_24h": 60000,
+                "price_change_24h": 500.5, "total_volume": 1500000000,
+            }])),
+            _ => Json(json!([])),
+        }
+    }
+
+    // Solo responde si las divisas llegan en minúsculas, como exige CoinGecko
+    async fn simple_price(Query(query): Query<HashMap<String, String>>) -> Json<serde_json::Value> {
+        match (query.get("ids").map(String::as_str), query.get("vs_currencies").map(String::as_str)) {
+            (Some("bitcoin"), Some("usd")) => Json(json!({ "bitcoin": { "usd": 67187.33 } })),
+            (Some("ethereum"), Some("eur")) => Json(json!({ "ethereum": { "eur": 2450.1 } })),
+            (Some(id), _) => Json(json!({ id: {} })),
+            _ => Json(json!({})),
+        }
+    }
+
+    async fn provider() -> CoinGeckoProvider {
+        let router = Router::new()
+            .route("/coins/markets", get(markets))
+            .route("/simple/price", get(simple_price));
+        CoinGeckoProvider::new(stub_server(router).await, None)
+    }
+
+    fn d(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn maps_symbols_to_coin_ids_and_lowercase_currencies() {
+        let coingecko = provider().await;
+        assert_eq!(coingecko.price(&Pair::new("BTC", "USD")).await.unwrap(), d("67187.33"));
+        // USDT se cotiza como USD
+        assert_eq!(coingecko.price(&Pair::new("BTC", "USDT")).await.unwrap(), d("67187.33"));
+        assert_eq!(coingecko.price(&Pair::new("ETH", "EUR")).await.unwrap(), d("2450.1"));
+
+        // Con símbolos repetidos se queda la moneda de mayor capitalización
+        let ticker = coingecko.ticker_24h(&Pair::new("BTC", "EUR")).await.unwrap();
+        assert_eq!((ticker.open, ticker.high, ticker.low, ticker.last), (d("60500"), d("62000"), d("60000"), d("61000.5")));
+        assert_eq!(ticker.quote_volume, Some(d("1500000000")));
+    }
+
+    #[tokio::test]
+    async fn lists_each_coin_against_the_supported_currencies() {
+        let pairs = provider().await.pairs().await.unwrap();
+        assert!(pairs.contains(&Pair::new("BTC", "EUR")) && pairs.contains(&Pair::new("ETH", "BTC")));
+        assert!(!pairs.contains(&Pair::new("BTC", "BTC")));
+        assert_eq!(pairs.len(), 2 * (VS_CURRENCIES.len() - 1));
+    }
+
+    #[tokio::test]
+    async fn unknown_coins_and_currencies_are_invalid_symbols() {
+        let coingecko = provider().await;
+        assert!(matches!(coingecko.price(&Pair::new("NOPE", "USD")).await, Err(ProviderError::InvalidSymbol(symbol)) if symbol == "NOPE/USD"));
+        assert!(matches!(coingecko.price(&Pair::new("BTC", "CHF")).await, Err(ProviderError::InvalidSymbol(_))));
+        assert!(matches!(coingecko.ticker_24h(&Pair::new("ETH", "GBP")).await, Err(ProviderError::InvalidSymbol(_))));
+    }
+}
@@ -0,0 +1,128 @@
+// Errores de los proveedores de precios. Display da el detalle técnico para los logs y
+// user_message el texto que se muestra en el chat, sin volcar la depuración de reqwest.
+use std::fmt;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::i18n::Locale;
+
+// Código de Binance para un símbolo que no existe
+const BINANCE_INVALID_SYMBOL: i64 = -1121;
+
+#[derive(Debug, Clone)]
+pub enum ProviderError {
+    // No se pudo conectar con la fuente o la conexión se cortó
+    Network(String),
+    // La fuente tardó más de lo permitido
+    Timeout(Duration),
+    // La fuente tiene el circuito abierto tras varios fallos seguidos
+    Disabled,
+    // Respuesta HTTP de error sin un mensaje del API reconocible
+    Status(u16),
+    // Error devuelto por el API del exchange, p. ej. Binance {"code": -1121, "msg": "Invalid symbol."}
+    Api { status: u16, code: Option<i64>, message: String },
+    // La fuente está limitando las peticiones (HTTP 429, o 418 cuando Binance banea la IP)
+    RateLimited { retry_after: Option<Duration> },
+    // El símbolo o par no existe en la fuente
+    InvalidSymbol(String),
+    // Un número de la respuesta no se pudo interpretar
+    MalformedNumber(String),
+    // La respuesta no tiene el formato esperado
+    InvalidResponse(String),
+    // Configuración de proveedores inválida
+    Config(String),
+    // Ninguna fuente respondió; se guarda el error de cada una
+    Unavailable(Vec<(&'static str, ProviderError)>),
+}
+
+pub type ProviderResult<T> = Result<T, ProviderError>;
+
+// Cuerpo de error de los APIs: Binance usa {"code", "msg"} y Coinbase {"message"}
+#[derive(Deserialize, Debug)]
+struct ApiErrorBody {
+    code: Option<i64>,
+    #[serde(alias = "message")]
+    msg: Option<String>,
+}
+
+impl ProviderError {
+    // Texto para el usuario en el idioma de su chat, sin los detalles técnicos del error
+    pub fn user_message(&self, locale: Locale) -> String {
+        crate::i18n::provider_error(locale, self)
+    }
+
+    pub fn is_invalid_symbol(&self) -> bool {
+        matches!(self, ProviderError::InvalidSymbol(_))
+    }
+
+    // Si el error indica que la fuente falla (y no que el usuario pidió algo que no existe),
+    // cuenta para su circuit breaker
+    pub fn is_source_failure(&self) -> bool {
+        !self.is_invalid_symbol()
+    }
+
+    // Traduce las respuestas de "par desconocido" de cada API (Binance -1121, 404 de Coinbase) a InvalidSymbol
+    pub fn or_invalid_symbol(self, symbol: impl fmt::Display) -> Self {
+        match self {
+            ProviderError::Status(404)
+            | ProviderError::Api { status: 404, .. }
+            | ProviderError::Api { code: Some(BINANCE_INVALID_SYMBOL), .. } => ProviderError::InvalidSymbol(symbol.to_string()),
+            other => other,
+        }
+    }
+}
+
+impl fmt::Display for ProviderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProviderError::Network(err) => write!(f, "network error: {}", err),
+            ProviderError::Timeout(after) => write!(f, "timed out after {:?}", after),
+            ProviderError::Disabled => write!(f, "temporarily disabled"),
+            ProviderError::Status(status) => write!(f, "HTTP {}", status),
+            ProviderError::Api { status, code: Some(code), message } => write!(f, "API error {} (HTTP {}): {}", code, status, message),
+            ProviderError::Api { status, code: None, message } => write!(f, "API error (HTTP {}): {}", status, message),
+            ProviderError::RateLimited { retry_after: Some(wait) } => write!(f, "rate limited, retry after {:?}", wait),
+            ProviderError::RateLimited { retry_after: None } => write!(f, "rate limited"),
+            ProviderError::InvalidSymbol(symbol) => write!(f, "unknown symbol '{}'", symbol),
+            ProviderError::MalformedNumber(value) => write!(f, "invalid number '{}'", value),
+            ProviderError::InvalidResponse(detail) => write!(f, "unexpected response: {}", detail),
+            ProviderError::Config(detail) => write!(f, "{}", detail),
+            ProviderError::Unavailable(errors) => {
+                let details: Vec<String> = errors.iter().map(|(name, err)| format!("{}: {}", name, err)).collect();
+                write!(f, "no price source available ({})", details.join("; "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProviderError {}
+
+impl From<reqwest::Error> for ProviderError {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_decode() {
+            ProviderError::InvalidResponse(err.to_string())
+        } else {
+            ProviderError::Network(err.to_string())
+        }
+    }
+}
+
+// Comprueba el estado HTTP de una respuesta y la decodifica como JSON, clasificando los errores
+pub async fn read_json<T: serde::de::DeserializeOwned>(response: reqwest::Response) -> ProviderResult<T> {
+    let status = response.status();
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS || status == reqwest::StatusCode::IM_A_TEAPOT {
+        let retry_after = response.headers().get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse().ok())
+            .map(Duration::from_secs);
+        return Err(ProviderError::RateLimited { retry_after });
+    }
+    if !status.is_success() {
+        return Err(match response.json::<ApiErrorBody>().await {
+            Ok(ApiErrorBody { code, msg: Some(message) }) => ProviderError::Api { status: status.as_u16(), code, message },
+            _ => ProviderError::Status(status.as_u16()),
+        });
+    }
+    Ok(response.json::<T>().await?)
+}
@@ -0,0 +1,85 @@
+// Tipos de cambio entre divisas fiat, para convertir a monedas que no cotizan en los exchanges.
+// Por defecto usa Frankfurter (tipos de referencia del BCE, sin clave); la URL se puede cambiar
+// por cualquier API con el mismo formato.
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+use super::{http_client, read_json, ProviderError, ProviderResult};
+
+// Los tipos de referencia se publican una vez al día, así que basta con renovarlos cada hora
+const RATES_TTL: Duration = Duration::from_secs(60 * 60);
+
+// Divisas fiat que se convierten con este proveedor si no existe un par en los exchanges
+const DEFAULT_FIAT: [&str; 31] = [
+    "USD", "EUR", "GBP", "JPY", "CHF", "CAD", "AUD", "NZD", "CNY", "HKD", "SGD", "SEK", "NOK", "DKK", "PLN", "CZK",
+    "HUF", "TRY", "MXN", "BRL", "INR", "KRW", "ZAR", "ILS", "THB", "IDR", "PHP", "MYR", "RON", "BGN", "ISK",
+];
+
+pub struct FxProvider {
+    client: reqwest::Client,
+    base_url: String,
+    currencies: Vec<String>,
+    // Unidades de cada divisa por dólar y cuándo se obtuvieron
+    rates: Mutex<HashMap<String, (Decimal, Instant)>>,
+}
+
+// Respuesta de /latest?base=USD&symbols=EUR: {"base": "USD", "date": "2024-05-03", "rates": {"EUR": 0.9287}}
+#[derive(Deserialize, Debug)]
+struct LatestResponse {
+    rates: HashMap<String, Decimal>,
+}
+
+impl FxProvider {
+    pub fn new(base_url: impl Into<String>, currencies: Vec<String>) -> Self {
+        FxProvider {
+            client: http_client(),
+            base_url: base_url.into(),
+            currencies: currencies.into_iter().map(|c| c.to_uppercase()).collect(),
+            rates: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // FX_API_URL cambia el proveedor y FIAT_CURRENCIES (separadas por comas) la lista de divisas fiat
+    pub fn from_env() -> Self {
+        Self::new(
+            std::env::var("FX_API_URL").unwrap_or("https://api.frankfurter.dev/v1".to_string()),
+            crate::config::env_list("FIAT_CURRENCIES")
+                .unwrap_or_else(|| DEFAULT_FIAT.iter().map(|c| c.to_string()).collect()),
+        )
+    }
+
+    pub fn is_fiat(&self, asset: &str) -> bool {
+        self.currencies.iter().any(|c| c.eq_ignore_ascii_case(asset))
+    }
+
+    // Unidades de `currency` que se obtienen por un dólar
+    pub async fn per_usd(&self, currency: &str) -> ProviderResult<Decimal> {
+        let currency = currency.to_uppercase();
+        if currency == "USD" {
+            return Ok(Decimal::ONE);
+        }
+        if !self.is_fiat(&currency) {
+            return Err(ProviderError::InvalidSymbol(currency));
+        }
+        if let Some((rate, fetched_at)) = self.rates.lock().unwrap().get(&currency) {
+            if fetched_at.elapsed() < RATES_TTL {
+                return Ok(*rate);
+            }
+        }
+
+        let url = format!("{}/latest", self.base_url);
+        let body: LatestResponse = read_json(self.client.get(url)
+            .query(&[("base", "USD"), ("symbols", currency.as_str())])
+            .send().await?).await
+            .map_err(|err| err.or_invalid_symbol(&currency))?;
+        let rate = body.rates.get(&currency).copied()
+            .filter(|rate| !rate.is_zero())
+            .ok_or_else(|| ProviderError::InvalidSymbol(currency.clone()))?;
+        self.rates.lock().unwrap().insert(currency, (rate, Instant::now()));
+        Ok(rate)
+    }
+}
@@ -0,0 +1,212 @@
+// Proveedor de precios basado en el API público de Kraken
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use tokio::sync::RwLock;
+
+use super::{http_client, parse_decimal, read_json, Pair, PriceProvider, ProviderError, ProviderResult, Ticker24h};
+
+pub struct KrakenProvider {
+    client: reqwest::Client,
+    base_url: String,
+    // Kraken usa nombres propios para los pares (XBTUSD, XETHZUSD...), así que se guarda
+    // la traducción obtenida de AssetPairs para poder pedir el ticker de un Pair
+    pair_names: RwLock<HashMap<Pair, String>>,
+}
+
+// Todas las respuestas de Kraken vienen envueltas en {"error": [...], "result": ...}
+#[derive(Deserialize, Debug)]
+struct KrakenResponse<T> {
+    error: Vec<String>,
+    result: Option<T>,
+}
+
+// Elemento de /0/public/AssetPairs
+#[derive(Deserialize, Debug)]
+struct AssetPair {
+    altname: String,
+    wsname: Option<String>,
+    status: Option<String>,
+}
+
+// Elemento de /0/public/Ticker. "c" es [precio de la última operación, volumen], "o" el precio de apertura
+// del día y el resto son pares [hoy, últimas 24 horas]: "v" volumen, "p" precio medio ponderado, "h" máximo y "l" mínimo
+#[derive(Deserialize, Debug)]
+struct TickerInfo {
+    c: Vec<String>,
+    o: String,
+    v: Vec<String>,
+    p: Vec<String>,
+    h: Vec<String>,
+    l: Vec<String>,
+}
+
+// Valor de las últimas 24 horas de un campo [hoy, últimas 24 horas]
+fn last_24h<'a>(field: &'a [String], name: &str) -> ProviderResult<&'a str> {
+    field.get(1).map(String::as_str)
+        .ok_or_else(|| ProviderError::InvalidResponse(format!("Kraken ticker without 24h {}", name)))
+}
+
+// Kraken devuelve los errores dentro de la respuesta con el formato "ECategoría:Mensaje"
+fn kraken_error(errors: &[String]) -> ProviderError {
+    let message = errors.join(", ");
+    if errors.iter().any(|e| e.starts_with("EQuery:Unknown asset pair")) {
+        ProviderError::InvalidSymbol(message)
+    } else if errors.iter().any(|e| e.contains("Rate limit") || e.contains("Too many requests")) {
+        ProviderError::RateLimited { retry_after: None }
+    } else {
+        ProviderError::Api { status: 200, code: None, message }
+    }
+}
+
+impl KrakenProvider {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        KrakenProvider { client: http_client(), base_url: base_url.into(), pair_names: RwLock::new(HashMap::new()) }
+    }
+
+    // La URL base se puede cambiar con KRAKEN_API_URL
+    pub fn from_env() -> Self {
+        Self::new(std::env::var("KRAKEN_API_URL").unwrap_or("https://api.kraken.com".to_string()))
+    }
+
+    // Kraken mantiene códigos heredados para algunos activos
+    fn normalize_asset(asset: &str) -> &str {
+        match asset {
+            "XBT" => "BTC",
+            "XDG" => "DOGE",
+            other => other,
+        }
+    }
+
+    async fn get<T: serde::de::DeserializeOwned>(&self, path: &str, query: &[(&str, &str)]) -> ProviderResult<T> {
+        let url = format!("{}{}", self.base_url, path);
+        let body: KrakenResponse<T> = read_json(self.client.get(url).query(query).send().await?).await?;
+        if !body.error.is_empty() {
+            return Err(kraken_error(&body.error));
+        }
+        body.result.ok_or_else(|| ProviderError::InvalidResponse("Kraken response without result".to_string()))
+    }
+
+    // Ticker de un par, traduciendo antes el par a su nombre en Kraken
+    async fn ticker(&self, pair: &Pair) -> ProviderResult<TickerInfo> {
+        if self.pair_names.read().await.is_empty() {
+            self.pairs().await?;
+        }
+        let name = self.pair_names.read().await.get(pair).cloned()
+            .ok_or_else(|| ProviderError::InvalidSymbol(pair.to_string()))?;
+
+        let tickers: HashMap<String, TickerInfo> = self.get("/0/public/Ticker", &[("pair", &name)]).await?;
+        // La clave de la respuesta no siempre coincide con el nombre pedido, así que se toma la única entrada
+        tickers.into_values().next()
+            .ok_or_else(|| ProviderError::InvalidResponse(format!("Kraken returned no ticker for {}", pair)))
+    }
+}
+
+#[async_trait]
+impl PriceProvider for KrakenProvider {
+    fn name(&self) -> &'static str {
+        "Kraken"
+    }
+
+    async fn pairs(&self) -> ProviderResult<Vec<Pair>> {
+        let asset_pairs: HashMap<String, AssetPair> = self.get("/0/public/AssetPairs", &[]).await?;
+        let mut names = HashMap::new();
+        for info in asset_pairs.into_values() {
+            if info.status.as_deref().is_some_and(|s| s != "online") {
+                continue;
+            }
+            // wsname tiene la forma "XBT/USD", que es la más fácil de normalizar
+            if let Some((base, quote)) = info.wsname.as_deref().and_then(|w| w.split_once('/')) {
+                let pair = Pair::new(Self::normalize_asset(base), Self::normalize_asset(quote));
+                names.insert(pair, info.altname);
+            }
+        }
+        let pairs = names.keys().cloned().collect();
+        *self.pair_names.write().await = names;
+        Ok(pairs)
+    }
+
+    async fn price(&self, pair: &Pair) -> ProviderResult<Decimal> {
+        let ticker = self.ticker(pair).await?;
+        let last = ticker.c.first()
+            .ok_or_else(|| ProviderError::InvalidResponse(format!("Kraken ticker for {} has no last trade", pair)))?;
+        parse_decimal(last)
+    }
+
+    // Kraken no ofrece una ventana móvil de 24 horas para la apertura, así que la variación
+    // se calcula desde la apertura del día (00:00 UTC)
+    async fn ticker_24h(&self, pair: &Pair) -> ProviderResult<Ticker24h> {
+        let ticker = self.ticker(pair).await?;
+        let last = parse_decimal(ticker.c.first()
+            .ok_or_else(|| ProviderError::InvalidResponse(format!("Kraken ticker for {} has no last trade", pair)))?)?;
+        let mut stats = Ticker24h::from_prices(
+            parse_decimal(&ticker.o)?,
+            parse_decimal(last_24h(&ticker.h, "high")?)?,
+            parse_decimal(last_24h(&ticker.l, "low")?)?,
+            last,
+        );
+        let volume = parse_decimal(last_24h(&ticker.v, "volume")?)?;
+        let weighted_avg = parse_decimal(last_24h(&ticker.p, "weighted average")?)?;
+        stats.volume = Some(volume);
+        stats.weighted_avg = Some(weighted_avg);
+        // El volumen en la moneda de cotización es exactamente volumen × precio medio ponderado
+        stats.quote_volume = Some(volume * weighted_avg);
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::routing::get;
+    use axum::{Json, Router};
+    use serde_json::json;
+
+    use super::*;
+    use crate::providers::stub_server;
+
+    // Kraken devuelve los errores con HTTP 200 dentro de "error"
+    async fn provider(ticker: serde_json::Value) -> KrakenProvider {
+        let router = Router::new()
+            .route("/0/public/AssetPairs", get(|| async {
+                Json(json!({ "error": [], "result": {
+                    "XXBTZUSD": { "altname": "XBTUSD", "wsname": "XBT/USD", "status": "online" },
+                    "XETHZUSD": { "altname": "ETHUSD", "wsname": "ETH/USD", "status": "cancel_only" },
+                }}))
+            }))
+            .route("/0/public/Ticker", get(move || async move { Json(ticker) }));
+        KrakenProvider::new(stub_server(router).await)
+    }
+
+    fn d(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn parses_pairs_and_tickers() {
+        let kraken = provider(json!({ "error": [], "result": { "XXBTZUSD": {
+            "c": ["67000.10000", "0.01"], "o": "66000.00000", "v": ["10.0", "100.0"],
+            "p": ["66500.0", "66600.0"], "h": ["67500.0", "68000.0"], "l": ["65500.0", "65000.0"],
+        }}})).await;
+        assert_eq!(kraken.pairs().await.unwrap(), vec![Pair::new("BTC", "USD")]);
+
+        let btc = Pair::new("BTC", "USD");
+        assert_eq!(kraken.price(&btc).await.unwrap(), d("67000.1"));
+        let ticker = kraken.ticker_24h(&btc).await.unwrap();
+        assert_eq!((ticker.open, ticker.high, ticker.low), (d("66000"), d("68000"), d("65000")));
+        assert_eq!(ticker.quote_volume, Some(d("6660000")));
+    }
+
+    #[tokio::test]
+    async fn maps_error_payloads_to_provider_errors() {
+        let kraken = provider(json!({ "error": ["EGeneral:Too many requests"] })).await;
+        assert!(matches!(kraken.price(&Pair::new("BTC", "USD")).await, Err(ProviderError::RateLimited { retry_after: None })));
+        // Los pares que no están en AssetPairs (o no están "online") no llegan a pedirse
+        assert!(matches!(kraken.price(&Pair::new("ETH", "USD")).await, Err(ProviderError::InvalidSymbol(_))));
+
+        let kraken = provider(json!({ "error": ["EService:Unavailable"] })).await;
+        assert!(matches!(kraken.price(&Pair::new("BTC", "USD")).await,
+            Err(ProviderError::Api { status: 200, code: None, message }) if message == "EService:Unavailable"));
+    }
+}
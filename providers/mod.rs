@@ -0,0 +1,158 @@
+// Proveedores de precios: cada exchange implementa el trait PriceProvider para que el bot
+// no dependa de un único API (p. ej. cuando Binance está bloqueado en la región).
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+
+mod binance;
+mod coinbase;
+mod coingecko;
+mod error;
+mod fx;
+mod kraken;
+
+pub use binance::{BinanceProvider, Candle};
+pub use coinbase::CoinbaseProvider;
+pub use coingecko::CoinGeckoProvider;
+pub use error::{ProviderError, ProviderResult};
+pub use fx::FxProvider;
+pub use kraken::KrakenProvider;
+
+use error::read_json;
+
+// User-Agent que se envía en todas las peticiones (Coinbase rechaza peticiones sin él)
+const USER_AGENT: &str = concat!("Cryptocat/", env!("CARGO_PKG_VERSION"));
+
+// Par de trading normalizado (activo base + activo de cotización), independiente del exchange.
+// Cada proveedor se encarga de traducirlo a su propio formato (BTCUSDT, BTC-USD, XBTUSD...).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Pair {
+    pub base: String,
+    pub quote: String,
+}
+
+impl Pair {
+    pub fn new(base: &str, quote: &str) -> Self {
+        Pair { base: base.to_uppercase(), quote: quote.to_uppercase() }
+    }
+}
+
+impl fmt::Display for Pair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.base, self.quote)
+    }
+}
+
+// Permite leer un par en formato "BTC/USDT" (el mismo que produce Display)
+impl FromStr for Pair {
+    type Err = ProviderError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('/') {
+            Some((base, quote)) if !base.is_empty() && !quote.is_empty() => Ok(Pair::new(base, quote)),
+            _ => Err(ProviderError::InvalidSymbol(s.to_string())),
+        }
+    }
+}
+
+// Estadísticas de las últimas 24 horas de un par. Los campos opcionales no los ofrecen todos los APIs.
+#[derive(Debug, Clone)]
+pub struct Ticker24h {
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub last: Decimal,
+    // Variación absoluta y porcentual del precio en las últimas 24 horas
+    pub change: Decimal,
+    pub change_percent: Decimal,
+    // Precio medio ponderado por volumen
+    pub weighted_avg: Option<Decimal>,
+    // Volumen negociado en el activo base y en el de cotización
+    pub volume: Option<Decimal>,
+    pub quote_volume: Option<Decimal>,
+}
+
+impl Ticker24h {
+    // Construye las estadísticas calculando la variación, para los APIs que no la dan calculada
+    pub fn from_prices(open: Decimal, high: Decimal, low: Decimal, last: Decimal) -> Self {
+        let change = last - open;
+        Ticker24h {
+            open,
+            high,
+            low,
+            last,
+            change,
+            change_percent: if open.is_zero() { Decimal::ZERO } else { change / open * Decimal::ONE_HUNDRED },
+            weighted_avg: None,
+            volume: None,
+            quote_volume: None,
+        }
+    }
+}
+
+// Interfaz común de los proveedores de precios
+#[async_trait]
+pub trait PriceProvider: Send + Sync {
+    // Nombre legible del proveedor, se muestra al usuario
+    fn name(&self) -> &'static str;
+
+    // Lista de pares que el proveedor puede cotizar en este momento
+    async fn pairs(&self) -> ProviderResult<Vec<Pair>>;
+
+    // Último precio de un par
+    async fn price(&self, pair: &Pair) -> ProviderResult<Decimal>;
+
+    // Estadísticas de las últimas 24 horas
+    async fn ticker_24h(&self, pair: &Pair) -> ProviderResult<Ticker24h>;
+}
+
+// Convierte un precio recibido como texto en Decimal, devolviendo error en lugar de un valor inventado
+pub fn parse_decimal(value: &str) -> ProviderResult<Decimal> {
+    Decimal::from_str(value.trim())
+        .map_err(|_| ProviderError::MalformedNumber(value.to_string()))
+}
+
+// Cliente HTTP compartido por los proveedores
+fn http_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .build()
+        .expect("failed to build HTTP client")
+}
+
+// Crea un proveedor a partir de su nombre, con la URL base tomada de las variables de entorno
+pub fn provider_by_name(name: &str) -> ProviderResult<Arc<dyn PriceProvider>> {
+    let provider: Arc<dyn PriceProvider> = match name.trim().to_lowercase().as_str() {
+        "binance" => Arc::new(BinanceProvider::from_env()),
+        "coinbase" => Arc::new(CoinbaseProvider::from_env()),
+        "kraken" => Arc::new(KrakenProvider::from_env()),
+        "coingecko" => Arc::new(CoinGeckoProvider::from_env()),
+        other => return Err(ProviderError::Config(format!("unknown price provider '{}'", other))),
+    };
+    Ok(provider)
+}
+
+// Devuelve la cadena de proveedores configurada en PRICE_PROVIDERS (p. ej. "binance,kraken,coinbase"),
+// en orden de preferencia. Por compatibilidad también se acepta PRICE_PROVIDER con un único nombre.
+pub fn providers_from_env() -> ProviderResult<Vec<Arc<dyn PriceProvider>>> {
+    let names = crate::config::env_list("PRICE_PROVIDERS")
+        .or_else(|| crate::config::env_list("PRICE_PROVIDER"))
+        .unwrap_or(vec!["binance".to_string()]);
+    if names.is_empty() {
+        return Err(ProviderError::Config("PRICE_PROVIDERS does not contain any provider".to_string()));
+    }
+    names.iter().map(|name| provider_by_name(name)).collect()
+}
+
+// Levanta un servidor HTTP local con las rutas indicadas y devuelve su URL base, para probar
+// los proveedores contra respuestas grabadas sin salir a la red
+#[cfg(test)]
+pub async fn stub_server(router: axum::Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    format!("http://{}", address)
+}
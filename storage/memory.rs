@@ -0,0 +1,217 @@
+// Almacenamiento en memoria, para pruebas o despliegues sin disco
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use teloxide::types::{ChatId, UserId};
+
+use super::{NewAlert, NewDigest, NewTrade, Storage, StorageResult};
+use crate::alerts::PriceAlert;
+use crate::digest::Digest;
+use crate::gains::Trade;
+use crate::portfolio::Holding;
+use crate::providers::Pair;
+
+#[derive(Default)]
+struct Data {
+    chats: BTreeSet<i64>,
+    watchlists: HashMap<i64, Vec<Pair>>,
+    next_alert_id: u64,
+    alerts: Vec<PriceAlert>,
+    holdings: HashMap<u64, Vec<Holding>>,
+    next_trade_id: u64,
+    trades: HashMap<u64, Vec<Trade>>,
+    chat_settings: HashMap<(i64, String), String>,
+    user_settings: HashMap<(u64, String), String>,
+    next_digest_id: u64,
+    digests: Vec<Digest>,
+}
+
+#[derive(Default)]
+pub struct MemoryStorage {
+    data: Mutex<Data>,
+}
+
+impl Storage for MemoryStorage {
+    fn migrate(&self) -> StorageResult<()> {
+        Ok(())
+    }
+
+    fn touch_chat(&self, chat_id: ChatId) -> StorageResult<()> {
+        self.data.lock().unwrap().chats.insert(chat_id.0);
+        Ok(())
+    }
+
+    fn watchlist(&self, chat_id: ChatId) -> StorageResult<Vec<Pair>> {
+        Ok(self.data.lock().unwrap().watchlists.get(&chat_id.0).cloned().unwrap_or_default())
+    }
+
+    fn add_to_watchlist(&self, chat_id: ChatId, pair: &Pair) -> StorageResult<bool> {
+        let mut data = self.data.lock().unwrap();
+        let list = data.watchlists.entry(chat_id.0).or_default();
+        if list.contains(pair) {
+            return Ok(false);
+        }
+        list.push(pair.clone());
+        Ok(true)
+    }
+
+    fn remove_from_watchlist(&self, chat_id: ChatId, pair: &Pair) -> StorageResult<bool> {
+        let mut data = self.data.lock().unwrap();
+        let Some(list) = data.watchlists.get_mut(&chat_id.0) else {
+            return Ok(false);
+        };
+        let before = list.len();
+        list.retain(|p| p != pair);
+        Ok(list.len() != before)
+    }
+
+    fn watched_pairs(&self) -> StorageResult<Vec<Pair>> {
+        let data = self.data.lock().unwrap();
+        let pairs: BTreeSet<Pair> = data.watchlists.values().flatten().cloned().collect();
+        Ok(pairs.into_iter().collect())
+    }
+
+    fn insert_alert(&self, alert: NewAlert) -> StorageResult<PriceAlert> {
+        let mut data = self.data.lock().unwrap();
+        data.next_alert_id += 1;
+        let alert = PriceAlert {
+            id: data.next_alert_id,
+            chat_id: alert.chat_id,
+            pair: alert.pair,
+            condition: alert.condition,
+            armed: alert.armed,
+        };
+        data.alerts.push(alert.clone());
+        Ok(alert)
+    }
+
+    fn set_alert_armed(&self, id: u64, armed: bool) -> StorageResult<()> {
+        if let Some(alert) = self.data.lock().unwrap().alerts.iter_mut().find(|a| a.id == id) {
+            alert.armed = armed;
+        }
+        Ok(())
+    }
+
+    fn delete_alert(&self, chat_id: ChatId, id: u64) -> StorageResult<bool> {
+        let mut data = self.data.lock().unwrap();
+        let before = data.alerts.len();
+        data.alerts.retain(|a| !(a.chat_id == chat_id && a.id == id));
+        Ok(data.alerts.len() != before)
+    }
+
+    fn alerts(&self) -> StorageResult<Vec<PriceAlert>> {
+        Ok(self.data.lock().unwrap().alerts.clone())
+    }
+
+    fn holdings(&self, user_id: UserId) -> StorageResult<Vec<Holding>> {
+        let mut holdings = self.data.lock().unwrap().holdings.get(&user_id.0).cloned().unwrap_or_default();
+        holdings.sort_by(|a, b| a.pair.cmp(&b.pair));
+        Ok(holdings)
+    }
+
+    fn save_holding(&self, user_id: UserId, holding: &Holding) -> StorageResult<()> {
+        let mut data = self.data.lock().unwrap();
+        let holdings = data.holdings.entry(user_id.0).or_default();
+        match holdings.iter_mut().find(|h| h.pair == holding.pair) {
+            Some(current) => *current = holding.clone(),
+            None => holdings.push(holding.clone()),
+        }
+        Ok(())
+    }
+
+    fn delete_holding(&self, user_id: UserId, pair: &Pair) -> StorageResult<bool> {
+        let mut data = self.data.lock().unwrap();
+        let Some(holdings) = data.holdings.get_mut(&user_id.0) else {
+            return Ok(false);
+        };
+        let before = holdings.len();
+        holdings.retain(|h| &h.pair != pair);
+        Ok(holdings.len() != before)
+    }
+
+    fn trades(&self, user_id: UserId) -> StorageResult<Vec<Trade>> {
+        let mut trades = self.data.lock().unwrap().trades.get(&user_id.0).cloned().unwrap_or_default();
+        trades.sort_by_key(|trade| (trade.time, trade.id));
+        Ok(trades)
+    }
+
+    fn insert_trade(&self, user_id: UserId, trade: NewTrade) -> StorageResult<Trade> {
+        let mut data = self.data.lock().unwrap();
+        data.next_trade_id += 1;
+        let trade = Trade {
+            id: data.next_trade_id,
+            time: trade.time,
+            kind: trade.kind,
+            pair: trade.pair,
+            quantity: trade.quantity,
+            price: trade.price,
+            fee: trade.fee,
+        };
+        data.trades.entry(user_id.0).or_default().push(trade.clone());
+        Ok(trade)
+    }
+
+    fn insert_trades(&self, user_id: UserId, trades: Vec<NewTrade>) -> StorageResult<()> {
+        for trade in trades {
+            self.insert_trade(user_id, trade)?;
+        }
+        Ok(())
+    }
+
+    fn insert_digest(&self, digest: NewDigest) -> StorageResult<Digest> {
+        let mut data = self.data.lock().unwrap();
+        data.next_digest_id += 1;
+        let digest = Digest {
+            id: data.next_digest_id,
+            chat_id: digest.chat_id,
+            schedule: digest.schedule,
+            time: digest.time,
+            timezone: digest.timezone,
+            pairs: digest.pairs,
+            next_run: digest.next_run,
+        };
+        data.digests.push(digest.clone());
+        Ok(digest)
+    }
+
+    fn set_digest_next_run(&self, id: u64, next_run: DateTime<Utc>) -> StorageResult<()> {
+        if let Some(digest) = self.data.lock().unwrap().digests.iter_mut().find(|d| d.id == id) {
+            digest.next_run = next_run;
+        }
+        Ok(())
+    }
+
+    fn delete_digest(&self, chat_id: ChatId, id: u64) -> StorageResult<bool> {
+        let mut data = self.data.lock().unwrap();
+        let before = data.digests.len();
+        data.digests.retain(|d| !(d.chat_id == chat_id && d.id == id));
+        Ok(data.digests.len() != before)
+    }
+
+    fn digests(&self) -> StorageResult<Vec<Digest>> {
+        Ok(self.data.lock().unwrap().digests.clone())
+    }
+
+    fn chat_setting(&self, chat_id: ChatId, key: &str) -> StorageResult<Option<String>> {
+        Ok(self.data.lock().unwrap().chat_settings.get(&(chat_id.0, key.to_string())).cloned())
+    }
+
+    fn set_chat_setting(&self, chat_id: ChatId, key: &str, value: &str) -> StorageResult<()> {
+        self.data.lock().unwrap().chat_settings.insert((chat_id.0, key.to_string()), value.to_string());
+        Ok(())
+    }
+
+    fn remove_chat_setting(&self, chat_id: ChatId, key: &str) -> StorageResult<bool> {
+        Ok(self.data.lock().unwrap().chat_settings.remove(&(chat_id.0, key.to_string())).is_some())
+    }
+
+    fn user_setting(&self, user_id: UserId, key: &str) -> StorageResult<Option<String>> {
+        Ok(self.data.lock().unwrap().user_settings.get(&(user_id.0, key.to_string())).cloned())
+    }
+
+    fn set_user_setting(&self, user_id: UserId, key: &str, value: &str) -> StorageResult<()> {
+        self.data.lock().unwrap().user_settings.insert((user_id.0, key.to_string()), value.to_string());
+        Ok(())
+    }
+}
@@ -0,0 +1,222 @@
+// Capa de persistencia: guarda chats, alertas, carteras, operaciones, resúmenes y ajustes para que sobrevivan a un reinicio del contenedor.
+// Hay una implementación sobre SQLite para producción y otra en memoria para pruebas.
+use std::sync::Arc;
+
+use chrono::{DateTime, NaiveTime, Utc};
+use chrono_tz::Tz;
+use rust_decimal::Decimal;
+
+use teloxide::types::{ChatId, UserId};
+
+use crate::alerts::{Condition, PriceAlert};
+use crate::digest::{Digest, Schedule};
+use crate::gains::{Trade, TradeKind};
+use crate::portfolio::Holding;
+use crate::providers::Pair;
+
+mod memory;
+mod sqlite;
+
+pub use memory::MemoryStorage;
+pub use sqlite::SqliteStorage;
+
+pub type StorageError = Box<dyn std::error::Error + Send + Sync>;
+pub type StorageResult<T> = Result<T, StorageError>;
+
+// Alerta todavía sin id; el almacenamiento asigna el id al guardarla
+#[derive(Debug, Clone)]
+pub struct NewAlert {
+    pub chat_id: ChatId,
+    pub pair: Pair,
+    pub condition: Condition,
+    pub armed: bool,
+}
+
+// Operación todavía sin id, igual que NewAlert
+#[derive(Debug, Clone)]
+pub struct NewTrade {
+    pub time: DateTime<Utc>,
+    pub kind: TradeKind,
+    pub pair: Pair,
+    pub quantity: Decimal,
+    pub price: Decimal,
+    pub fee: Decimal,
+}
+
+// Resumen programado todavía sin id
+#[derive(Debug, Clone)]
+pub struct NewDigest {
+    pub chat_id: ChatId,
+    pub schedule: Schedule,
+    pub time: NaiveTime,
+    pub timezone: Tz,
+    pub pairs: Vec<Pair>,
+    pub next_run: DateTime<Utc>,
+}
+
+pub trait Storage: Send + Sync {
+    // Aplica las migraciones de esquema pendientes
+    fn migrate(&self) -> StorageResult<()>;
+
+    // Registra un chat (o actualiza la fecha en que se vio por última vez)
+    fn touch_chat(&self, chat_id: ChatId) -> StorageResult<()>;
+
+    // Lista de seguimiento de cada chat; add/remove devuelven false si no había nada que cambiar
+    fn watchlist(&self, chat_id: ChatId) -> StorageResult<Vec<Pair>>;
+    fn add_to_watchlist(&self, chat_id: ChatId, pair: &Pair) -> StorageResult<bool>;
+    fn remove_from_watchlist(&self, chat_id: ChatId, pair: &Pair) -> StorageResult<bool>;
+    // Pares distintos que aparecen en alguna lista de seguimiento
+    fn watched_pairs(&self) -> StorageResult<Vec<Pair>>;
+
+    // Alertas de precio
+    fn insert_alert(&self, alert: NewAlert) -> StorageResult<PriceAlert>;
+    fn set_alert_armed(&self, id: u64, armed: bool) -> StorageResult<()>;
+    fn delete_alert(&self, chat_id: ChatId, id: u64) -> StorageResult<bool>;
+    fn alerts(&self) -> StorageResult<Vec<PriceAlert>>;
+
+    // Cartera de cada usuario; save_holding crea o reemplaza la posición del par
+    fn holdings(&self, user_id: UserId) -> StorageResult<Vec<Holding>>;
+    fn save_holding(&self, user_id: UserId, holding: &Holding) -> StorageResult<()>;
+    fn delete_holding(&self, user_id: UserId, pair: &Pair) -> StorageResult<bool>;
+
+    // Registro de operaciones de cada usuario, ordenado por fecha
+    fn trades(&self, user_id: UserId) -> StorageResult<Vec<Trade>>;
+    fn insert_trade(&self, user_id: UserId, trade: NewTrade) -> StorageResult<Trade>;
+    // Guarda varias operaciones de una vez: o se guardan todas o ninguna
+    fn insert_trades(&self, user_id: UserId, trades: Vec<NewTrade>) -> StorageResult<()>;
+
+    // Resúmenes de mercado programados; set_digest_next_run guarda el siguiente envío tras cada uno
+    fn insert_digest(&self, digest: NewDigest) -> StorageResult<Digest>;
+    fn set_digest_next_run(&self, id: u64, next_run: DateTime<Utc>) -> StorageResult<()>;
+    fn delete_digest(&self, chat_id: ChatId, id: u64) -> StorageResult<bool>;
+    fn digests(&self) -> StorageResult<Vec<Digest>>;
+
+    // Ajustes por chat (p. ej. el idioma elegido con /lang)
+    fn chat_setting(&self, chat_id: ChatId, key: &str) -> StorageResult<Option<String>>;
+    fn set_chat_setting(&self, chat_id: ChatId, key: &str, value: &str) -> StorageResult<()>;
+    fn remove_chat_setting(&self, chat_id: ChatId, key: &str) -> StorageResult<bool>;
+
+    // Ajustes por usuario (p. ej. el método de cálculo de ganancias)
+    fn user_setting(&self, user_id: UserId, key: &str) -> StorageResult<Option<String>>;
+    fn set_user_setting(&self, user_id: UserId, key: &str, value: &str) -> StorageResult<()>;
+}
+
+// Crea el almacenamiento configurado: STORAGE_BACKEND=sqlite (por defecto) usa el fichero de
+// DATABASE_PATH (por defecto cryptocat.db) y STORAGE_BACKEND=memory no guarda nada en disco
+pub fn storage_from_env() -> StorageResult<Arc<dyn Storage>> {
+    match std::env::var("STORAGE_BACKEND").unwrap_or("sqlite".to_string()).to_lowercase().as_str() {
+        "sqlite" => {
+            let path = std::env::var("DATABASE_PATH").unwrap_or("cryptocat.db".to_string());
+            log::info!("Using SQLite database at {}", path);
+            Ok(Arc::new(SqliteStorage::open(&path)?))
+        }
+        "memory" => {
+            log::warn!("Using in-memory storage, data will be lost on restart");
+            Ok(Arc::new(MemoryStorage::default()))
+        }
+        other => Err(format!("unknown storage backend '{}'", other).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::alerts::Direction;
+
+    // Los dos backends detrás del trait, ya migrados
+    fn backends() -> Vec<(&'static str, Box<dyn Storage>)> {
+        let sqlite = SqliteStorage::open(":memory:").unwrap();
+        let memory = MemoryStorage::default();
+        let backends: Vec<(&'static str, Box<dyn Storage>)> = vec![("sqlite", Box::new(sqlite)), ("memory", Box::new(memory))];
+        for (_, storage) in &backends {
+            storage.migrate().unwrap();
+        }
+        backends
+    }
+
+    fn d(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn alerts_round_trip() {
+        for (name, storage) in backends() {
+            let btc = Pair::new("BTC", "USDT");
+            let threshold = Condition::Threshold { direction: Direction::Above, threshold: d("70000.5") };
+            let movement = Condition::Move { percent: d("2.5"), window: Duration::from_secs(3600) };
+            let first = storage.insert_alert(NewAlert { chat_id: ChatId(1), pair: btc.clone(), condition: threshold.clone(), armed: true }).unwrap();
+            let second = storage.insert_alert(NewAlert { chat_id: ChatId(-100), pair: Pair::new("ETH", "BTC"), condition: movement.clone(), armed: false }).unwrap();
+            assert_ne!(first.id, second.id, "{}", name);
+
+            storage.set_alert_armed(first.id, false).unwrap();
+            let alerts = storage.alerts().unwrap();
+            let loaded: Vec<_> = alerts.iter().map(|a| (a.id, a.chat_id, a.pair.clone(), a.condition.clone(), a.armed)).collect();
+            assert_eq!(loaded, vec![
+                (first.id, ChatId(1), btc, threshold, false),
+                (second.id, ChatId(-100), Pair::new("ETH", "BTC"), movement, false),
+            ], "{}", name);
+
+            // Solo se borra desde el chat al que pertenece la alerta
+            assert!(!storage.delete_alert(ChatId(1), second.id).unwrap(), "{}", name);
+            assert!(storage.delete_alert(ChatId(-100), second.id).unwrap(), "{}", name);
+            assert_eq!(storage.alerts().unwrap().len(), 1, "{}", name);
+        }
+    }
+
+    #[test]
+    fn watchlists_round_trip() {
+        for (name, storage) in backends() {
+            let (btc, eth, sol) = (Pair::new("BTC", "USDT"), Pair::new("ETH", "USDT"), Pair::new("SOL", "USDT"));
+            assert!(storage.add_to_watchlist(ChatId(1), &sol).unwrap(), "{}", name);
+            assert!(storage.add_to_watchlist(ChatId(1), &btc).unwrap(), "{}", name);
+            assert!(!storage.add_to_watchlist(ChatId(1), &btc).unwrap(), "{}", name);
+            assert!(storage.add_to_watchlist(ChatId(2), &eth).unwrap(), "{}", name);
+            assert!(storage.add_to_watchlist(ChatId(2), &btc).unwrap(), "{}", name);
+
+            // Cada chat conserva el orden en que se añadieron los pares
+            assert_eq!(storage.watchlist(ChatId(1)).unwrap(), vec![sol.clone(), btc.clone()], "{}", name);
+            assert_eq!(storage.watched_pairs().unwrap(), vec![btc.clone(), eth.clone(), sol.clone()], "{}", name);
+
+            assert!(storage.remove_from_watchlist(ChatId(1), &sol).unwrap(), "{}", name);
+            assert!(!storage.remove_from_watchlist(ChatId(1), &eth).unwrap(), "{}", name);
+            assert_eq!(storage.watchlist(ChatId(1)).unwrap(), vec![btc.clone()], "{}", name);
+            assert!(storage.watchlist(ChatId(3)).unwrap().is_empty(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn trades_round_trip() {
+        let at = |minute: i64| DateTime::from_timestamp(1_700_000_000 + minute * 60, 0).unwrap();
+        let trade = |minute: i64, kind: TradeKind, quantity: &str, price: &str, fee: &str| NewTrade {
+            time: at(minute),
+            kind,
+            pair: Pair::new("BTC", "USDT"),
+            quantity: d(quantity),
+            price: d(price),
+            fee: d(fee),
+        };
+        for (name, storage) in backends() {
+            let (alice, bob) = (UserId(10), UserId(20));
+            let sell = storage.insert_trade(alice, trade(30, TradeKind::Sell, "0.25", "68000", "1.5")).unwrap();
+            storage.insert_trades(alice, vec![
+                trade(0, TradeKind::Buy, "0.5", "65000.12345678", "0"),
+                trade(10, TradeKind::Fee, "0.0001", "0", "0"),
+            ]).unwrap();
+            storage.insert_trade(bob, trade(5, TradeKind::Buy, "1", "1", "0")).unwrap();
+
+            // Se devuelven en orden cronológico y sin perder precisión
+            let trades = storage.trades(alice).unwrap();
+            let loaded: Vec<_> = trades.iter().map(|t| (t.time, t.kind, t.quantity, t.price, t.fee)).collect();
+            assert_eq!(loaded, vec![
+                (at(0), TradeKind::Buy, d("0.5"), d("65000.12345678"), d("0")),
+                (at(10), TradeKind::Fee, d("0.0001"), d("0"), d("0")),
+                (at(30), TradeKind::Sell, d("0.25"), d("68000"), d("1.5")),
+            ], "{}", name);
+            assert_eq!(trades[2].id, sell.id, "{}", name);
+            assert_eq!(storage.trades(bob).unwrap().len(), 1, "{}", name);
+            assert!(storage.trades(UserId(30)).unwrap().is_empty(), "{}", name);
+        }
+    }
+}
@@ -0,0 +1,492 @@
+// Almacenamiento sobre SQLite. El esquema se versiona con PRAGMA user_version y las
+// migraciones pendientes se aplican al arrancar.
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveTime, SecondsFormat, Utc};
+use chrono_tz::Tz;
+use rusqlite::{params, Connection, OptionalExtension};
+use rust_decimal::Decimal;
+use teloxide::types::{ChatId, UserId};
+
+use super::{NewAlert, NewDigest, NewTrade, Storage, StorageResult};
+use crate::alerts::{Condition, Direction, PriceAlert};
+use crate::digest::{Digest, Schedule};
+use crate::gains::{Trade, TradeKind};
+use crate::portfolio::Holding;
+use crate::providers::Pair;
+
+// Migraciones en orden; la posición en la lista es la versión del esquema que alcanzan.
+// Nunca se modifica una migración ya publicada: los cambios van en una nueva entrada al final.
+const MIGRATIONS: &[&str] = &[
+    // 1: chats, ajustes por chat y alertas
+    "CREATE TABLE chats (
+        chat_id INTEGER PRIMARY KEY,
+        first_seen TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        last_seen TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+    CREATE TABLE chat_settings (
+        chat_id INTEGER NOT NULL,
+        key TEXT NOT NULL,
+        value TEXT NOT NULL,
+        PRIMARY KEY (chat_id, key)
+    );
+    CREATE TABLE alerts (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        chat_id INTEGER NOT NULL,
+        base TEXT NOT NULL,
+        quote TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        direction TEXT,
+        threshold TEXT,
+        percent TEXT,
+        window_secs INTEGER,
+        armed INTEGER NOT NULL,
+        created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+    CREATE INDEX alerts_chat_id ON alerts (chat_id);",
+    // 2: listas de seguimiento por chat
+    "CREATE TABLE watchlist (
+        chat_id INTEGER NOT NULL,
+        base TEXT NOT NULL,
+        quote TEXT NOT NULL,
+        added_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (chat_id, base, quote)
+    );",
+    // 3: carteras por usuario; cantidad y coste se guardan como texto para no perder precisión
+    "CREATE TABLE holdings (
+        user_id INTEGER NOT NULL,
+        base TEXT NOT NULL,
+        quote TEXT NOT NULL,
+        quantity TEXT NOT NULL,
+        cost TEXT NOT NULL,
+        updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (user_id, base, quote)
+    );",
+    // 4: registro de operaciones y ajustes por usuario
+    "CREATE TABLE trades (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        user_id INTEGER NOT NULL,
+        time TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        base TEXT NOT NULL,
+        quote TEXT NOT NULL,
+        quantity TEXT NOT NULL,
+        price TEXT NOT NULL,
+        fee TEXT NOT NULL
+    );
+    CREATE INDEX trades_user_id ON trades (user_id, time);
+    CREATE TABLE user_settings (
+        user_id INTEGER NOT NULL,
+        key TEXT NOT NULL,
+        value TEXT NOT NULL,
+        PRIMARY KEY (user_id, key)
+    );",
+    // 5: resúmenes de mercado programados; los pares se guardan como "BTC/USDT,ETH/USDT"
+    "CREATE TABLE digests (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        chat_id INTEGER NOT NULL,
+        schedule TEXT NOT NULL,
+        time TEXT NOT NULL,
+        timezone TEXT NOT NULL,
+        pairs TEXT NOT NULL,
+        next_run TEXT NOT NULL,
+        created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+    CREATE INDEX digests_chat_id ON digests (chat_id);",
+];
+
+pub struct SqliteStorage {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStorage {
+    pub fn open(path: &str) -> StorageResult<Self> {
+        let conn = Connection::open(path)?;
+        conn.busy_timeout(Duration::from_secs(5))?;
+        Ok(SqliteStorage { conn: Mutex::new(conn) })
+    }
+}
+
+// Columnas de la tabla alerts que describen la condición
+fn condition_columns(condition: &Condition) -> (&'static str, Option<String>, Option<String>, Option<String>, Option<i64>) {
+    match condition {
+        Condition::Threshold { direction, threshold } =>
+            ("threshold", Some(direction.to_string()), Some(threshold.to_string()), None, None),
+        Condition::Move { percent, window } =>
+            ("move", None, None, Some(percent.to_string()), Some(window.as_secs() as i64)),
+    }
+}
+
+fn parse_decimal_column(value: Option<String>) -> StorageResult<Decimal> {
+    let value = value.ok_or("missing decimal column")?;
+    Ok(Decimal::from_str(&value)?)
+}
+
+// Reconstruye una alerta a partir de una fila de la tabla alerts
+fn alert_from_row(row: &rusqlite::Row) -> StorageResult<PriceAlert> {
+    let kind: String = row.get("kind")?;
+    let condition = match kind.as_str() {
+        "threshold" => Condition::Threshold {
+            direction: row.get::<_, Option<String>>("direction")?.ok_or("missing direction")?.parse::<Direction>()?,
+            threshold: parse_decimal_column(row.get("threshold")?)?,
+        },
+        "move" => Condition::Move {
+            percent: parse_decimal_column(row.get("percent")?)?,
+            window: Duration::from_secs(row.get::<_, Option<i64>>("window_secs")?.ok_or("missing window")? as u64),
+        },
+        other => return Err(format!("unknown alert kind '{}'", other).into()),
+    };
+    Ok(PriceAlert {
+        id: row.get::<_, i64>("id")? as u64,
+        chat_id: ChatId(row.get("chat_id")?),
+        pair: Pair::new(&row.get::<_, String>("base")?, &row.get::<_, String>("quote")?),
+        condition,
+        armed: row.get("armed")?,
+    })
+}
+
+// Reconstruye una operación a partir de una fila de la tabla trades
+fn trade_from_row(row: &rusqlite::Row) -> StorageResult<Trade> {
+    Ok(Trade {
+        id: row.get::<_, i64>("id")? as u64,
+        time: row.get::<_, String>("time")?.parse::<DateTime<Utc>>()?,
+        kind: row.get::<_, String>("kind")?.parse::<TradeKind>()?,
+        pair: Pair::new(&row.get::<_, String>("base")?, &row.get::<_, String>("quote")?),
+        quantity: parse_decimal_column(row.get("quantity")?)?,
+        price: parse_decimal_column(row.get("price")?)?,
+        fee: parse_decimal_column(row.get("fee")?)?,
+    })
+}
+
+// Reconstruye un resumen a partir de una fila de la tabla digests
+fn digest_from_row(row: &rusqlite::Row) -> StorageResult<Digest> {
+    let pairs: String = row.get("pairs")?;
+    Ok(Digest {
+        id: row.get::<_, i64>("id")? as u64,
+        chat_id: ChatId(row.get("chat_id")?),
+        schedule: row.get::<_, String>("schedule")?.parse::<Schedule>()?,
+        time: NaiveTime::parse_from_str(&row.get::<_, String>("time")?, "%H:%M")?,
+        timezone: row.get::<_, String>("timezone")?.parse::<Tz>()?,
+        pairs: pairs.split(',').map(|pair| pair.parse::<Pair>()).collect::<Result<_, _>>()?,
+        next_run: row.get::<_, String>("next_run")?.parse::<DateTime<Utc>>()?,
+    })
+}
+
+// Inserta una fila en la tabla trades, dentro o fuera de una transacción
+fn insert_trade_row(conn: &Connection, user_id: UserId, trade: &NewTrade) -> StorageResult<()> {
+    conn.execute(
+        "INSERT INTO trades (user_id, time, kind, base, quote, quantity, price, fee)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![user_id.0 as i64, trade.time.to_rfc3339_opts(SecondsFormat::Secs, true), trade.kind.to_string(), trade.pair.base,
+            trade.pair.quote, trade.quantity.normalize().to_string(), trade.price.normalize().to_string(), trade.fee.normalize().to_string()],
+    )?;
+    Ok(())
+}
+
+impl Storage for SqliteStorage {
+    fn migrate(&self) -> StorageResult<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            let tx = conn.transaction()?;
+            tx.execute_batch(migration)?;
+            tx.pragma_update(None, "user_version", index + 1)?;
+            tx.commit()?;
+            log::info!("Applied database migration {}", index + 1);
+        }
+        Ok(())
+    }
+
+    fn touch_chat(&self, chat_id: ChatId) -> StorageResult<()> {
+        self.conn.lock().unwrap().execute(
+            "INSERT INTO chats (chat_id) VALUES (?1)
+             ON CONFLICT (chat_id) DO UPDATE SET last_seen = CURRENT_TIMESTAMP",
+            params![chat_id.0],
+        )?;
+        Ok(())
+    }
+
+    fn watchlist(&self, chat_id: ChatId) -> StorageResult<Vec<Pair>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT base, quote FROM watchlist WHERE chat_id = ?1 ORDER BY rowid")?;
+        let pairs = stmt.query_map(params![chat_id.0], |row| {
+            Ok(Pair::new(&row.get::<_, String>(0)?, &row.get::<_, String>(1)?))
+        })?.collect::<Result<Vec<_>, _>>()?;
+        Ok(pairs)
+    }
+
+    fn add_to_watchlist(&self, chat_id: ChatId, pair: &Pair) -> StorageResult<bool> {
+        let inserted = self.conn.lock().unwrap().execute(
+            "INSERT OR IGNORE INTO watchlist (chat_id, base, quote) VALUES (?1, ?2, ?3)",
+            params![chat_id.0, pair.base, pair.quote],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    fn remove_from_watchlist(&self, chat_id: ChatId, pair: &Pair) -> StorageResult<bool> {
+        let deleted = self.conn.lock().unwrap().execute(
+            "DELETE FROM watchlist WHERE chat_id = ?1 AND base = ?2 AND quote = ?3",
+            params![chat_id.0, pair.base, pair.quote],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    fn watched_pairs(&self) -> StorageResult<Vec<Pair>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT DISTINCT base, quote FROM watchlist ORDER BY base, quote")?;
+        let pairs = stmt.query_map([], |row| {
+            Ok(Pair::new(&row.get::<_, String>(0)?, &row.get::<_, String>(1)?))
+        })?.collect::<Result<Vec<_>, _>>()?;
+        Ok(pairs)
+    }
+
+    fn insert_alert(&self, alert: NewAlert) -> StorageResult<PriceAlert> {
+        let conn = self.conn.lock().unwrap();
+        let (kind, direction, threshold, percent, window_secs) = condition_columns(&alert.condition);
+        conn.execute(
+            "INSERT INTO alerts (chat_id, base, quote, kind, direction, threshold, percent, window_secs, armed)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![alert.chat_id.0, alert.pair.base, alert.pair.quote, kind, direction, threshold, percent, window_secs, alert.armed],
+        )?;
+        Ok(PriceAlert {
+            id: conn.last_insert_rowid() as u64,
+            chat_id: alert.chat_id,
+            pair: alert.pair,
+            condition: alert.condition,
+            armed: alert.armed,
+        })
+    }
+
+    fn set_alert_armed(&self, id: u64, armed: bool) -> StorageResult<()> {
+        self.conn.lock().unwrap().execute(
+            "UPDATE alerts SET armed = ?1 WHERE id = ?2",
+            params![armed, id as i64],
+        )?;
+        Ok(())
+    }
+
+    fn delete_alert(&self, chat_id: ChatId, id: u64) -> StorageResult<bool> {
+        let deleted = self.conn.lock().unwrap().execute(
+            "DELETE FROM alerts WHERE chat_id = ?1 AND id = ?2",
+            params![chat_id.0, id as i64],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    fn alerts(&self) -> StorageResult<Vec<PriceAlert>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT * FROM alerts ORDER BY id")?;
+        let mut rows = stmt.query([])?;
+        let mut alerts = Vec::new();
+        while let Some(row) = rows.next()? {
+            alerts.push(alert_from_row(row)?);
+        }
+        Ok(alerts)
+    }
+
+    fn holdings(&self, user_id: UserId) -> StorageResult<Vec<Holding>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT base, quote, quantity, cost FROM holdings WHERE user_id = ?1 ORDER BY base, quote")?;
+        let mut rows = stmt.query(params![user_id.0 as i64])?;
+        let mut holdings = Vec::new();
+        while let Some(row) = rows.next()? {
+            holdings.push(Holding {
+                pair: Pair::new(&row.get::<_, String>(0)?, &row.get::<_, String>(1)?),
+                quantity: parse_decimal_column(row.get(2)?)?,
+                cost: parse_decimal_column(row.get(3)?)?,
+            });
+        }
+        Ok(holdings)
+    }
+
+    fn save_holding(&self, user_id: UserId, holding: &Holding) -> StorageResult<()> {
+        self.conn.lock().unwrap().execute(
+            "INSERT INTO holdings (user_id, base, quote, quantity, cost) VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT (user_id, base, quote) DO UPDATE
+             SET quantity = excluded.quantity, cost = excluded.cost, updated_at = CURRENT_TIMESTAMP",
+            params![user_id.0 as i64, holding.pair.base, holding.pair.quote, holding.quantity.normalize().to_string(), holding.cost.normalize().to_string()],
+        )?;
+        Ok(())
+    }
+
+    fn delete_holding(&self, user_id: UserId, pair: &Pair) -> StorageResult<bool> {
+        let deleted = self.conn.lock().unwrap().execute(
+            "DELETE FROM holdings WHERE user_id = ?1 AND base = ?2 AND quote = ?3",
+            params![user_id.0 as i64, pair.base, pair.quote],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    fn trades(&self, user_id: UserId) -> StorageResult<Vec<Trade>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT * FROM trades WHERE user_id = ?1 ORDER BY time, id")?;
+        let mut rows = stmt.query(params![user_id.0 as i64])?;
+        let mut trades = Vec::new();
+        while let Some(row) = rows.next()? {
+            trades.push(trade_from_row(row)?);
+        }
+        Ok(trades)
+    }
+
+    fn insert_trade(&self, user_id: UserId, trade: NewTrade) -> StorageResult<Trade> {
+        let conn = self.conn.lock().unwrap();
+        insert_trade_row(&conn, user_id, &trade)?;
+        Ok(Trade {
+            id: conn.last_insert_rowid() as u64,
+            time: trade.time,
+            kind: trade.kind,
+            pair: trade.pair,
+            quantity: trade.quantity,
+            price: trade.price,
+            fee: trade.fee,
+        })
+    }
+
+    fn insert_trades(&self, user_id: UserId, trades: Vec<NewTrade>) -> StorageResult<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        for trade in &trades {
+            insert_trade_row(&tx, user_id, trade)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn insert_digest(&self, digest: NewDigest) -> StorageResult<Digest> {
+        let conn = self.conn.lock().unwrap();
+        let pairs = digest.pairs.iter().map(|pair| pair.to_string()).collect::<Vec<_>>().join(",");
+        conn.execute(
+            "INSERT INTO digests (chat_id, schedule, time, timezone, pairs, next_run) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![digest.chat_id.0, digest.schedule.to_string(), digest.time.format("%H:%M").to_string(),
+                digest.timezone.name(), pairs, digest.next_run.to_rfc3339_opts(SecondsFormat::Secs, true)],
+        )?;
+        Ok(Digest {
+            id: conn.last_insert_rowid() as u64,
+            chat_id: digest.chat_id,
+            schedule: digest.schedule,
+            time: digest.time,
+            timezone: digest.timezone,
+            pairs: digest.pairs,
+            next_run: digest.next_run,
+        })
+    }
+
+    fn set_digest_next_run(&self, id: u64, next_run: DateTime<Utc>) -> StorageResult<()> {
+        self.conn.lock().unwrap().execute(
+            "UPDATE digests SET next_run = ?1 WHERE id = ?2",
+            params![next_run.to_rfc3339_opts(SecondsFormat::Secs, true), id as i64],
+        )?;
+        Ok(())
+    }
+
+    fn delete_digest(&self, chat_id: ChatId, id: u64) -> StorageResult<bool> {
+        let deleted = self.conn.lock().unwrap().execute(
+            "DELETE FROM digests WHERE chat_id = ?1 AND id = ?2",
+            params![chat_id.0, id as i64],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    fn digests(&self) -> StorageResult<Vec<Digest>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT * FROM digests ORDER BY id")?;
+        let mut rows = stmt.query([])?;
+        let mut digests = Vec::new();
+        while let Some(row) = rows.next()? {
+            digests.push(digest_from_row(row)?);
+        }
+        Ok(digests)
+    }
+
+    fn chat_setting(&self, chat_id: ChatId, key: &str) -> StorageResult<Option<String>> {
+        let value = self.conn.lock().unwrap().query_row(
+            "SELECT value FROM chat_settings WHERE chat_id = ?1 AND key = ?2",
+            params![chat_id.0, key],
+            |row| row.get(0),
+        ).optional()?;
+        Ok(value)
+    }
+
+    fn set_chat_setting(&self, chat_id: ChatId, key: &str, value: &str) -> StorageResult<()> {
+        self.conn.lock().unwrap().execute(
+            "INSERT INTO chat_settings (chat_id, key, value) VALUES (?1, ?2, ?3)
+             ON CONFLICT (chat_id, key) DO UPDATE SET value = excluded.value",
+            params![chat_id.0, key, value],
+        )?;
+        Ok(())
+    }
+
+    fn remove_chat_setting(&self, chat_id: ChatId, key: &str) -> StorageResult<bool> {
+        let removed = self.conn.lock().unwrap().execute(
+            "DELETE FROM chat_settings WHERE chat_id = ?1 AND key = ?2",
+            params![chat_id.0, key],
+        )?;
+        Ok(removed > 0)
+    }
+
+    fn user_setting(&self, user_id: UserId, key: &str) -> StorageResult<Option<String>> {
+        let value = self.conn.lock().unwrap().query_row(
+            "SELECT value FROM user_settings WHERE user_id = ?1 AND key = ?2",
+            params![user_id.0 as i64, key],
+            |row| row.get(0),
+        ).optional()?;
+        Ok(value)
+    }
+
+    fn set_user_setting(&self, user_id: UserId, key: &str, value: &str) -> StorageResult<()> {
+        self.conn.lock().unwrap().execute(
+            "INSERT INTO user_settings (user_id, key, value) VALUES (?1, ?2, ?3)
+             ON CONFLICT (user_id, key) DO UPDATE SET value = excluded.value",
+            params![user_id.0 as i64, key, value],
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user_version(storage: &SqliteStorage) -> usize {
+        storage.conn.lock().unwrap().query_row("PRAGMA user_version", [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn migrations_create_the_schema_once() {
+        let storage = SqliteStorage::open(":memory:").unwrap();
+        assert_eq!(user_version(&storage), 0);
+        storage.migrate().unwrap();
+        assert_eq!(user_version(&storage), MIGRATIONS.len());
+
+        let tables: Vec<String> = {
+            let conn = storage.conn.lock().unwrap();
+            let mut stmt = conn.prepare("SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name").unwrap();
+            stmt.query_map([], |row| row.get(0)).unwrap().collect::<Result<_, _>>().unwrap()
+        };
+        assert_eq!(tables, ["alerts", "chat_settings", "chats", "digests", "holdings", "trades", "user_settings", "watchlist"]);
+
+        // Volver a migrar no aplica nada ni pierde datos
+        storage.add_to_watchlist(ChatId(1), &Pair::new("BTC", "USDT")).unwrap();
+        storage.migrate().unwrap();
+        assert_eq!(user_version(&storage), MIGRATIONS.len());
+        assert_eq!(storage.watchlist(ChatId(1)).unwrap(), vec![Pair::new("BTC", "USDT")]);
+    }
+
+    #[test]
+    fn pending_migrations_are_applied_from_the_stored_version() {
+        let storage = SqliteStorage::open(":memory:").unwrap();
+        {
+            let conn = storage.conn.lock().unwrap();
+            conn.execute_batch(MIGRATIONS[0]).unwrap();
+            conn.pragma_update(None, "user_version", 1).unwrap();
+            conn.execute("INSERT INTO chat_settings (chat_id, key, value) VALUES (1, 'language', 'es')", []).unwrap();
+        }
+        storage.migrate().unwrap();
+        assert_eq!(user_version(&storage), MIGRATIONS.len());
+        assert_eq!(storage.chat_setting(ChatId(1), "language").unwrap().as_deref(), Some("es"));
+        assert!(storage.trades(UserId(1)).unwrap().is_empty());
+    }
+}
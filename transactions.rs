@@ -0,0 +1,448 @@
+// Exportación e importación CSV del registro de operaciones (/export y /import). Se importan el
+// historial de operaciones de Binance (el formato actual y el antiguo) y el formato genérico que
+// genera /export. Todas las filas se validan antes de guardar nada: si alguna falla no se importa
+// ninguna y se responde con el error de cada línea.
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, SecondsFormat, SubsecRound, Utc};
+use rust_decimal::Decimal;
+use teloxide::types::UserId;
+
+use crate::gains::{self, GainsError, Trade, TradeKind};
+use crate::i18n::{self, field_error, parse_number, Locale};
+use crate::ledger::{cost_method, gains_error};
+use crate::prices::PriceService;
+use crate::providers::Pair;
+use crate::storage::{NewTrade, Storage, StorageResult};
+
+// Tamaño máximo del fichero y número máximo de filas que se aceptan
+pub const MAX_IMPORT_BYTES: u32 = 1024 * 1024;
+const MAX_IMPORT_ROWS: usize = 10_000;
+// Errores de línea que se muestran como mucho en la respuesta
+const MAX_REPORTED_ERRORS: usize = 20;
+
+// Columnas del formato genérico, en el orden en que las escribe /export
+const GENERIC_HEADER: [&str; 8] = ["id", "date", "type", "base", "quote", "quantity", "price", "fee"];
+
+// CSV con todas las operaciones del usuario en el formato genérico
+pub fn export_csv(trades: &[Trade]) -> Result<Vec<u8>, csv::Error> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(GENERIC_HEADER)?;
+    for trade in trades {
+        writer.write_record([
+            trade.id.to_string(),
+            trade.time.to_rfc3339_opts(SecondsFormat::Secs, true),
+            trade.kind.to_string(),
+            trade.pair.base.clone(),
+            trade.pair.quote.clone(),
+            trade.quantity.normalize().to_string(),
+            trade.price.normalize().to_string(),
+            trade.fee.normalize().to_string(),
+        ])?;
+    }
+    writer.into_inner().map_err(|err| err.into_error().into())
+}
+
+// Formatos de fichero reconocidos por su cabecera
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Generic,
+    // Date(UTC),Pair,Side,Price,Executed,Amount,Fee con el activo pegado a cada importe ("0.5BTC")
+    BinanceTrades,
+    // Date(UTC),Market,Type,Price,Amount,Total,Fee,Fee Coin del exportador antiguo
+    BinanceLegacy,
+}
+
+impl Format {
+    // Clave del nombre del formato en el catálogo
+    fn name_key(&self) -> &'static str {
+        match self {
+            Format::Generic => "import.format.generic",
+            Format::BinanceTrades => "import.format.binance",
+            Format::BinanceLegacy => "import.format.binance_legacy",
+        }
+    }
+
+    fn detect(columns: &HashMap<String, usize>) -> Option<Format> {
+        let has = |names: &[&str]| names.iter().all(|name| columns.contains_key(*name));
+        if has(&["date", "type", "base", "quote", "quantity", "price"]) {
+            Some(Format::Generic)
+        } else if has(&["date(utc)", "pair", "side", "price", "executed", "fee"]) {
+            Some(Format::BinanceTrades)
+        } else if has(&["date(utc)", "market", "type", "price", "amount", "fee", "fee coin"]) {
+            Some(Format::BinanceLegacy)
+        } else {
+            None
+        }
+    }
+}
+
+// Par de una fila: completo, o solo el símbolo del mercado ("BTCUSDT") cuando el fichero no lo separa
+enum Market {
+    Pair(Pair),
+    Symbol(String),
+}
+
+// Fila leída del fichero, antes de resolver el par y repartir la comisión
+struct Row {
+    line: u64,
+    time: DateTime<Utc>,
+    kind: TradeKind,
+    market: Market,
+    quantity: Decimal,
+    price: Decimal,
+    // Comisión y activo en que se pagó
+    fee: Option<(Decimal, String)>,
+}
+
+// Fecha en RFC 3339 o en "YYYY-MM-DD HH:MM:SS" / "YYYY-MM-DD" (en UTC)
+fn parse_time(locale: Locale, value: &str) -> Result<DateTime<Utc>, String> {
+    let value = value.trim();
+    DateTime::parse_from_rfc3339(value).map(|time| time.with_timezone(&Utc))
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").map(|time| time.and_utc()))
+        .or_else(|_| NaiveDate::parse_from_str(value, "%Y-%m-%d").map(|date| date.and_hms_opt(0, 0, 0).unwrap().and_utc()))
+        .map_err(|_| i18n::t(locale, "import.invalid_date", &[("value", &value)]))
+}
+
+// Número de las columnas de Binance, que agrupan los miles con comas ("21,000.5"). Solo se quitan
+// las comas que separan grupos de tres cifras en la parte entera; cualquier otra es un error.
+fn parse_binance_number(locale: Locale, value: &str, field: &str) -> Result<Decimal, String> {
+    let value = value.trim();
+    let (integer, fraction) = value.split_once('.').unwrap_or((value, ""));
+    let mut groups = integer.split(',');
+    let first = groups.next().unwrap_or_default();
+    let grouped = (1..=3).contains(&first.len()) && groups.all(|group| group.len() == 3);
+    if fraction.contains(',') || integer.contains(',') && !grouped {
+        return Err(field_error(locale, "import.thousands", value, field));
+    }
+    parse_number(locale, &value.replace(',', ""), field)
+}
+
+// Importe con el activo pegado detrás, como los escribe Binance: "0.5BTC" -> (0.5, "BTC")
+fn parse_amount_with_asset(locale: Locale, value: &str, field: &str) -> Result<(Decimal, String), String> {
+    let value = value.trim();
+    let split = value.find(|c: char| !(c.is_ascii_digit() || c == '.' || c == ',')).unwrap_or(value.len());
+    let (number, asset) = value.split_at(split);
+    if asset.is_empty() {
+        return Err(field_error(locale, "import.no_asset", value, field));
+    }
+    Ok((parse_binance_number(locale, number, field)?, asset.trim().to_uppercase()))
+}
+
+fn parse_side(locale: Locale, value: &str) -> Result<TradeKind, String> {
+    match value.trim().parse::<TradeKind>() {
+        Ok(kind) => Ok(kind),
+        Err(_) => Err(i18n::t(locale, "import.invalid_type", &[("value", &value.trim())])),
+    }
+}
+
+// Lee una fila según el formato; `get` devuelve el valor de una columna por su nombre
+fn parse_row(locale: Locale, format: Format, line: u64, get: impl Fn(&str) -> Option<String>) -> Result<Row, String> {
+    let column = |name: &str| get(name).filter(|value| !value.trim().is_empty())
+        .ok_or_else(|| i18n::t(locale, "import.missing", &[("column", &name)]));
+    match format {
+        Format::Generic => {
+            let kind = parse_side(locale, &column("type")?)?;
+            let pair = Pair::new(column("base")?.trim(), column("quote")?.trim());
+            let quantity = parse_number(locale, &column("quantity")?, "quantity")?;
+            let price = match kind {
+                TradeKind::Fee => get("price").map(|price| parse_number(locale, &price, "price")).transpose()?.unwrap_or_default(),
+                _ => parse_number(locale, &column("price")?, "price")?,
+            };
+            let fee = get("fee").filter(|fee| !fee.trim().is_empty())
+                .map(|fee| parse_number(locale, &fee, "fee"))
+                .transpose()?
+                .map(|fee| (fee, pair.quote.clone()));
+            Ok(Row { line, time: parse_time(locale, &column("date")?)?, kind, market: Market::Pair(pair), quantity, price, fee })
+        }
+        Format::BinanceTrades => {
+            let kind = parse_side(locale, &column("side")?)?;
+            let (quantity, base) = parse_amount_with_asset(locale, &column("executed")?, "executed")?;
+            let symbol = column("pair")?.trim().to_uppercase();
+            // El activo base va pegado a la cantidad, así que la cotización es lo que sobra del símbolo
+            let market = match symbol.strip_prefix(&base) {
+                Some(quote) if !quote.is_empty() => Market::Pair(Pair::new(&base, quote)),
+                _ => Market::Symbol(symbol),
+            };
+            Ok(Row {
+                line,
+                time: parse_time(locale, &column("date(utc)")?)?,
+                kind,
+                market,
+                quantity,
+                price: parse_binance_number(locale, &column("price")?, "price")?,
+                fee: Some(parse_amount_with_asset(locale, &column("fee")?, "fee")?),
+            })
+        }
+        Format::BinanceLegacy => Ok(Row {
+            line,
+            time: parse_time(locale, &column("date(utc)")?)?,
+            kind: parse_side(locale, &column("type")?)?,
+            market: Market::Symbol(column("market")?.trim().to_uppercase()),
+            quantity: parse_binance_number(locale, &column("amount")?, "amount")?,
+            price: parse_binance_number(locale, &column("price")?, "price")?,
+            fee: Some((parse_binance_number(locale, &column("fee")?, "fee")?, column("fee coin")?.trim().to_uppercase())),
+        }),
+    }
+    .and_then(|row| match row.kind {
+        TradeKind::Fee if format != Format::Generic => Err(i18n::t(locale, "import.binance_fee", &[])),
+        _ if row.quantity.is_zero() => Err(i18n::t(locale, "import.zero_quantity", &[])),
+        _ => Ok(row),
+    })
+}
+
+// Lee y valida el fichero completo; devuelve las filas o la lista de errores por línea
+fn read_rows(locale: Locale, data: &[u8]) -> Result<(Format, Vec<Row>), Vec<String>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(data);
+    let headers = reader.headers().map_err(|err| vec![i18n::t(locale, "import.header_failed", &[("error", &err)])])?.clone();
+    let columns: HashMap<String, usize> = headers.iter().enumerate()
+        .map(|(index, name)| (name.trim_start_matches('\u{feff}').trim().to_lowercase(), index))
+        .collect();
+    let format = Format::detect(&columns)
+        .ok_or_else(|| vec![i18n::t(locale, "import.unknown_format", &[("columns", &headers.iter().collect::<Vec<_>>().join(", "))])])?;
+
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+    for record in reader.records() {
+        let record = match record {
+            Ok(record) => record,
+            Err(err) => {
+                errors.push(line_error(locale, err.position().map(|p| p.line()).unwrap_or_default(), &err));
+                continue;
+            }
+        };
+        let line = record.position().map(|p| p.line()).unwrap_or_default();
+        // Las líneas vacías al final de algunos ficheros no cuentan
+        if record.iter().all(|value| value.is_empty()) {
+            continue;
+        }
+        if rows.len() + errors.len() >= MAX_IMPORT_ROWS {
+            return Err(vec![i18n::t(locale, "import.too_many_rows", &[("max", &MAX_IMPORT_ROWS)])]);
+        }
+        let get = |name: &str| columns.get(name).and_then(|index| record.get(*index)).map(str::to_string);
+        match parse_row(locale, format, line, get) {
+            Ok(row) => rows.push(row),
+            Err(err) => errors.push(line_error(locale, line, &err)),
+        }
+    }
+    if rows.is_empty() && errors.is_empty() {
+        errors.push(i18n::t(locale, "import.no_trades", &[]));
+    }
+    if errors.is_empty() { Ok((format, rows)) } else { Err(errors) }
+}
+
+fn line_error(locale: Locale, line: u64, error: &(dyn std::fmt::Display + Sync)) -> String {
+    i18n::t(locale, "import.line", &[("line", &line), ("error", error)])
+}
+
+// Respuesta con los errores encontrados, recortada para que quepa en un mensaje
+fn errors_text(locale: Locale, errors: &[String]) -> String {
+    let mut lines = vec![i18n::tn(locale, "import.errors", errors.len(), &[])];
+    lines.extend(errors.iter().take(MAX_REPORTED_ERRORS).cloned());
+    if errors.len() > MAX_REPORTED_ERRORS {
+        lines.push(i18n::t(locale, "import.more_errors", &[("count", &(errors.len() - MAX_REPORTED_ERRORS))]));
+    }
+    lines.join("\n")
+}
+
+// Dos operaciones iguales (misma fecha, tipo, par, cantidad, precio y comisión) se consideran la misma,
+// para que importar dos veces el mismo fichero no duplique nada. Las fechas se guardan en segundos,
+// así que las del fichero se truncan igual antes de comparar.
+fn same_trade(a: &NewTrade, b: &Trade) -> bool {
+    a.time.trunc_subsecs(0) == b.time.trunc_subsecs(0) && a.kind == b.kind && a.pair == b.pair && a.quantity == b.quantity && a.price == b.price && a.fee == b.fee
+}
+
+// Valida el fichero, comprueba que el registro sigue cuadrando con las operaciones nuevas y las guarda
+pub async fn import_csv(prices: &PriceService, storage: &dyn Storage, user_id: UserId, locale: Locale, data: &[u8]) -> String {
+    let (format, rows) = match read_rows(locale, data) {
+        Ok(rows) => rows,
+        Err(errors) => return errors_text(locale, &errors),
+    };
+
+    // Los símbolos sin separar ("BTCUSDT") se resuelven con los pares que cotizan los proveedores
+    let mut symbols: HashMap<String, Option<Pair>> = HashMap::new();
+    for row in &rows {
+        if let Market::Symbol(symbol) = &row.market {
+            if !symbols.contains_key(symbol) {
+                let pair = match prices.resolve(symbol).await {
+                    Ok(pair) => Some(pair),
+                    Err(err) if err.is_invalid_symbol() => None,
+                    Err(err) => return i18n::t(locale, "import.check_pairs_failed", &[("error", &err.user_message(locale))]),
+                };
+                symbols.insert(symbol.clone(), pair);
+            }
+        }
+    }
+
+    let existing = match storage.trades(user_id) {
+        Ok(trades) => trades,
+        Err(err) => {
+            log::error!("Could not load trades of user {}: {}", user_id, err);
+            return i18n::t(locale, "trade.load_failed", &[]);
+        }
+    };
+
+    // Filas convertidas en operaciones; una comisión en el activo base se registra como operación aparte
+    let mut errors = Vec::new();
+    let mut new_trades: Vec<(u64, NewTrade)> = Vec::new();
+    let mut ignored_fees: BTreeMap<String, usize> = BTreeMap::new();
+    let mut duplicates = 0;
+    for row in rows {
+        let pair = match &row.market {
+            Market::Pair(pair) => pair.clone(),
+            Market::Symbol(symbol) => match &symbols[symbol] {
+                Some(pair) => pair.clone(),
+                None => {
+                    errors.push(line_error(locale, row.line, &i18n::t(locale, "import.unknown_pair", &[("symbol", symbol)])));
+                    continue;
+                }
+            },
+        };
+        let mut trade = NewTrade { time: row.time, kind: row.kind, pair: pair.clone(), quantity: row.quantity, price: row.price, fee: Decimal::ZERO };
+        let mut base_fee = None;
+        match row.fee {
+            Some((fee, _)) if fee.is_zero() => {}
+            Some((fee, asset)) if asset == pair.quote => trade.fee = fee,
+            Some((fee, asset)) if asset == pair.base => base_fee = Some(fee),
+            Some((_, asset)) => *ignored_fees.entry(asset).or_default() += 1,
+            None => {}
+        }
+        if existing.iter().any(|old| same_trade(&trade, old)) {
+            duplicates += 1;
+            continue;
+        }
+        new_trades.push((row.line, trade));
+        if let Some(fee) = base_fee {
+            new_trades.push((row.line, NewTrade { time: row.time, kind: TradeKind::Fee, pair, quantity: fee, price: Decimal::ZERO, fee: Decimal::ZERO }));
+        }
+    }
+    if !errors.is_empty() {
+        return errors_text(locale, &errors);
+    }
+    if new_trades.is_empty() {
+        return i18n::t(locale, "import.nothing_new", &[("count", &duplicates)]);
+    }
+
+    // Se recalcula el registro completo para detectar ventas de más; las operaciones nuevas llevan ids
+    // provisionales por encima de los reales para saber de qué línea viene cada error
+    let first_new_id = existing.iter().map(|trade| trade.id).max().unwrap_or_default() + 1;
+    let mut all_trades = existing.clone();
+    all_trades.extend(new_trades.iter().enumerate().map(|(index, (_, trade))| Trade {
+        id: first_new_id + index as u64,
+        time: trade.time,
+        kind: trade.kind,
+        pair: trade.pair.clone(),
+        quantity: trade.quantity,
+        price: trade.price,
+        fee: trade.fee,
+    }));
+    let line_of = |trade_id: u64| trade_id.checked_sub(first_new_id).map(|index| new_trades[index as usize].0);
+    match gains::compute(&all_trades, cost_method(storage, user_id)) {
+        Ok(_) => {}
+        Err(GainsError::Oversold { trade_id, pair, available, requested }) => {
+            let error = i18n::t(locale, "import.oversold", &[
+                ("requested", &i18n::amount(locale, requested)),
+                ("base", &pair.base),
+                ("available", &i18n::amount(locale, available)),
+            ]);
+            return errors_text(locale, &[match line_of(trade_id) {
+                Some(line) => line_error(locale, line, &error),
+                None => i18n::t(locale, "import.existing", &[("id", &trade_id), ("error", &error)]),
+            }]);
+        }
+        Err(err) => return errors_text(locale, &[gains_error(locale, &err)]),
+    }
+
+    let count = new_trades.len();
+    if let Err(err) = storage.insert_trades(user_id, new_trades.into_iter().map(|(_, trade)| trade).collect()) {
+        log::error!("Could not import trades of user {}: {}", user_id, err);
+        return i18n::t(locale, "import.save_failed", &[]);
+    }
+    log::info!("User {} imported {} trades ({:?} format)", user_id, count, format);
+
+    let mut lines = vec![i18n::tn(locale, "import.imported", count, &[("format", &i18n::t(locale, format.name_key(), &[]))])];
+    if duplicates > 0 {
+        lines.push(i18n::tn(locale, "import.skipped", duplicates, &[]));
+    }
+    for (asset, rows) in ignored_fees {
+        lines.push(i18n::tn(locale, "import.ignored_fees", rows, &[("asset", &asset)]));
+    }
+    lines.join("\n")
+}
+
+// Operaciones del usuario en CSV, o None si no tiene ninguna
+pub fn export_for(storage: &dyn Storage, user_id: UserId) -> StorageResult<Option<Vec<u8>>> {
+    let trades = storage.trades(user_id)?;
+    if trades.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(export_csv(&trades)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::cache::PriceCache;
+    use crate::prices::FailoverSettings;
+    use crate::storage::MemoryStorage;
+
+    #[test]
+    fn generic_numbers_reject_commas() {
+        assert_eq!(parse_number(Locale::En, " 0.5 ", "quantity"), Ok(Decimal::from_str("0.5").unwrap()));
+        assert!(parse_number(Locale::En, "0,5", "quantity").is_err());
+        assert!(parse_number(Locale::En, "1,000", "quantity").is_err());
+        assert!(parse_number(Locale::En, "-1", "quantity").is_err());
+    }
+
+    #[test]
+    fn binance_numbers_strip_only_thousands_separators() {
+        assert_eq!(parse_binance_number(Locale::En, "21,000.5", "price"), Ok(Decimal::from_str("21000.5").unwrap()));
+        assert_eq!(parse_binance_number(Locale::En, "1,234,567", "price"), Ok(Decimal::from(1_234_567)));
+        for ambiguous in ["0,5", "1,0000", ",500", "12,34.5", "1.000,5"] {
+            assert!(parse_binance_number(Locale::En, ambiguous, "price").is_err(), "{} was accepted", ambiguous);
+        }
+        assert_eq!(parse_amount_with_asset(Locale::En, "1,500.25USDT", "fee"), Ok((Decimal::from_str("1500.25").unwrap(), "USDT".to_string())));
+        assert!(parse_amount_with_asset(Locale::En, "0,5BTC", "fee").is_err());
+    }
+
+    fn test_prices() -> PriceService {
+        let settings = FailoverSettings { timeout: Duration::from_secs(1), failure_threshold: 1, cooldown: Duration::from_secs(1) };
+        PriceService::new(Vec::new(), settings, "USDT", Decimal::TWO, PriceCache::new(Duration::ZERO))
+    }
+
+    #[tokio::test]
+    async fn importing_the_same_file_twice_adds_nothing() {
+        let prices = test_prices();
+        let storage = MemoryStorage::default();
+        let user = UserId(42);
+        // Fechas con milisegundos, que se guardan truncadas a segundos
+        let data = b"date,type,base,quote,quantity,price,fee
+\
+            2024-01-01T10:00:00.250Z,buy,BTC,USDT,1,40000,4
+\
+            2024-01-02T12:30:15.999Z,sell,BTC,USDT,0.5,42000,2.1
+";
+        assert!(import_csv(&prices, &storage, user, Locale::En, data).await.starts_with("Imported 2 trades"));
+        let reply = import_csv(&prices, &storage, user, Locale::En, data).await;
+        assert_eq!(reply, "Nothing to import, all 2 trades of the file are already in your ledger.");
+        assert_eq!(storage.trades(user).unwrap().len(), 2);
+
+        // El fichero exportado también se reconoce como ya importado
+        let exported = export_for(&storage, user).unwrap().unwrap();
+        assert!(import_csv(&prices, &storage, user, Locale::En, &exported).await.starts_with("Nothing to import"));
+    }
+
+    #[test]
+    fn ambiguous_commas_are_line_errors() {
+        let data = b"date,type,base,quote,quantity,price,fee\n2024-01-01,buy,BTC,USDT,\"0,5\",40000,0\n";
+        let Err(errors) = read_rows(Locale::En, data) else { panic!("the file was accepted") };
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("Line 2: '0,5' is not a valid quantity"), "{}", errors[0]);
+    }
+}
@@ -0,0 +1,129 @@
+// Listas de seguimiento por chat: "/watch add btc eth", "/watch remove eth" y "/watchlist",
+// que responde con una tabla de precios y variación de 24 horas con un botón para refrescarla.
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup};
+use teloxide::prelude::*;
+
+use crate::i18n::{self, Locale};
+use crate::prices::PriceService;
+use crate::providers::Pair;
+use crate::storage::Storage;
+
+// Callback data del botón "Refresh" de la tabla
+pub const REFRESH_WATCHLIST: &str = "watchlist_refresh";
+// Número máximo de pares por chat, para que la tabla quepa en un mensaje y no agote el límite del API
+const MAX_WATCHLIST_LEN: usize = 25;
+
+// Procesa "/watch add <símbolos...>" y "/watch remove <símbolos...>" y devuelve el texto de respuesta
+pub async fn watch_command(prices: &PriceService, storage: &dyn Storage, chat_id: ChatId, locale: Locale, input: &str) -> String {
+    let mut args = input.split_whitespace();
+    let action = args.next().map(|a| a.to_lowercase());
+    let symbols: Vec<&str> = args.collect();
+    if symbols.is_empty() {
+        return i18n::t(locale, "watch.usage", &[]);
+    }
+
+    let current = match storage.watchlist(chat_id) {
+        Ok(list) => list,
+        Err(err) => {
+            log::error!("Could not load watchlist of chat {}: {}", chat_id, err);
+            return i18n::t(locale, "watchlist.load_failed", &[]);
+        }
+    };
+
+    let mut lines = Vec::new();
+    match action.as_deref() {
+        Some("add") => {
+            let mut len = current.len();
+            for symbol in symbols {
+                let pair = match prices.resolve(symbol).await {
+                    Ok(pair) => pair,
+                    Err(err) => {
+                        lines.push(format!("{}: {}", symbol, err.user_message(locale)));
+                        continue;
+                    }
+                };
+                if len >= MAX_WATCHLIST_LEN {
+                    lines.push(i18n::t(locale, "watch.full", &[("pair", &pair), ("max", &MAX_WATCHLIST_LEN)]));
+                    continue;
+                }
+                match storage.add_to_watchlist(chat_id, &pair) {
+                    Ok(true) => {
+                        len += 1;
+                        lines.push(i18n::t(locale, "watch.added", &[("pair", &pair)]));
+                    }
+                    Ok(false) => lines.push(i18n::t(locale, "watch.already", &[("pair", &pair)])),
+                    Err(err) => {
+                        log::error!("Could not add {} to watchlist of chat {}: {}", pair, chat_id, err);
+                        lines.push(i18n::t(locale, "watch.save_failed", &[("pair", &pair)]));
+                    }
+                }
+            }
+        }
+        Some("remove") => {
+            for symbol in symbols {
+                // Se busca en la lista del chat sin consultar al exchange, por si el par ya no cotiza
+                let Some(pair) = find_in_watchlist(&current, symbol) else {
+                    lines.push(i18n::t(locale, "watch.not_found", &[("symbol", &symbol)]));
+                    continue;
+                };
+                match storage.remove_from_watchlist(chat_id, pair) {
+                    Ok(_) => lines.push(i18n::t(locale, "watch.removed", &[("pair", pair)])),
+                    Err(err) => {
+                        log::error!("Could not remove {} from watchlist of chat {}: {}", pair, chat_id, err);
+                        lines.push(i18n::t(locale, "watch.remove_failed", &[("pair", pair)]));
+                    }
+                }
+            }
+        }
+        _ => return i18n::t(locale, "watch.usage", &[]),
+    }
+    lines.join("\n")
+}
+
+// Busca un símbolo escrito por el usuario ("sol", "SOLUSDT", "sol/usdt") en la lista del chat
+fn find_in_watchlist<'a>(list: &'a [Pair], symbol: &str) -> Option<&'a Pair> {
+    let normalized: String = symbol.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_uppercase();
+    list.iter().find(|p| format!("{}{}", p.base, p.quote) == normalized)
+        .or_else(|| list.iter().find(|p| p.base == normalized))
+}
+
+// Tabla con el precio y la variación de 24 horas de cada par, consultados a la vez.
+// Se devuelve en HTML para que Telegram la muestre con fuente monoespaciada.
+pub async fn watchlist_table(prices: &PriceService, pairs: &[Pair], locale: Locale) -> String {
+    if pairs.is_empty() {
+        return i18n::t(locale, "watchlist.empty", &[]);
+    }
+
+    let tickers = futures_util::future::join_all(pairs.iter().map(|pair| prices.ticker_24h(pair))).await;
+    let symbol_header = i18n::t(locale, "table.symbol", &[]);
+    let symbol_width = pairs.iter().map(|p| p.to_string().len()).max().unwrap_or(0).max(symbol_header.chars().count());
+    let mut rows = vec![format!("{:<width$}  {:>14}  {:>8}", symbol_header, i18n::t(locale, "table.price", &[]),
+        i18n::t(locale, "table.change", &[]), width = symbol_width)];
+    let mut sources = Vec::new();
+    for (pair, ticker) in pairs.iter().zip(tickers) {
+        match ticker {
+            Ok(quote) => {
+                if !sources.contains(&quote.source) {
+                    sources.push(quote.source);
+                }
+                rows.push(format!("{:<width$}  {:>14}  {:>7}%", quote.pair.to_string(), i18n::price(locale, quote.ticker.last),
+                    i18n::number(locale, quote.ticker.change_percent, 2, true), width = symbol_width));
+            }
+            Err(err) => {
+                log::warn!("Watchlist could not fetch {}: {}", pair, err);
+                rows.push(format!("{:<width$}  {:>14}  {:>8}", pair.to_string(), i18n::t(locale, "table.unavailable", &[]), "",
+                    width = symbol_width));
+            }
+        }
+    }
+    let sources = if sources.is_empty() { "-".to_string() } else { sources.join(", ") };
+    format!("<pre>{}</pre>\n{}", rows.join("\n"), i18n::t(locale, "watchlist.source", &[("sources", &sources)]))
+}
+
+pub fn watchlist_keyboard(locale: Locale) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::default()
+        .append_row(vec![InlineKeyboardButton::callback(i18n::t(locale, "watchlist.refresh", &[]), REFRESH_WATCHLIST)])
+}
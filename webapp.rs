@@ -0,0 +1,342 @@
+// Web App de Telegram servida por el propio bot: una página con los precios de la lista de seguimiento
+// y sus gráficos, y un API JSON detrás que usa el mismo servicio de precios que los comandos. Cada
+// petición al API lleva el initData que Telegram entrega a la página, firmado con el token del bot,
+// y así se sabe qué usuario la hace sin ningún login propio.
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::{Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use serde_json::json;
+use sha2::Sha256;
+use teloxide::types::ChatId;
+
+use crate::chart;
+use crate::config::env_or;
+use crate::i18n::{self, Locale};
+use crate::prices::PriceService;
+use crate::providers::BinanceProvider;
+use crate::storage::Storage;
+
+type HmacSha256 = Hmac<Sha256>;
+
+// Página de la Web App; el JavaScript pide los datos al API de este mismo servidor
+const PAGE: &str = include_str!("webapp.html");
+// Desfase tolerado entre el reloj de Telegram y el del servidor para un auth_date en el futuro
+const MAX_CLOCK_SKEW: Duration = Duration::from_secs(60);
+
+pub struct WebAppSettings {
+    pub address: SocketAddr,
+    // Antigüedad máxima del initData; pasado ese tiempo hay que volver a abrir la Web App
+    pub max_auth_age: Duration,
+}
+
+impl WebAppSettings {
+    // WEBAPP_ADDR (p. ej. 0.0.0.0:8080) activa el servidor; WEBAPP_AUTH_MAX_AGE_SECS (por defecto un día)
+    // limita la antigüedad del initData. La URL pública (WEBAPP_URL) es la que abre el botón de menú.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let Ok(address) = std::env::var("WEBAPP_ADDR") else {
+            return Ok(None);
+        };
+        let address = address.trim().parse().map_err(|err| format!("invalid WEBAPP_ADDR '{}': {}", address, err))?;
+        Ok(Some(WebAppSettings {
+            address,
+            max_auth_age: Duration::from_secs(env_or("WEBAPP_AUTH_MAX_AGE_SECS", 24 * 60 * 60)),
+        }))
+    }
+}
+
+// Servicios que comparten las rutas del servidor
+struct AppState {
+    prices: Arc<PriceService>,
+    storage: Arc<dyn Storage>,
+    charts: Arc<BinanceProvider>,
+    bot_token: String,
+    max_auth_age: Duration,
+}
+
+// Usuario de Telegram que ha abierto la Web App, tal como viene en el campo "user" del initData
+#[derive(Deserialize, Debug)]
+pub struct WebAppUser {
+    pub id: u64,
+    pub first_name: String,
+    #[serde(default)]
+    pub language_code: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum InitDataError {
+    Missing,
+    Malformed,
+    BadSignature,
+    Expired,
+    FromFuture,
+}
+
+impl std::fmt::Display for InitDataError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InitDataError::Missing => write!(f, "missing Telegram init data"),
+            InitDataError::Malformed => write!(f, "malformed Telegram init data"),
+            InitDataError::BadSignature => write!(f, "invalid Telegram init data signature"),
+            InitDataError::Expired => write!(f, "expired Telegram init data, please reopen the web app"),
+            InitDataError::FromFuture => write!(f, "Telegram init data is dated in the future, check the clock of your device"),
+        }
+    }
+}
+
+impl IntoResponse for InitDataError {
+    fn into_response(self) -> Response {
+        error(StatusCode::UNAUTHORIZED, self.to_string())
+    }
+}
+
+// Comprueba el initData según la documentación de Telegram: el hash es el HMAC-SHA256 de los demás
+// campos ordenados ("clave=valor" separados por saltos de línea) con la clave HMAC-SHA256("WebAppData", token)
+pub fn validate_init_data(init_data: &str, bot_token: &str, max_age: Duration, now: DateTime<Utc>) -> Result<WebAppUser, InitDataError> {
+    let mut fields: Vec<(String, String)> = serde_urlencoded::from_str(init_data).map_err(|_| InitDataError::Malformed)?;
+    let hash = match fields.iter().position(|(key, _)| key == "hash") {
+        Some(index) => fields.remove(index).1,
+        None => return Err(InitDataError::Missing),
+    };
+    fields.sort_by(|a, b| a.0.cmp(&b.0));
+    let check_string = fields.iter().map(|(key, value)| format!("{}={}", key, value)).collect::<Vec<_>>().join("\n");
+
+    let mut secret = HmacSha256::new_from_slice(b"WebAppData").expect("HMAC accepts any key length");
+    secret.update(bot_token.as_bytes());
+    let mut mac = HmacSha256::new_from_slice(&secret.finalize().into_bytes()).expect("HMAC accepts any key length");
+    mac.update(check_string.as_bytes());
+    let expected = decode_hex(&hash).ok_or(InitDataError::BadSignature)?;
+    // verify_slice compara en tiempo constante
+    mac.verify_slice(&expected).map_err(|_| InitDataError::BadSignature)?;
+
+    let field = |name: &str| fields.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str());
+    let auth_date = field("auth_date")
+        .and_then(|date| date.parse::<i64>().ok())
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+        .ok_or(InitDataError::Malformed)?;
+    // Una fecha posterior a la actual solo se acepta dentro del margen de desfase entre relojes
+    match (now - auth_date).to_std() {
+        Ok(age) if age > max_age => return Err(InitDataError::Expired),
+        Err(_) if (auth_date - now).to_std().is_ok_and(|ahead| ahead > MAX_CLOCK_SKEW) => return Err(InitDataError::FromFuture),
+        _ => {}
+    }
+    serde_json::from_str(field("user").ok_or(InitDataError::Malformed)?).map_err(|_| InitDataError::Malformed)
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok()).collect()
+}
+
+// Error del API: código HTTP y mensaje para la página
+fn error(status: StatusCode, message: impl Into<String>) -> Response {
+    (status, Json(json!({ "error": message.into() }))).into_response()
+}
+
+// El initData viaja en la cabecera "Authorization: tma <initData>"
+fn authenticate(state: &AppState, headers: &HeaderMap) -> Result<WebAppUser, InitDataError> {
+    let init_data = headers.get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("tma "));
+    let result = match init_data {
+        Some(init_data) => validate_init_data(init_data, &state.bot_token, state.max_auth_age, Utc::now()),
+        None => Err(InitDataError::Missing),
+    };
+    if let Err(err) = &result {
+        log::debug!("Web app request rejected: {}", err);
+    }
+    result
+}
+
+// Idioma de las respuestas: el elegido con /lang en el chat privado o el de la app del usuario
+fn user_locale(state: &AppState, user: &WebAppUser) -> Locale {
+    i18n::locale_for(state.storage.as_ref(), ChatId(user.id as i64), user.language_code.as_deref())
+}
+
+async fn page() -> Html<&'static str> {
+    Html(PAGE)
+}
+
+// Lista de seguimiento del chat privado del usuario con precio y variación de 24 horas
+async fn watchlist(State(state): State<Arc<AppState>>, headers: HeaderMap) -> Response {
+    let user = match authenticate(&state, &headers) {
+        Ok(user) => user,
+        Err(err) => return err.into_response(),
+    };
+    // En un chat privado el id del chat es el del usuario
+    let chat_id = ChatId(user.id as i64);
+    let locale = user_locale(&state, &user);
+    let pairs = match state.storage.watchlist(chat_id) {
+        Ok(pairs) => pairs,
+        Err(err) => {
+            log::error!("Could not load watchlist of chat {}: {}", chat_id, err);
+            return error(StatusCode::INTERNAL_SERVER_ERROR, i18n::t(locale, "watchlist.load_failed", &[]));
+        }
+    };
+    let tickers = futures_util::future::join_all(pairs.iter().map(|pair| state.prices.ticker_24h(pair))).await;
+    let rows: Vec<_> = pairs.iter().zip(tickers).map(|(pair, ticker)| match ticker {
+        Ok(quote) => json!({
+            "pair": pair.to_string(),
+            "last": quote.ticker.last.normalize().to_string(),
+            "change_percent": quote.ticker.change_percent.round_dp(2).to_string(),
+            "high": quote.ticker.high.normalize().to_string(),
+            "low": quote.ticker.low.normalize().to_string(),
+            "source": quote.source,
+        }),
+        Err(err) => json!({
+            "pair": pair.to_string(),
+            "error": err.user_message(locale),
+        }),
+    }).collect();
+    Json(json!({ "user": { "id": user.id, "first_name": user.first_name }, "pairs": rows })).into_response()
+}
+
+#[derive(Deserialize)]
+struct PriceParams {
+    symbol: String,
+}
+
+// Precio actual de un símbolo, resuelto igual que en /price
+async fn price(State(state): State<Arc<AppState>>, headers: HeaderMap, Query(params): Query<PriceParams>) -> Response {
+    let locale = match authenticate(&state, &headers) {
+        Ok(user) => user_locale(&state, &user),
+        Err(err) => return err.into_response(),
+    };
+    let pair = match state.prices.resolve(&params.symbol).await {
+        Ok(pair) => pair,
+        Err(err) => return error(StatusCode::NOT_FOUND, err.user_message(locale)),
+    };
+    match state.prices.price(&pair).await {
+        Ok(quote) => Json(json!({
+            "pair": pair.to_string(),
+            "price": quote.price.normalize().to_string(),
+            "source": quote.source,
+        })).into_response(),
+        Err(err) => error(StatusCode::BAD_GATEWAY, err.user_message(locale)),
+    }
+}
+
+#[derive(Deserialize)]
+struct ChartParams {
+    symbol: String,
+    interval: Option<String>,
+    range: Option<String>,
+}
+
+// Gráfico de velas en PNG, con los mismos parámetros y el mismo dibujo que /chart
+async fn chart_png(State(state): State<Arc<AppState>>, headers: HeaderMap, Query(params): Query<ChartParams>) -> Response {
+    let locale = match authenticate(&state, &headers) {
+        Ok(user) => user_locale(&state, &user),
+        Err(err) => return err.into_response(),
+    };
+    let args = format!("{} {} {}", params.symbol,
+        params.interval.as_deref().unwrap_or("1h"), params.range.as_deref().unwrap_or("1d"));
+    let request = match chart::parse_chart_args(locale, &args) {
+        Ok(request) => request,
+        Err(message) => return error(StatusCode::BAD_REQUEST, message),
+    };
+    let pair = match state.prices.resolve(&request.symbol).await {
+        Ok(pair) => pair,
+        Err(err) => return error(StatusCode::NOT_FOUND, err.user_message(locale)),
+    };
+    let candles = match state.charts.klines(&pair, request.interval, request.candles).await {
+        Ok(candles) if !candles.is_empty() => candles,
+        Ok(_) => return error(StatusCode::NOT_FOUND, i18n::t(locale, "chart.no_data", &[("pair", &pair)])),
+        Err(err) => return error(StatusCode::BAD_GATEWAY, err.user_message(locale)),
+    };
+    match tokio::task::spawn_blocking(move || chart::render(&candles, chart::WIDTH, chart::HEIGHT)).await {
+        Ok(Ok(png)) => ([(header::CONTENT_TYPE, "image/png"), (header::CACHE_CONTROL, "no-store")], png).into_response(),
+        Ok(Err(err)) => {
+            log::error!("Could not render web app chart of {}: {}", pair, err);
+            error(StatusCode::INTERNAL_SERVER_ERROR, i18n::t(locale, "chart.render_failed", &[]))
+        }
+        Err(err) => {
+            log::error!("Web app chart rendering task failed: {}", err);
+            error(StatusCode::INTERNAL_SERVER_ERROR, i18n::t(locale, "chart.render_failed", &[]))
+        }
+    }
+}
+
+fn router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/", get(page))
+        .route("/api/watchlist", get(watchlist))
+        .route("/api/price", get(price))
+        .route("/api/chart", get(chart_png))
+        .with_state(state)
+}
+
+// Arranca el servidor de la Web App; si no puede escuchar en la dirección configurada lo anota y sigue sin él
+pub async fn serve(settings: WebAppSettings, prices: Arc<PriceService>, storage: Arc<dyn Storage>, charts: Arc<BinanceProvider>, bot_token: String) {
+    let address = settings.address;
+    let state = AppState { prices, storage, charts, bot_token, max_auth_age: settings.max_auth_age };
+    log::info!("Serving the web app on {}", address);
+    let served = match tokio::net::TcpListener::bind(address).await {
+        Ok(tcp) => axum::serve(tcp, router(Arc::new(state))).await,
+        Err(err) => Err(err),
+    };
+    if let Err(err) = served {
+        log::error!("Web app server on {} failed: {}", address, err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: &str = "123456:TEST-TOKEN";
+    const MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+    // initData firmado como lo firma Telegram, con la fecha de autenticación indicada
+    fn signed_init_data(auth_date: i64) -> String {
+        let user = r#"{"id":5000001,"first_name":"Test","language_code":"es"}"#;
+        let fields = [("auth_date", auth_date.to_string()), ("query_id", "AAE".to_string()), ("user", user.to_string())];
+        let check_string = fields.iter().map(|(key, value)| format!("{}={}", key, value)).collect::<Vec<_>>().join("\n");
+        let mut secret = HmacSha256::new_from_slice(b"WebAppData").unwrap();
+        secret.update(TOKEN.as_bytes());
+        let mut mac = HmacSha256::new_from_slice(&secret.finalize().into_bytes()).unwrap();
+        mac.update(check_string.as_bytes());
+        let hash: String = mac.finalize().into_bytes().iter().map(|byte| format!("{:02x}", byte)).collect();
+        serde_urlencoded::to_string([&fields[..], &[("hash", hash)]].concat()).unwrap()
+    }
+
+    fn validate_at(auth_date: i64, now: i64) -> Result<WebAppUser, InitDataError> {
+        validate_init_data(&signed_init_data(auth_date), TOKEN, MAX_AGE, DateTime::from_timestamp(now, 0).unwrap())
+    }
+
+    #[test]
+    fn accepts_signed_init_data_within_the_allowed_window() {
+        let now = 1_700_000_000;
+        let user = validate_at(now - 60, now).unwrap();
+        assert_eq!((user.id, user.first_name.as_str(), user.language_code.as_deref()), (5000001, "Test", Some("es")));
+        assert!(validate_at(now - MAX_AGE.as_secs() as i64, now).is_ok());
+        assert!(validate_at(now + MAX_CLOCK_SKEW.as_secs() as i64, now).is_ok());
+    }
+
+    #[test]
+    fn rejects_expired_and_future_init_data() {
+        let now = 1_700_000_000;
+        assert_eq!(validate_at(now - MAX_AGE.as_secs() as i64 - 1, now).unwrap_err(), InitDataError::Expired);
+        assert_eq!(validate_at(now + MAX_CLOCK_SKEW.as_secs() as i64 + 1, now).unwrap_err(), InitDataError::FromFuture);
+        assert_eq!(validate_at(now + 24 * 60 * 60, now).unwrap_err(), InitDataError::FromFuture);
+    }
+
+    #[test]
+    fn rejects_tampered_or_incomplete_init_data() {
+        let now = Utc::now();
+        let tampered = signed_init_data(now.timestamp()).replace("Test", "Mallory");
+        assert_eq!(validate_init_data(&tampered, TOKEN, MAX_AGE, now).unwrap_err(), InitDataError::BadSignature);
+        assert_eq!(validate_init_data(&signed_init_data(now.timestamp()), "654321:OTHER", MAX_AGE, now).unwrap_err(), InitDataError::BadSignature);
+        assert_eq!(validate_init_data("auth_date=1&user=%7B%7D", TOKEN, MAX_AGE, now).unwrap_err(), InitDataError::Missing);
+    }
+}
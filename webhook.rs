@@ -0,0 +1,157 @@
+// Modo webhook: en lugar de consultar getUpdates, Telegram envía cada actualización por HTTP
+// a un listener axum propio. Las actualizaciones llegan a los mismos handlers que en modo polling.
+use std::convert::Infallible;
+use std::net::SocketAddr;
+
+use reqwest::Url;
+use teloxide::prelude::*;
+use teloxide::update_listeners::{webhooks, UpdateListener};
+
+use crate::config::env_or;
+
+// Configuración del webhook leída del entorno
+pub struct WebhookSettings {
+    // Dirección local en la que escucha el servidor HTTP
+    pub address: SocketAddr,
+    // URL pública (HTTPS) registrada en Telegram; su ruta es también la ruta local del listener
+    pub url: Url,
+    // Valor esperado en la cabecera X-Telegram-Bot-Api-Secret-Token
+    pub secret_token: Option<String>,
+    // Si es false no se llama a setWebhook/deleteWebhook (webhook registrado aparte o pruebas locales)
+    pub setup: bool,
+}
+
+impl WebhookSettings {
+    // BOT_MODE=webhook activa el modo webhook; cualquier otro valor (o ninguno) mantiene el polling.
+    // WEBHOOK_URL es obligatoria; WEBHOOK_ADDR (por defecto 0.0.0.0:8443), WEBHOOK_SECRET_TOKEN y WEBHOOK_SETUP son opcionales.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let mode = std::env::var("BOT_MODE").unwrap_or_default();
+        if !mode.trim().eq_ignore_ascii_case("webhook") {
+            return Ok(None);
+        }
+
+        let url = std::env::var("WEBHOOK_URL").map_err(|_| "WEBHOOK_URL is required in webhook mode".to_string())?;
+        let url = Url::parse(url.trim()).map_err(|err| format!("invalid WEBHOOK_URL '{}': {}", url, err))?;
+        let address = std::env::var("WEBHOOK_ADDR").unwrap_or("0.0.0.0:8443".to_string());
+        let address = address.trim().parse().map_err(|err| format!("invalid WEBHOOK_ADDR '{}': {}", address, err))?;
+        let secret_token = std::env::var("WEBHOOK_SECRET_TOKEN").ok()
+            .map(|token| token.trim().to_string())
+            .filter(|token| !token.is_empty());
+
+        Ok(Some(WebhookSettings { address, url, secret_token, setup: env_or("WEBHOOK_SETUP", true) }))
+    }
+}
+
+// Registra el webhook (si WEBHOOK_SETUP lo permite), arranca el servidor HTTP y devuelve el update listener
+// que alimenta al dispatcher. Al parar el listener se apaga el servidor y se borra el webhook.
+pub async fn listen(bot: Bot, settings: WebhookSettings) -> ResponseResult<impl UpdateListener<Err = Infallible>> {
+    let mut options = webhooks::Options::new(settings.address, settings.url.clone());
+    if let Some(token) = settings.secret_token {
+        options = options.secret_token(token);
+    }
+
+    if settings.setup {
+        // Sin WEBHOOK_SECRET_TOKEN se genera un secreto aleatorio, que solo conocen el bot y Telegram
+        let secret_token = options.get_or_gen_secret_token().to_string();
+        bot.set_webhook(settings.url).secret_token(secret_token).await?;
+    } else if options.secret_token.is_none() {
+        log::warn!("WEBHOOK_SECRET_TOKEN is not set, incoming webhook requests are not verified");
+    }
+
+    let address = options.address;
+    log::info!("Listening for webhook updates on {}{}", address, options.path);
+    let (mut listener, stop, router) = webhooks::axum_no_setup(options);
+    let stop_token = listener.stop_token();
+
+    tokio::spawn(async move {
+        let served = match tokio::net::TcpListener::bind(address).await {
+            Ok(tcp) => axum::serve(tcp, router).with_graceful_shutdown(stop).await,
+            Err(err) => Err(err),
+        };
+        if let Err(err) = served {
+            log::error!("Webhook server on {} failed: {}", address, err);
+            stop_token.stop();
+        }
+        if settings.setup {
+            if let Err(err) = bot.delete_webhook().await {
+                log::error!("Could not delete the webhook: {}", err);
+            }
+        }
+    });
+
+    Ok(listener)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures_util::StreamExt;
+    use teloxide::update_listeners::AsUpdateStream;
+
+    use super::*;
+
+    const SECRET: &str = "s3cret-token";
+
+    // Actualización grabada de un mensaje "/price btc" en un chat privado
+    const UPDATE: &str = r#"{
+        "update_id": 100000001,
+        "message": {
+            "message_id": 42,
+            "date": 1700000000,
+            "chat": { "id": 5000001, "type": "private", "first_name": "Test" },
+            "from": { "id": 5000001, "is_bot": false, "first_name": "Test", "language_code": "en" },
+            "text": "/price btc",
+            "entities": [{ "type": "bot_command", "offset": 0, "length": 6 }]
+        }
+    }"#;
+
+    // Puerto libre en localhost para el servidor del webhook
+    async fn free_address() -> SocketAddr {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        listener.local_addr().unwrap()
+    }
+
+    // POST de la actualización con la cabecera de secreto indicada; reintenta mientras el servidor arranca
+    async fn post_update(url: &Url, secret: Option<&str>) -> reqwest::StatusCode {
+        let client = reqwest::Client::new();
+        for _ in 0..50 {
+            let mut request = client.post(url.clone())
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(UPDATE);
+            if let Some(secret) = secret {
+                request = request.header("X-Telegram-Bot-Api-Secret-Token", secret);
+            }
+            match request.send().await {
+                Ok(response) => return response.status(),
+                Err(err) if err.is_connect() => tokio::time::sleep(Duration::from_millis(20)).await,
+                Err(err) => panic!("webhook request failed: {}", err),
+            }
+        }
+        panic!("the webhook server did not start");
+    }
+
+    #[tokio::test]
+    async fn only_requests_with_the_secret_token_are_accepted() {
+        let address = free_address().await;
+        let url = Url::parse(&format!("http://{}/telegram/webhook", address)).unwrap();
+        // Sin setup no se llama al API de Telegram, así que basta con un token cualquiera
+        let settings = WebhookSettings { address, url: url.clone(), secret_token: Some(SECRET.to_string()), setup: false };
+        let mut listener = listen(Bot::new("123456:TEST"), settings).await.unwrap();
+
+        assert_eq!(post_update(&url, Some("wrong-token")).await, reqwest::StatusCode::UNAUTHORIZED);
+        assert_eq!(post_update(&url, None).await, reqwest::StatusCode::UNAUTHORIZED);
+        assert_eq!(post_update(&url, Some(SECRET)).await, reqwest::StatusCode::OK);
+
+        // Solo la petición autenticada llega al dispatcher
+        let stop = listener.stop_token();
+        let mut updates = std::pin::pin!(listener.as_stream());
+        let update = tokio::time::timeout(Duration::from_secs(5), updates.next()).await
+            .expect("no update was delivered")
+            .expect("the update stream ended")
+            .unwrap();
+        assert_eq!(update.id.0, 100000001);
+        stop.stop();
+        assert!(tokio::time::timeout(Duration::from_secs(5), updates.next()).await.unwrap().is_none());
+    }
+}